@@ -1,5 +1,4 @@
 use service::{move_request, player_move};
-use std::collections::HashMap;
 use truncate_core::{
     board::Coordinate,
     game::Game,
@@ -14,7 +13,7 @@ pub mod service {
 pub static TRUNCATE_DICT: &str = include_str!("../../dict_builder/final_wordlist.txt");
 
 pub fn init_dict() -> anyhow::Result<WordDict> {
-    let mut valid_words = HashMap::new();
+    let mut valid_words = WordDict::new();
     let lines = TRUNCATE_DICT.lines();
 
     for line in lines {
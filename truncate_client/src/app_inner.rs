@@ -2,7 +2,7 @@ use eframe::egui;
 use epaint::vec2;
 use instant::Duration;
 use truncate_core::{
-    messages::{RoomCode, TruncateToken},
+    messages::{MatchHistory, RoomCode, TruncateToken},
     npc::scoring::NPCPersonality,
     rules::GameRules,
 };
@@ -12,16 +12,22 @@ use crate::{
     handle_messages::handle_server_msg,
     lil_bits::{ChangelogSplashUI, SplashUI},
     regions::{
-        active_game::{ActiveGame, HeaderType},
+        account::{AccountAction, AccountState},
+        active_game::{ActiveGame, GameLocation, HeaderType},
         generator::GeneratorState,
         lobby::Lobby,
+        match_history::{render_match_history, MatchHistoryAction},
         native_menu::render_native_menu_if_required,
+        puzzle_editor::{PuzzleEditorAction, PuzzleEditorState},
         replayer::ReplayerState,
         single_player::SinglePlayerState,
         tutorial::TutorialState,
     },
     utils::{
+        depot::ThemeRequest,
         includes::{changelogs, ChangePriority, Tutorial},
+        locale::{ui_format, ui_text},
+        theming::ThemeChoice,
         urls::back_to_menu,
     },
 };
@@ -34,15 +40,23 @@ pub enum GameStatus {
     Generator(GeneratorState),
     Tutorial(TutorialState),
     PendingSinglePlayer(Lobby),
+    /// Building a custom puzzle to play or share
+    PuzzleEditor(PuzzleEditorState),
     SinglePlayer(SinglePlayerState),
     PendingDaily,
     PendingJoin(RoomCode),
+    /// Waiting in the matchmaking queue, possibly until a known time
+    PendingMatch(Option<u64>),
     PendingCreate,
     PendingStart(Lobby),
     Active(ActiveGame),
     Concluded(ActiveGame, u64),
     PendingReplay,
     Replay(ReplayerState),
+    /// A player's finished online games, once they have loaded
+    MatchHistory(Option<MatchHistory>),
+    /// Claiming a name and linking an email to the player's account
+    Account(AccountState),
     HardError(Vec<String>),
 }
 
@@ -68,26 +82,25 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
 
     // Block all further actions until we have a login token from the server,
     // or until the player accepts to play offline.
-    // TODO: Daily puzzle is still inaccessible offline, thus needs a similar check.
     if let (Some(waiting_for_login), None) = (&outer.started_login_at, &outer.logged_in_as) {
         if (current_time - *waiting_for_login) < Duration::from_secs(5) {
-            SplashUI::new(vec!["INITIALIZING".to_string()])
+            SplashUI::new(vec![ui_text("initializing").to_string()])
                 .animated(true)
                 .render(ui, &outer.theme, current_time, &outer.map_texture);
             return;
         } else {
             let resp = SplashUI::new(vec![
-                "COULD NOT CONNECT".to_string(),
-                "TO TRUNCATE".to_string(),
+                ui_text("could_not_connect").to_string(),
+                ui_text("to_truncate").to_string(),
             ])
             .byline(vec![
-                "Offline play may not be saved.".to_string(),
-                "Reload to try again,".to_string(),
-                "or continue to play offline.".to_string(),
+                ui_text("offline_not_saved").to_string(),
+                ui_text("reload_to_try_again").to_string(),
+                ui_text("or_play_offline").to_string(),
             ])
             .with_button(
                 "continue",
-                "CONTINUE".to_string(),
+                ui_text("continue").to_string(),
                 outer.theme.button_primary,
                 14.0,
             )
@@ -108,7 +121,7 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         .is_some_and(|c| c == "CHANGE_LOG");
 
     if !outer.unread_changelogs.is_empty() && !loading_changelog {
-        let all_changelogs = changelogs();
+        let all_changelogs = changelogs(outer.locale);
 
         // TODO: handle showing multiple changelogs
 
@@ -130,17 +143,17 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                     ChangelogSplashUI::new(splash_message.clone(), current_time)
                         .with_button(
                             "view",
-                            "VIEW SCENARIO".to_string(),
+                            ui_text("view_scenario").to_string(),
                             outer.theme.button_primary,
                         )
                         .with_button(
                             "skip",
-                            "REMIND ME LATER".to_string(),
+                            ui_text("remind_me_later").to_string(),
                             outer.theme.button_primary,
                         )
                         .with_button(
                             "ignore",
-                            "IGNORE FOREVER".to_string(),
+                            ui_text("ignore_forever").to_string(),
                             outer.theme.button_scary,
                         )
                 });
@@ -197,9 +210,9 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
 
     if loading_changelog {
         outer.event_dispatcher.event(format!("updates_listing"));
-        let mut changelog_ui = SplashUI::new(vec!["Latest updates".to_string()]);
+        let mut changelog_ui = SplashUI::new(vec![ui_text("latest_updates").to_string()]);
 
-        let mut ordered_changelogs = changelogs()
+        let mut ordered_changelogs = changelogs(outer.locale)
             .into_iter()
             .filter(|(_, log)| log.effective_day <= outer.launched_at_day)
             .collect::<Vec<_>>();
@@ -210,7 +223,7 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                 changelog_id,
                 changelog_tut
                     .changelog_name
-                    .unwrap_or_else(|| "Update".to_string()),
+                    .unwrap_or_else(|| ui_text("update").to_string()),
                 outer.theme.button_primary,
                 11.0,
             )
@@ -219,7 +232,10 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         let resp = changelog_ui.render(ui, &outer.theme, current_time, &outer.map_texture);
 
         if let Some(requested_changelog) = resp.clicked {
-            let changelog_tut = changelogs().get(requested_changelog).unwrap().clone();
+            let changelog_tut = changelogs(outer.locale)
+                .get(requested_changelog)
+                .unwrap()
+                .clone();
 
             outer
                 .event_dispatcher
@@ -294,6 +310,25 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
                 }
             }
         }
+        GameStatus::PuzzleEditor(editor) => {
+            match editor.render(ui, &outer.theme, &outer.backchannel) {
+                Some(PuzzleEditorAction::Play(puzzle)) => {
+                    outer
+                        .event_dispatcher
+                        .event("custom_puzzle_played_from_editor");
+                    new_game_status =
+                        Some(GameStatus::SinglePlayer(SinglePlayerState::new_custom(
+                            puzzle,
+                            ui.ctx(),
+                            outer.map_texture.clone(),
+                            outer.theme.clone(),
+                            outer.event_dispatcher.clone(),
+                        )));
+                }
+                Some(PuzzleEditorAction::BackToMenu) => back_to_menu(),
+                None => {}
+            }
+        }
         GameStatus::SinglePlayer(sp) => {
             // Special performance debug mode — hide the sidebar to give us more space
             if outer.log_frames {
@@ -310,17 +345,24 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             ) {
                 send(msg);
             }
+
+            // Without an account to save to, hold on to daily attempts until we can log in
+            if outer.logged_in_as.is_none() {
+                if let Some(attempt) = sp.queued_daily_attempt() {
+                    outer.offline_daily.record(attempt);
+                }
+            }
         }
         GameStatus::PendingDaily => {
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
             } else {
-                vec![format!("LOADING DAILY PUZZLE")]
+                vec![ui_text("loading_daily_puzzle").to_string()]
             })
             .animated(outer.error.is_none())
             .with_button(
                 "cancel",
-                "CANCEL".to_string(),
+                ui_text("cancel").to_string(),
                 outer.theme.button_primary,
                 14.0,
             );
@@ -335,12 +377,41 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
             } else {
-                vec![format!("JOINING {room_code}")]
+                vec![ui_format("joining_room", &[("room", room_code)])]
+            })
+            .animated(outer.error.is_none())
+            .with_button(
+                "cancel",
+                ui_text("cancel").to_string(),
+                outer.theme.button_primary,
+                14.0,
+            );
+
+            let resp = splash.render(ui, &outer.theme, current_time, &outer.map_texture);
+
+            if resp.clicked == Some("cancel") {
+                back_to_menu();
+            }
+        }
+        GameStatus::PendingMatch(gives_up_at) => {
+            let mut lines = vec![ui_text("finding_an_opponent").to_string()];
+            if let Some(gives_up_at) = gives_up_at {
+                let remaining = gives_up_at.saturating_sub(current_time.as_secs());
+                lines.push(ui_format(
+                    "or_playing_computer_in",
+                    &[("seconds", &remaining)],
+                ));
+            }
+
+            let splash = SplashUI::new(if let Some(error) = &outer.error {
+                vec![error.clone()]
+            } else {
+                lines
             })
             .animated(outer.error.is_none())
             .with_button(
                 "cancel",
-                "CANCEL".to_string(),
+                ui_text("cancel").to_string(),
                 outer.theme.button_primary,
                 14.0,
             );
@@ -348,6 +419,7 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             let resp = splash.render(ui, &outer.theme, current_time, &outer.map_texture);
 
             if resp.clicked == Some("cancel") {
+                send(PlayerMessage::CancelMatch);
                 back_to_menu();
             }
         }
@@ -355,12 +427,12 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
             } else {
-                vec!["CREATING ROOM".to_string()]
+                vec![ui_text("creating_room").to_string()]
             })
             .animated(outer.error.is_none())
             .with_button(
                 "cancel",
-                "CANCEL".to_string(),
+                ui_text("cancel").to_string(),
                 outer.theme.button_primary,
                 14.0,
             );
@@ -378,7 +450,13 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         }
         GameStatus::Active(game) => {
             if let Some(msg) = game.render(ui, current_time, None) {
-                send(msg);
+                if !matches!(game.location, GameLocation::Spectating) {
+                    send(msg);
+                } else if matches!(msg, PlayerMessage::Resign) {
+                    // Spectators have nothing to resign, so the resign button leaves the game instead
+                    send(PlayerMessage::StopSpectating);
+                    back_to_menu();
+                }
             }
         }
         GameStatus::Concluded(game, _winner) => {
@@ -390,12 +468,12 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             let splash = SplashUI::new(if let Some(error) = &outer.error {
                 vec![error.clone()]
             } else {
-                vec!["LOADING REPLAY".to_string()]
+                vec![ui_text("loading_replay").to_string()]
             })
             .animated(outer.error.is_none())
             .with_button(
                 "cancel",
-                "CANCEL".to_string(),
+                ui_text("cancel").to_string(),
                 outer.theme.button_primary,
                 14.0,
             );
@@ -409,10 +487,69 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
         GameStatus::Replay(replay) => {
             replay.render(ui, &outer.theme, current_time, &outer.backchannel);
         }
+        GameStatus::MatchHistory(None) => {
+            let splash = SplashUI::new(if let Some(error) = &outer.error {
+                vec![error.clone()]
+            } else {
+                vec![ui_text("loading_match_history").to_string()]
+            })
+            .animated(outer.error.is_none())
+            .with_button(
+                "cancel",
+                ui_text("cancel").to_string(),
+                outer.theme.button_primary,
+                14.0,
+            );
+
+            let resp = splash.render(ui, &outer.theme, current_time, &outer.map_texture);
+
+            if resp.clicked == Some("cancel") {
+                back_to_menu();
+            }
+        }
+        GameStatus::MatchHistory(Some(history)) => {
+            match render_match_history(history, ui, &outer.theme, &outer.map_texture) {
+                Some(MatchHistoryAction::Replay(game_id)) => {
+                    send(PlayerMessage::LoadReplay(game_id));
+                    new_game_status = Some(GameStatus::PendingReplay);
+                }
+                Some(MatchHistoryAction::BackToMenu) => back_to_menu(),
+                None => {}
+            }
+        }
+        GameStatus::Account(account) => {
+            let player_token = outer.logged_in_as.clone();
+            let message =
+                match account.render(ui, &outer.theme, &outer.map_texture, outer.error.as_ref()) {
+                    Some(AccountAction::ClaimName(player_name)) => {
+                        player_token.map(|player_token| PlayerMessage::ClaimName {
+                            player_token,
+                            player_name,
+                        })
+                    }
+                    Some(AccountAction::RequestLoginLink(email)) => {
+                        player_token.map(|player_token| PlayerMessage::RequestLoginLink {
+                            player_token,
+                            email,
+                        })
+                    }
+                    Some(AccountAction::BackToMenu) => {
+                        back_to_menu();
+                        None
+                    }
+                    None => None,
+                };
+
+            if let Some(message) = message {
+                outer.error = None;
+                account.status = None;
+                send(message);
+            }
+        }
         GameStatus::HardError(msg) => {
             let splash = SplashUI::new(msg.clone()).with_button(
                 "reload",
-                "RELOAD".to_string(),
+                ui_text("reload").to_string(),
                 outer.theme.button_primary,
                 14.0,
             );
@@ -423,6 +560,41 @@ pub fn render(outer: &mut OuterApplication, ui: &mut egui::Ui, current_time: Dur
             }
         }
     }
+    let theme_request = match &mut outer.game_status {
+        GameStatus::Tutorial(tutorial) => tutorial.active_game_mut(),
+        GameStatus::SinglePlayer(sp) => Some(&mut sp.active_game),
+        GameStatus::Active(game) | GameStatus::Concluded(game, _) => Some(game),
+        _ => None,
+    }
+    .and_then(|game| game.depot.ui_state.theme_request.take());
+
+    if let Some(request) = theme_request {
+        let choice = outer
+            .theme_choice
+            .unwrap_or_else(|| ThemeChoice::from_theme(&outer.theme));
+        let choice = match request {
+            ThemeRequest::NextTheme => choice.next_theme(),
+            ThemeRequest::ToggleColourBlind => choice.toggle_colour_blind(),
+        };
+        choice.save();
+        outer.theme_choice = Some(choice);
+        outer.theme = choice.theme();
+
+        let ctx = ui.ctx();
+        ctx.style_mut(|style| {
+            style.visuals.window_fill = outer.theme.water;
+            style.visuals.panel_fill = outer.theme.water;
+        });
+        match &mut outer.game_status {
+            GameStatus::Tutorial(tutorial) => tutorial.apply_theme(ctx, &outer.theme),
+            GameStatus::SinglePlayer(sp) => sp.apply_theme(ctx, &outer.theme),
+            GameStatus::Active(game) | GameStatus::Concluded(game, _) => {
+                game.apply_theme(ctx, &outer.theme)
+            }
+            _ => {}
+        }
+    }
+
     if let Some(new_game_status) = new_game_status {
         outer.game_status = new_game_status;
     }
@@ -6,10 +6,11 @@ use serde::{Deserialize, Serialize};
 type R = Receiver<GameMessage>;
 type S = Sender<PlayerMessage>;
 
-use super::utils::Theme;
+use super::utils::{theming::ThemeChoice, Theme};
 use crate::app_inner::AppInnerStorage;
-use crate::utils::daily::get_puzzle_day;
+use crate::utils::daily::{get_puzzle_day, OfflineDailyQueue};
 use crate::utils::includes::changelogs;
+use crate::utils::locale::{detect_locale, use_locale};
 use crate::utils::macros::current_time;
 use crate::{app_inner, utils::glyph_utils::Glypher};
 use eframe::egui::{self, Frame, Margin, TextureOptions};
@@ -18,7 +19,10 @@ use eframe::wasm_bindgen::JsValue;
 use epaint::{Color32, Stroke, TextureHandle};
 use truncate_core::{
     board::Board,
+    game::Game,
+    locale::Locale,
     messages::{GameMessage, PlayerMessage},
+    moves::Move,
     npc::scoring::NPCParams,
     player::Player,
     rules::GameRules,
@@ -44,6 +48,12 @@ pub enum BackchannelMsg {
         next_player: usize,
         npc_params: NPCParams,
     },
+    /// Compares a move played from the given game state against the NPC's best move
+    AnalyseMove {
+        game: Game,
+        played: Move,
+        npc_params: NPCParams,
+    },
     /// Tells the outer host to add a given word to the NPC's known dictionaries
     Remember { word: String },
     /// Tells the outer host to forget all words learned via BackchannelMsg::Remember
@@ -106,9 +116,15 @@ pub static GLYPHER: OnceLock<Glypher> = OnceLock::new();
 pub struct OuterApplication {
     pub name: String,
     pub theme: Theme,
+    /// The built-in theme last picked from the settings menu, if any
+    pub theme_choice: Option<ThemeChoice>,
+    /// The language text is shown in, and that the server judges our words in
+    pub locale: Locale,
     pub launched_at_day: u32,
     pub started_login_at: Option<Duration>,
     pub logged_in_as: Option<String>,
+    /// Daily puzzle attempts waiting to be saved once the player is logged in
+    pub offline_daily: OfflineDailyQueue,
     pub unread_changelogs: Vec<String>,
     pub inner_storage: AppInnerStorage,
     pub game_status: app_inner::GameStatus,
@@ -133,6 +149,8 @@ impl OuterApplication {
     ) -> Self {
         let mut fonts = egui::FontDefinitions::default();
         let launched_at_day = get_puzzle_day(current_time!());
+        let locale = detect_locale();
+        use_locale(locale);
 
         // Main tile font
         {
@@ -214,11 +232,12 @@ impl OuterApplication {
                         screen_height,
                         user_agent,
                         referrer,
+                        locale,
                     })
                     .unwrap();
             }
             None => {
-                let unread_changelogs: Vec<_> = changelogs()
+                let unread_changelogs: Vec<_> = changelogs(locale)
                     .iter()
                     .filter_map(|(name, changelog)| {
                         if changelog.effective_day > launched_at_day {
@@ -236,16 +255,27 @@ impl OuterApplication {
                         user_agent,
                         referrer,
                         unread_changelogs,
+                        locale,
                     })
                     .unwrap();
             }
         }
 
-        let theme = if launched_at_day >= ART_CHANGE_DAY {
+        let default_theme = if launched_at_day >= ART_CHANGE_DAY {
             Theme::day()
         } else {
             Theme::old_day()
         };
+        let theme_source = custom_theme();
+        let theme_choice = theme_source.as_deref().and_then(ThemeChoice::from_name);
+        let theme = match theme_source.map(|source| Theme::load(&source)) {
+            Some(Ok(theme)) => theme,
+            Some(Err(e)) => {
+                tracing::warn!("{e}");
+                default_theme
+            }
+            None => default_theme,
+        };
 
         {
             use egui::FontFamily;
@@ -287,9 +317,12 @@ impl OuterApplication {
         Self {
             name: player_name,
             theme,
+            theme_choice,
+            locale,
             launched_at_day,
             started_login_at: Some(current_time!()),
             logged_in_as: None,
+            offline_daily: OfflineDailyQueue::load(),
             unread_changelogs: vec![],
             game_status,
             inner_storage: AppInnerStorage::default(),
@@ -496,3 +529,22 @@ mod debug {
         }
     }
 }
+
+/// A theme chosen by the player, either as the name of a built-in theme or a YAML theme.
+/// On the web this lives in local storage, natively it is read from the file at `TR_THEME`.
+fn custom_theme() -> Option<String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        local_storage.get_item("truncate_theme").unwrap()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = std::env::var("TR_THEME").ok()?;
+        match std::fs::read_to_string(&path) {
+            Ok(source) => Some(source),
+            Err(_) => Theme::named(&path).map(|_| path),
+        }
+    }
+}
@@ -2,19 +2,27 @@ use eframe::egui;
 
 use truncate_core::{
     board::Board,
+    game::{GAME_COLOR_BLUE, GAME_COLOR_RED},
     generation::{generate_board, BoardSeed},
     messages::LobbyPlayerMessage,
     npc::scoring::NPCPersonality,
+    puzzle::CustomPuzzle,
     rules::GameRules,
 };
 
 use crate::{
     app_inner::GameStatus,
     regions::{
-        active_game::HeaderType, lobby::Lobby, single_player::SinglePlayerState,
+        account::AccountState, active_game::HeaderType, lobby::Lobby,
+        puzzle_editor::PuzzleEditorState, single_player::SinglePlayerState,
         tutorial::TutorialState,
     },
-    utils::{self, daily::get_puzzle_day, macros::current_time},
+    utils::{
+        self,
+        daily::{get_playable_daily_puzzle, get_puzzle_day},
+        locale::ui_text,
+        macros::current_time,
+    },
 };
 
 use super::OuterApplication;
@@ -35,12 +43,16 @@ pub fn handle_launch_code(
                 send_to_server(PlayerMessage::RejoinGame(token.to_string()));
                 return Some(GameStatus::PendingJoin("...".into()));
             }
-            _ => return Some(GameStatus::HardError(vec!["Could not rejoin".to_string()])),
+            _ => {
+                return Some(GameStatus::HardError(vec![
+                    ui_text("could_not_rejoin").to_string()
+                ]))
+            }
         },
         "TUTORIAL_RULES" => {
             return Some(GameStatus::Tutorial(TutorialState::new(
                 "rules".to_string(),
-                utils::includes::rules(outer.launched_at_day),
+                utils::includes::rules(outer.locale, outer.launched_at_day),
                 ui.ctx(),
                 outer.map_texture.clone(),
                 &outer.theme,
@@ -53,22 +65,23 @@ pub fn handle_launch_code(
             board.grow();
             return Some(GameStatus::PendingSinglePlayer(Lobby::new(
                 ui.ctx(),
-                "Single Player".into(),
+                ui_text("single_player").into(),
                 vec![
                     LobbyPlayerMessage {
-                        name: "You".into(),
+                        name: ui_text("player_you").into(),
                         index: 0,
-                        color: (128, 128, 255),
+                        color: GAME_COLOR_BLUE,
                     },
                     LobbyPlayerMessage {
-                        name: "Computer".into(),
+                        name: ui_text("player_computer").into(),
                         index: 1,
-                        color: (255, 80, 80),
+                        color: GAME_COLOR_RED,
                     },
                 ],
                 0,
                 board,
                 outer.map_texture.clone(),
+                &outer.theme,
             )));
         }
         "DAILY_PUZZLE" => {
@@ -77,9 +90,31 @@ pub fn handle_launch_code(
             // TODO: Handle day not matching exact_current_day
             if let Some(token) = &outer.logged_in_as {
                 send_to_server(PlayerMessage::LoadDailyPuzzle(token.clone(), day));
+                return Some(GameStatus::PendingDaily);
             }
 
-            return Some(GameStatus::PendingDaily);
+            // Offline, so generate the puzzle here and pick up where any offline attempt left off
+            outer.event_dispatcher.event("offline_daily");
+            let mut puzzle_game = get_playable_daily_puzzle(
+                ui.ctx(),
+                day,
+                &outer.map_texture,
+                &outer.theme,
+                &outer.backchannel,
+                outer.event_dispatcher.clone(),
+            );
+            if let Some(queued) = outer.offline_daily.latest_for_day(day) {
+                if let HeaderType::Summary { attempt, .. } = &mut puzzle_game.header {
+                    *attempt = Some(queued.attempt);
+                }
+                if puzzle_game.resume_moves(queued.moves(), &outer.backchannel) {
+                    puzzle_game.hints_used = queued.hints;
+                    puzzle_game.takebacks_used = queued.takebacks;
+                }
+            }
+            puzzle_game.active_game.depot.ui_state.game_header = puzzle_game.header.clone();
+
+            return Some(GameStatus::SinglePlayer(puzzle_game));
         }
         "RANDOM_PUZZLE" => {
             let seed = (current_time!().as_micros() % 243985691) as u32;
@@ -88,7 +123,7 @@ pub fn handle_launch_code(
                 .expect("Common seeds can be reasonably expected to produce a board")
                 .board;
             let header = HeaderType::Summary {
-                title: format!("Regular Puzzle"),
+                title: ui_text("regular_puzzle").to_string(),
                 attempt: None,
             };
             let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
@@ -114,7 +149,7 @@ pub fn handle_launch_code(
                 .expect("Common seeds can be reasonably expected to produce a board")
                 .board;
             let header = HeaderType::Summary {
-                title: format!("Easy Puzzle"),
+                title: ui_text("easy_puzzle").to_string(),
                 attempt: None,
             };
             let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
@@ -133,6 +168,18 @@ pub fn handle_launch_code(
             );
             return Some(GameStatus::SinglePlayer(puzzle_game));
         }
+        "PUZZLE_EDITOR" => {
+            outer.event_dispatcher.event("puzzle_editor");
+            let seed = (current_time!().as_micros() % 243985691) as u32;
+            let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
+            return Some(GameStatus::PuzzleEditor(PuzzleEditorState::new(
+                ui.ctx(),
+                outer.map_texture.clone(),
+                &outer.theme,
+                rules_generation,
+                seed,
+            )));
+        }
         "DEBUG_BEHEMOTH" => {
             let behemoth_board = Board::from_string(include_str!("../tutorials/test_board.txt"));
             let seed_for_hand_tiles = BoardSeed::new_with_generation(0, 1);
@@ -198,7 +245,7 @@ pub fn handle_launch_code(
                 .expect("Common seeds can be reasonably expected to produce a board")
                 .board;
             let header = HeaderType::Summary {
-                title: format!("Truncate Puzzle"),
+                title: ui_text("truncate_puzzle").to_string(),
                 attempt: None,
             };
             outer
@@ -220,8 +267,38 @@ pub fn handle_launch_code(
             return Some(GameStatus::SinglePlayer(puzzle_game));
         } else {
             return Some(GameStatus::HardError(vec![
-                "Sorry, that puzzle URL".to_string(),
-                "doesn't look right!".to_string(),
+                ui_text("sorry_puzzle_url").to_string(),
+                ui_text("does_not_look_right").to_string(),
+            ]));
+        }
+    }
+
+    if let Some(code) = launch_code.strip_prefix("CUSTOM:") {
+        if let Ok(puzzle) = CustomPuzzle::from_code(code) {
+            outer.event_dispatcher.event("linked_custom_puzzle");
+            return Some(GameStatus::SinglePlayer(SinglePlayerState::new_custom(
+                puzzle,
+                ui.ctx(),
+                outer.map_texture.clone(),
+                outer.theme.clone(),
+                outer.event_dispatcher.clone(),
+            )));
+        } else {
+            return Some(GameStatus::HardError(vec![
+                ui_text("sorry_puzzle_url").to_string(),
+                ui_text("does_not_look_right").to_string(),
+            ]));
+        }
+    }
+
+    if launch_code.starts_with("SPECTATE:") {
+        if let Some(code) = launch_code.split(':').nth(1) {
+            send_to_server(PlayerMessage::SpectateGame(code.to_string()));
+            return Some(GameStatus::PendingJoin(code.to_string()));
+        } else {
+            return Some(GameStatus::HardError(vec![
+                ui_text("sorry_spectating_url").to_string(),
+                ui_text("does_not_look_right").to_string(),
             ]));
         }
     }
@@ -232,10 +309,65 @@ pub fn handle_launch_code(
             return Some(GameStatus::PendingReplay);
         } else {
             return Some(GameStatus::HardError(vec![
-                "Sorry, that replay URL".to_string(),
-                "doesn't look right!".to_string(),
+                ui_text("sorry_replay_url").to_string(),
+                ui_text("does_not_look_right").to_string(),
+            ]));
+        }
+    }
+
+    if launch_code.starts_with("LOGIN:") {
+        if let Some(link_token) = launch_code.split(':').nth(1) {
+            send_to_server(PlayerMessage::RedeemLoginLink(link_token.to_string()));
+            return Some(GameStatus::Account(AccountState::new(
+                outer.name.clone(),
+                Some(ui_text("logging_in").to_string()),
+            )));
+        } else {
+            return Some(GameStatus::HardError(vec![
+                ui_text("sorry_login_link").to_string(),
+                ui_text("does_not_look_right").to_string(),
+            ]));
+        }
+    }
+
+    if launch_code == "ACCOUNT" {
+        if outer.logged_in_as.is_none() {
+            return Some(GameStatus::HardError(vec![
+                ui_text("accounts").to_string(),
+                ui_text("require_a_connection").to_string(),
             ]));
         }
+        return Some(GameStatus::Account(AccountState::new(
+            outer.name.clone(),
+            None,
+        )));
+    }
+
+    if launch_code == "FIND_MATCH" {
+        let Some(player_token) = outer.logged_in_as.clone() else {
+            return Some(GameStatus::HardError(vec![
+                ui_text("finding_a_match").to_string(),
+                ui_text("requires_a_connection").to_string(),
+            ]));
+        };
+        send_to_server(PlayerMessage::FindMatch {
+            player_token,
+            player_name: outer.name.clone(),
+            effective_day: outer.launched_at_day,
+            rules_generation: None,
+        });
+        return Some(GameStatus::PendingMatch(None));
+    }
+
+    if launch_code == "MATCH_HISTORY" {
+        let Some(player_token) = outer.logged_in_as.clone() else {
+            return Some(GameStatus::HardError(vec![
+                ui_text("match_history").to_string(),
+                ui_text("requires_a_connection").to_string(),
+            ]));
+        };
+        send_to_server(PlayerMessage::RequestMatchHistory(player_token));
+        return Some(GameStatus::MatchHistory(None));
     }
 
     // No room code means we start a new game.
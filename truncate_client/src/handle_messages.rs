@@ -1,7 +1,8 @@
 use eframe::egui;
 use truncate_core::{
     game::{self, GAME_COLOR_BLUE, GAME_COLOR_RED},
-    generation,
+    generation::{self, BoardSeed},
+    npc::scoring::NPCPersonality,
     rules::GameRules,
 };
 
@@ -11,15 +12,20 @@ use crate::{
         active_game::{ActiveGame, GameLocation, HeaderType},
         lobby::Lobby,
         replayer::ReplayerState,
+        single_player::SinglePlayerState,
     },
     utils::{
         daily::{get_playable_daily_puzzle, get_raw_daily_puzzle},
         game_evals::get_main_dict,
+        locale::{server_error_text, ui_format, ui_text},
+        macros::current_time,
     },
 };
 
 use super::OuterApplication;
-use truncate_core::messages::{GameMessage, GameStateMessage};
+use truncate_core::messages::{
+    GameMessage, GameStateMessage, MatchmakingStatus, Presence, ServerError,
+};
 
 /// Main delegator for all messages from the server to the client,
 /// both in-game and other.
@@ -33,12 +39,13 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
         match msg {
             GameMessage::Ping | GameMessage::Ack(_) | GameMessage::PleaseLogin => { /* handled at comms layer */
             }
-            GameMessage::JoinedLobby(player_index, id, players, board, token) => {
+            GameMessage::JoinedLobby(player_index, id, players, board, rule_settings, token) => {
                 // If we're already in a lobby, treat this as a lobby update
                 // (the websocket probably dropped and reconnected)
                 if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
                     if lobby.room_code.to_uppercase() == id.to_uppercase() {
                         lobby.players = players;
+                        lobby.rule_settings = Some(rule_settings);
                         lobby.update_board(board, ui);
                         continue;
                     }
@@ -59,20 +66,24 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                         .set_hash(id.to_uppercase().as_str());
                 }
 
-                outer.game_status = GameStatus::PendingStart(Lobby::new(
+                let mut lobby = Lobby::new(
                     ui.ctx(),
                     id.to_uppercase(),
                     players,
                     player_index,
                     board,
                     outer.map_texture.clone(),
-                ))
+                    &outer.theme,
+                );
+                lobby.rule_settings = Some(rule_settings);
+                outer.game_status = GameStatus::PendingStart(lobby)
             }
-            GameMessage::LobbyUpdate(_player_index, _id, players, board) => {
+            GameMessage::LobbyUpdate(_player_index, _id, players, board, rule_settings) => {
                 match &mut outer.game_status {
                     GameStatus::PendingStart(editor_state) => {
                         // TODO: Assert that this message is for the correct lobby
                         editor_state.players = players;
+                        editor_state.rule_settings = Some(rule_settings);
                         editor_state.update_board(board, ui);
                     }
                     _ => panic!("Game update hit an unknown state"),
@@ -128,15 +139,62 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     remaining_turns,
                 ));
             }
+            GameMessage::SpectatingGame(state_message) => {
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    if game.depot.gameplay.room_code.to_uppercase()
+                        == state_message.room_code.to_uppercase()
+                    {
+                        game.apply_new_state(state_message);
+                        continue;
+                    }
+                }
+
+                let GameStateMessage {
+                    room_code,
+                    players,
+                    player_number,
+                    next_player_number,
+                    board,
+                    hand,
+                    changes: _,
+                    game_ends_at,
+                    paused: _,
+                    remaining_turns,
+                } = state_message;
+
+                outer.game_status = GameStatus::Active(ActiveGame::new(
+                    ui.ctx(),
+                    room_code.to_uppercase(),
+                    None,
+                    None,
+                    players,
+                    player_number,
+                    next_player_number,
+                    board,
+                    hand,
+                    outer.map_texture.clone(),
+                    outer.theme.clone(),
+                    GameLocation::Spectating,
+                    game_ends_at,
+                    remaining_turns,
+                ));
+            }
+            GameMessage::SpectatorsUpdate(room_code, count) => {
+                if let GameStatus::PendingStart(lobby) = &mut outer.game_status {
+                    if lobby.room_code.to_uppercase() == room_code.to_uppercase() {
+                        lobby.spectators = count;
+                    }
+                }
+            }
             GameMessage::GameUpdate(state_message) => match &mut outer.game_status {
                 GameStatus::Active(game) => {
                     game.apply_new_state(state_message);
                 }
                 _ => {
                     outer.game_status = GameStatus::HardError(vec![
-                        "Game hit unknown case".into(),
-                        "Received game message".into(),
-                        "while not in a game".into(),
+                        ui_text("game_hit_unknown_case").into(),
+                        ui_text("received_game_message").into(),
+                        ui_text("while_not_in_a_game").into(),
                     ])
                 }
             },
@@ -146,9 +204,9 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 }
                 _ => {
                     outer.game_status = GameStatus::HardError(vec![
-                        "Game hit unknown case".into(),
-                        "Received game message".into(),
-                        "while not in a game".into(),
+                        ui_text("game_hit_unknown_case").into(),
+                        ui_text("received_game_message").into(),
+                        ui_text("while_not_in_a_game").into(),
                     ])
                 }
             },
@@ -173,12 +231,73 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 GameStatus::Active(game) => {
                     // assert_eq!(game.room_code, id);
                     // assert_eq!(game.player_number, num);
-                    game.depot.gameplay.error_msg = Some(err);
+                    game.depot.gameplay.error_msg = Some(err.to_string());
                 }
                 _ => {}
             },
-            GameMessage::GenericError(err) => {
-                outer.error = Some(err);
+            GameMessage::PlayerPresence(room_code, player, presence) => {
+                if let GameStatus::Active(game) = &mut outer.game_status {
+                    let gameplay = &mut game.depot.gameplay;
+                    if gameplay.room_code.to_uppercase() == room_code.to_uppercase() {
+                        match presence {
+                            Presence::Connected => {
+                                gameplay.disconnected_players.remove(&player);
+                            }
+                            Presence::Disconnected { forfeits_at } => {
+                                gameplay.disconnected_players.insert(player, forfeits_at);
+                            }
+                        }
+                    }
+                }
+            }
+            GameMessage::Matchmaking(status) => match status {
+                MatchmakingStatus::Searching { gives_up_at } => {
+                    if let GameStatus::PendingMatch(waiting_until) = &mut outer.game_status {
+                        *waiting_until = Some(gives_up_at);
+                    }
+                }
+                MatchmakingStatus::Cancelled => { /* Already back at the menu */ }
+                MatchmakingStatus::TimedOut => {
+                    if !matches!(outer.game_status, GameStatus::PendingMatch(_)) {
+                        continue;
+                    }
+
+                    // Nobody else is around, so play against the computer instead
+                    let seed = (current_time!().as_micros() % 243985691) as u32;
+                    let board_seed = BoardSeed::new(seed);
+                    let board = generation::generate_board(board_seed.clone())
+                        .expect("Common seeds can be reasonably expected to produce a board")
+                        .board;
+                    let rules_generation = GameRules::latest(Some(outer.launched_at_day)).0;
+                    outer.game_status = GameStatus::SinglePlayer(SinglePlayerState::new(
+                        "matchmaking_fallback".to_string(),
+                        ui.ctx(),
+                        outer.map_texture.clone(),
+                        outer.theme.clone(),
+                        board,
+                        Some(board_seed),
+                        rules_generation,
+                        true,
+                        HeaderType::Timers,
+                        NPCPersonality::jet(),
+                        outer.event_dispatcher.clone(),
+                    ));
+                }
+            },
+            GameMessage::Error(err) => {
+                if err == ServerError::InvalidToken {
+                    // The server won't accept this player token again,
+                    // so don't keep trying it every time the game loads.
+                    #[cfg(target_arch = "wasm32")]
+                    {
+                        let local_storage =
+                            web_sys::window().unwrap().local_storage().unwrap().unwrap();
+                        local_storage.remove_item("truncate_player_token").unwrap();
+                    }
+                    outer.logged_in_as = None;
+                }
+
+                outer.error = Some(server_error_text(&err));
             }
             GameMessage::SupplyDefinitions(definitions) => {
                 match &mut outer.game_status {
@@ -201,6 +320,7 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
             }
             GameMessage::LoggedInAs {
                 token: player_token,
+                player_name,
                 unread_changelogs,
             } => {
                 #[cfg(target_arch = "wasm32")]
@@ -212,8 +332,48 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                         .unwrap();
                 }
 
+                // Any login after the first on this connection came from a login link
+                let switched_account = outer.logged_in_as.is_some();
+
+                if let Some(player_name) = player_name {
+                    remember_name(&mut outer.name, player_name);
+                }
+                // Save any daily puzzles that were played while we couldn't reach the server
+                let queued_attempts = outer.offline_daily.persist_messages(&player_token);
+                if !queued_attempts.is_empty() {
+                    outer.event_dispatcher.event("offline_daily_synced");
+                }
+                for msg in queued_attempts {
+                    outer.tx_player.try_send(msg).unwrap();
+                }
+
                 outer.logged_in_as = Some(player_token);
                 outer.unread_changelogs = unread_changelogs;
+
+                if let GameStatus::Account(account) = &mut outer.game_status {
+                    if switched_account {
+                        account.name_input = outer.name.clone();
+                        account.status = Some(ui_text("logged_in").to_string());
+                    }
+                }
+            }
+            GameMessage::NameClaimed(player_name) => {
+                remember_name(&mut outer.name, player_name.clone());
+
+                if let GameStatus::Account(account) = &mut outer.game_status {
+                    account.status = Some(ui_format(
+                        "now_named",
+                        &[("name", &player_name.to_uppercase())],
+                    ));
+                }
+            }
+            GameMessage::LoginLinkSent(email) => {
+                if let GameStatus::Account(account) = &mut outer.game_status {
+                    account.status = Some(ui_format(
+                        "check_email_for_link",
+                        &[("email", &email.to_uppercase())],
+                    ));
+                }
             }
             GameMessage::ResumeDailyPuzzle(latest_puzzle_state, best_puzzle) => {
                 let mut puzzle_game = get_playable_daily_puzzle(
@@ -243,32 +403,23 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     puzzle_game.best_game = Some(best_game);
                 }
 
-                let unplayed_puzzle = puzzle_game.clone();
-
                 match &mut puzzle_game.header {
                     HeaderType::Summary { attempt, .. } => {
                         *attempt = Some(latest_puzzle_state.attempt as usize)
                     }
                     _ => {}
                 }
-                puzzle_game.move_sequence = latest_puzzle_state.current_moves.clone();
-
-                let delay = puzzle_game.game.rules.battle_delay;
-                puzzle_game.game.rules.battle_delay = 0;
-                for next_move in latest_puzzle_state.current_moves.into_iter() {
-                    if puzzle_game
-                        .handle_move(next_move, &outer.backchannel, false)
-                        .is_err()
-                    {
-                        puzzle_game = unplayed_puzzle;
-                        break;
-                    }
+
+                if puzzle_game.resume_moves(latest_puzzle_state.current_moves, &outer.backchannel) {
+                    puzzle_game.hints_used = latest_puzzle_state.hints;
+                    puzzle_game.takebacks_used = latest_puzzle_state.takebacks;
                 }
-                puzzle_game.game.rules.battle_delay = delay;
 
-                puzzle_game.active_game.depot.ui_state.game_header = puzzle_game.header.clone();
                 outer.game_status = GameStatus::SinglePlayer(puzzle_game);
             }
+            GameMessage::DailyMovesSaved(day, attempt) => {
+                outer.offline_daily.saved(day, attempt);
+            }
             GameMessage::DailyStats(stats) => match &mut outer.game_status {
                 GameStatus::SinglePlayer(game) => {
                     game.daily_stats = Some(stats);
@@ -290,14 +441,14 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                     GameRules::generation(rules_generation),
                 );
                 if human_starts {
-                    game.add_player("You".into());
-                    game.add_player("Computer".into());
+                    game.add_player(ui_text("player_you").into());
+                    game.add_player(ui_text("player_computer").into());
 
                     game.players[0].color = GAME_COLOR_BLUE;
                     game.players[1].color = GAME_COLOR_RED;
                 } else {
-                    game.add_player("Computer".into());
-                    game.add_player("You".into());
+                    game.add_player(ui_text("player_computer").into());
+                    game.add_player(ui_text("player_you").into());
 
                     game.players[0].color = GAME_COLOR_RED;
                     game.players[1].color = GAME_COLOR_BLUE;
@@ -319,6 +470,38 @@ pub fn handle_server_msg(outer: &mut OuterApplication, ui: &mut egui::Ui) {
                 );
                 outer.game_status = GameStatus::Replay(replayer);
             }
+            GameMessage::MatchHistory(history) => {
+                if let GameStatus::MatchHistory(loaded) = &mut outer.game_status {
+                    *loaded = Some(history);
+                }
+            }
+            GameMessage::LoadMultiplayerReplay(replay) => {
+                let mut game = replay.starting_game;
+                game.board.cache_special_squares();
+
+                let replayer = ReplayerState::new(
+                    ui.ctx(),
+                    outer.map_texture.clone(),
+                    outer.theme.clone(),
+                    game,
+                    replay.moves,
+                    replay.player_number as usize,
+                );
+                outer.game_status = GameStatus::Replay(replayer);
+            }
         }
     }
 }
+
+/// Uses a name for future games, including after the page is reloaded
+fn remember_name(name: &mut String, player_name: String) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        local_storage
+            .set_item("truncate_name_history", &player_name)
+            .unwrap();
+    }
+
+    *name = player_name;
+}
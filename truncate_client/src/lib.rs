@@ -162,6 +162,16 @@ pub fn backchannel(msg: String) -> String {
 
             return serde_json::to_string(&best).expect("Resultant move should be serializable");
         }
+        BackchannelMsg::AnalyseMove {
+            game,
+            played,
+            npc_params,
+        } => {
+            let analysis = utils::game_evals::client_analyse_move(&game, &played, &npc_params);
+
+            return serde_json::to_string(&analysis)
+                .expect("Resultant analysis should be serializable");
+        }
         BackchannelMsg::Remember { word } => {
             utils::game_evals::remember(&word);
             return String::new();
@@ -9,6 +9,7 @@ use truncate_core::reporting::{BattleReport, BattleWord};
 
 use crate::utils::{
     depot::{AestheticDepot, TruncateDepot},
+    locale::ui_text,
     tex::paint_dialog_background,
     text::TextHelper,
     Lighten,
@@ -59,9 +60,9 @@ impl<'a> BattleUI<'a> {
                 [
                     if &w.original_word == "#" || &w.original_word == "|" {
                         let label = if &w.original_word == "#" {
-                            "A TOWN"
+                            ui_text("a_town")
                         } else {
-                            "AN ARTIFACT"
+                            ui_text("an_artifact")
                         };
                         ui.painter().layout_no_wrap(
                             // TODO: It would be nice to phrase this as <player_name>'s town,
@@ -338,20 +339,26 @@ impl<'a> BattleUI<'a> {
                         )
                         .paint(aesthetics.theme.text, ui, false),
                         (Some(true), _) => TextHelper::light(
-                            "Definition not found",
+                            ui_text("definition_not_found"),
+                            24.0,
+                            Some(ui.available_width()),
+                            ui,
+                        )
+                        .paint(aesthetics.theme.text, ui, false),
+                        (Some(false), _) => TextHelper::light(
+                            ui_text("invalid_word"),
+                            24.0,
+                            Some(ui.available_width()),
+                            ui,
+                        )
+                        .paint(aesthetics.theme.text, ui, false),
+                        (None, _) => TextHelper::light(
+                            ui_text("unchecked"),
                             24.0,
                             Some(ui.available_width()),
                             ui,
                         )
                         .paint(aesthetics.theme.text, ui, false),
-                        (Some(false), _) => {
-                            TextHelper::light("Invalid word", 24.0, Some(ui.available_width()), ui)
-                                .paint(aesthetics.theme.text, ui, false)
-                        }
-                        (None, _) => {
-                            TextHelper::light("Unchecked", 24.0, Some(ui.available_width()), ui)
-                                .paint(aesthetics.theme.text, ui, false)
-                        }
                     };
 
                     ui.add_space(12.0);
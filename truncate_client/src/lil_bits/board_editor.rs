@@ -2,19 +2,21 @@ use epaint::{emath::Align, vec2, Color32, Rect, TextureHandle, Vec2};
 
 use truncate_core::{
     board::{Board, Coordinate, Square},
+    generation::Symmetry,
     messages::PlayerMessage,
 };
 
 use eframe::egui::{self, Id, Layout, Margin, RichText, Sense};
 
 use crate::{
-    regions::lobby::BoardEditingMode,
+    regions::lobby::{cycle, BoardEditingMode},
     utils::{
         depot::{AestheticDepot, TimingDepot},
+        locale::ui_text,
         mapper::MappedBoard,
         tex::{render_tex_quads, Tex, TexQuad},
         text::TextHelper,
-        Lighten, Theme,
+        Theme,
     },
 };
 
@@ -30,10 +32,180 @@ enum EditorDrag {
     RemoveArtifact(usize),
 }
 
+#[derive(Clone, Copy)]
+enum EditorTool {
+    Grow,
+    Shrink,
+    Rotate,
+    FlipHorizontal,
+    FlipVertical,
+    Undo,
+    Redo,
+    Paste,
+}
+
+const SYMMETRIES: [Symmetry; 3] = [
+    Symmetry::TwoFoldRotational,
+    Symmetry::SmoothTwoFoldRotational,
+    Symmetry::Asymmetric,
+];
+const MAX_UNDO_STEPS: usize = 100;
+
+/// Editor settings and history that need to outlive a single frame
+#[derive(Clone)]
+pub struct BoardEditorState {
+    pub symmetry: Symmetry,
+    undo: Vec<Board>,
+    redo: Vec<Board>,
+    /// A board in the `Board::from_string` format, waiting to be loaded
+    pasted_board: String,
+    paste_failed: bool,
+}
+
+impl Default for BoardEditorState {
+    fn default() -> Self {
+        Self {
+            symmetry: Symmetry::TwoFoldRotational,
+            undo: vec![],
+            redo: vec![],
+            pasted_board: String::new(),
+            paste_failed: false,
+        }
+    }
+}
+
+impl BoardEditorState {
+    /// Remembers the board as it is before an edit, so the edit can be undone
+    fn checkpoint(&mut self, board: &Board) {
+        if self.undo.last() != Some(board) {
+            self.undo.push(board.clone());
+        }
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    fn undo(&mut self, board: &mut Board) -> bool {
+        // Strokes that didn't end up changing anything still leave a checkpoint behind
+        while let Some(previous) = self.undo.pop() {
+            if previous != *board {
+                self.redo.push(std::mem::replace(board, previous));
+                return true;
+            }
+        }
+        false
+    }
+
+    fn redo(&mut self, board: &mut Board) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(std::mem::replace(board, next));
+        true
+    }
+
+    /// Runs a tool against the board, returning whether the board changed
+    fn apply(&mut self, tool: EditorTool, board: &mut Board) -> bool {
+        match tool {
+            EditorTool::Undo => return self.undo(board),
+            EditorTool::Redo => return self.redo(board),
+            EditorTool::Paste => {
+                let Ok(pasted) = Board::try_from_string(&self.pasted_board) else {
+                    self.paste_failed = true;
+                    return false;
+                };
+                self.paste_failed = false;
+                self.pasted_board.clear();
+                self.checkpoint(board);
+                *board = editable(pasted);
+                return true;
+            }
+            _ => {}
+        }
+
+        self.checkpoint(board);
+        match tool {
+            EditorTool::Grow => board.grow(),
+            EditorTool::Shrink => board.shrink(),
+            EditorTool::Rotate => board.rotate_clockwise(),
+            EditorTool::FlipHorizontal => board.flip_horizontal(),
+            EditorTool::FlipVertical => board.flip_vertical(),
+            EditorTool::Undo | EditorTool::Redo | EditorTool::Paste => unreachable!(),
+        }
+        true
+    }
+}
+
+/// The name of the UI string describing a symmetry
+fn symmetry_label(symmetry: Symmetry) -> &'static str {
+    match symmetry {
+        Symmetry::TwoFoldRotational => "symmetry_rotational",
+        Symmetry::SmoothTwoFoldRotational => "symmetry_smooth_rotational",
+        Symmetry::Asymmetric => "symmetry_none",
+    }
+}
+
+/// Strips everything the editor can't place from a board, such as played tiles and fog
+fn editable(mut board: Board) -> Board {
+    for square in board.squares.iter_mut().flatten() {
+        *square = match *square {
+            Square::Water { .. } | Square::Fog {} => Square::water(),
+            Square::Land { .. } | Square::Occupied { .. } => Square::land(),
+            Square::Obelisk { .. } => Square::obelisk(),
+            Square::Town { player, .. } => Square::town(player),
+            Square::Artifact { player, .. } => Square::artifact(player),
+        };
+    }
+    board.cache_special_squares();
+    board
+}
+
+/// Where a change to one square should be copied to under the given symmetry.
+/// Smooth symmetry only keeps the land matching, as it does when generating boards,
+/// leaving towns, artifacts and obelisks to be placed freely.
+fn mirrored_square(
+    board: &Board,
+    symmetry: Symmetry,
+    coord: Coordinate,
+    new_state: Square,
+) -> Option<(Coordinate, Square)> {
+    let recip = board.reciprocal_coordinate(coord);
+
+    match symmetry {
+        Symmetry::Asymmetric => return None,
+        Symmetry::SmoothTwoFoldRotational => {
+            let is_terrain =
+                |square: &Square| matches!(square, Square::Water { .. } | Square::Land { .. });
+            let recip_square = board.get(recip).ok()?;
+            if !is_terrain(&new_state) || !is_terrain(&recip_square) {
+                return None;
+            }
+        }
+        Symmetry::TwoFoldRotational => {}
+    }
+
+    // TODO: Player mirroring won't work for >2 players
+    let mirrored_state = match new_state {
+        Square::Water { .. } | Square::Land { .. } | Square::Obelisk { .. } => new_state,
+        Square::Town { player: p, .. } => Square::town((p + 1) % 2),
+        Square::Artifact { player: p, .. } => Square::artifact((p + 1) % 2),
+        Square::Occupied { .. } => {
+            unreachable!("Board editor should not place occupied tiles")
+        }
+        Square::Fog { .. } => {
+            unreachable!("Board editor should not place fog")
+        }
+    };
+
+    Some((recip, mirrored_state))
+}
+
 pub struct EditorUI<'a> {
     board: &'a mut Board,
     mapped_board: &'a mut MappedBoard,
     editing_mode: &'a mut BoardEditingMode,
+    editor_state: &'a mut BoardEditorState,
     player_colors: &'a Vec<Color32>,
 }
 
@@ -42,12 +214,14 @@ impl<'a> EditorUI<'a> {
         board: &'a mut Board,
         mapped_board: &'a mut MappedBoard,
         editing_mode: &'a mut BoardEditingMode,
+        editor_state: &'a mut BoardEditorState,
         player_colors: &'a Vec<Color32>,
     ) -> Self {
         Self {
             board,
             mapped_board,
             editing_mode,
+            editor_state,
             player_colors,
         }
     }
@@ -56,13 +230,12 @@ impl<'a> EditorUI<'a> {
 impl<'a> EditorUI<'a> {
     pub fn render(
         self,
-        _invert: bool, // TODO: Transpose to any rotation
         ui: &mut egui::Ui,
         theme: &Theme,
         map_texture: &TextureHandle,
     ) -> Option<PlayerMessage> {
         let mut edited = false;
-        let mut msg = None;
+        let mut tool = None;
 
         let mut highlights = [None; 5];
         match self.editing_mode {
@@ -89,7 +262,7 @@ impl<'a> EditorUI<'a> {
             };
             let pcol = |pnum: usize| self.player_colors.get(pnum).copied();
 
-            let text = TextHelper::heavy("STOP EDITING BOARD", 10.0, None, ui);
+            let text = TextHelper::heavy(ui_text("stop_editing_board"), 10.0, None, ui);
             if text
                 .button(theme.button_scary, theme.text, map_texture, ui)
                 .clicked()
@@ -97,29 +270,64 @@ impl<'a> EditorUI<'a> {
                 *self.editing_mode = BoardEditingMode::None;
             }
 
-            let text = TextHelper::heavy("GROW BOARD", 10.0, None, ui);
+            if self.editor_state.paste_failed {
+                ui.label(RichText::new(ui_text("board_paste_failed")).color(Color32::WHITE));
+            }
+            let text = TextHelper::heavy(ui_text("load_pasted_board"), 10.0, None, ui);
             if text
                 .button(Color32::WHITE, theme.text, map_texture, ui)
                 .clicked()
             {
-                self.board.grow();
-                let aesthetics = AestheticDepot {
-                    theme: theme.clone(),
-                    qs_tick: 0,
-                    map_texture: map_texture.clone(),
-                    player_colors: self.player_colors.clone(),
-                    destruction_tick: 0.0,
-                    destruction_duration: 0.0,
-                };
-                self.mapped_board.remap_texture(
-                    ui.ctx(),
-                    &aesthetics,
-                    &TimingDepot::default(),
-                    None,
-                    None,
-                    &self.board,
-                );
-                msg = Some(PlayerMessage::EditBoard(self.board.clone()));
+                tool = Some(EditorTool::Paste);
+            }
+            ui.add(
+                egui::TextEdit::multiline(&mut self.editor_state.pasted_board)
+                    .hint_text(ui_text("paste_board_here"))
+                    .desired_rows(2)
+                    .desired_width(160.0),
+            );
+
+            let text = TextHelper::heavy(ui_text("copy_board"), 10.0, None, ui);
+            if text
+                .button(Color32::WHITE, theme.text, map_texture, ui)
+                .clicked()
+            {
+                let board_text = self.board.to_string();
+                ui.ctx().output_mut(|o| o.copied_text = board_text);
+            }
+
+            ui.add_space(12.0);
+
+            let tools = [
+                ("redo", EditorTool::Redo),
+                ("undo", EditorTool::Undo),
+                ("flip_up_down", EditorTool::FlipVertical),
+                ("flip_left_right", EditorTool::FlipHorizontal),
+                ("rotate_board", EditorTool::Rotate),
+                ("shrink_board", EditorTool::Shrink),
+                ("grow_board", EditorTool::Grow),
+            ];
+            for (label, board_tool) in tools {
+                let text = TextHelper::heavy(ui_text(label), 10.0, None, ui);
+                if text
+                    .button(Color32::WHITE, theme.text, map_texture, ui)
+                    .clicked()
+                {
+                    tool = Some(board_tool);
+                }
+            }
+
+            let text = TextHelper::heavy(
+                ui_text(symmetry_label(self.editor_state.symmetry)),
+                10.0,
+                None,
+                ui,
+            );
+            if text
+                .button(Color32::WHITE, theme.text, map_texture, ui)
+                .clicked()
+            {
+                self.editor_state.symmetry = cycle(&SYMMETRIES, self.editor_state.symmetry);
             }
 
             ui.label(RichText::new("Actions").color(Color32::WHITE));
@@ -189,7 +397,7 @@ impl<'a> EditorUI<'a> {
                             let editing_mode = self.editing_mode.clone();
 
                             let response = EditorSquareUI::new()
-                                .square(square.clone())
+                                .square(*square)
                                 .action(editing_mode.clone())
                                 .render(ui, &theme, &map_texture);
 
@@ -210,7 +418,10 @@ impl<'a> EditorUI<'a> {
                                     match (drag_action, &square) {
                                         (
                                             EditorDrag::MakeLand,
-                                            Square::Water { .. } | Square::Artifact { .. },
+                                            Square::Water { .. }
+                                            | Square::Artifact { .. }
+                                            | Square::Occupied { .. }
+                                            | Square::Fog { .. },
                                         ) => modify_pos = Some((coord, Square::land())),
                                         (
                                             EditorDrag::RemoveLand,
@@ -250,6 +461,8 @@ impl<'a> EditorUI<'a> {
                                 }
                             }
                             if response.drag_started() {
+                                // Each stroke can be undone as a whole
+                                self.editor_state.checkpoint(self.board);
                                 ui.ctx().memory_mut(|mem| {
                                     mem.data.insert_temp(
                                         Id::NULL,
@@ -258,14 +471,13 @@ impl<'a> EditorUI<'a> {
                                             "With no board editing set we should not be editing"
                                         ),
                                             BoardEditingMode::Land => match square {
-                                                Square::Water { .. } | Square::Artifact { .. } => {
-                                                    EditorDrag::MakeLand
-                                                }
+                                                Square::Water { .. }
+                                                | Square::Artifact { .. }
+                                                | Square::Occupied { .. }
+                                                | Square::Fog { .. } => EditorDrag::MakeLand,
                                                 Square::Land { .. }
                                                 | Square::Town { .. }
                                                 | Square::Obelisk { .. } => EditorDrag::RemoveLand,
-                                                Square::Occupied { .. } => unreachable!(),
-                                                Square::Fog { .. } => unreachable!(),
                                             },
                                             BoardEditingMode::Town(editing_player) => {
                                                 match square {
@@ -310,45 +522,9 @@ impl<'a> EditorUI<'a> {
                 // Not bounds-checking values as they came from the above loop over this very state.
                 self.board.squares[coord.y][coord.x] = new_state;
 
-                // TODO: Put board mirroring behind a flag
+                if let Some((recip, mirrored_state)) =
+                    mirrored_square(self.board, self.editor_state.symmetry, coord, new_state)
                 {
-                    let recip = self.board.reciprocal_coordinate(coord);
-
-                    // TODO: Player mirroring won't work for >2 players
-                    let mirrored_state = match new_state {
-                        Square::Water { .. } | Square::Land { .. } | Square::Obelisk { .. } => {
-                            new_state
-                        }
-                        Square::Town { player: p, .. } => {
-                            if p == 0 {
-                                Square::Town {
-                                    player: 1,
-                                    defeated: false,
-                                    foggy: false,
-                                }
-                            } else {
-                                Square::Town {
-                                    player: 0,
-                                    defeated: false,
-                                    foggy: false,
-                                }
-                            }
-                        }
-                        Square::Artifact { player: p, .. } => {
-                            if p == 0 {
-                                Square::artifact(1)
-                            } else {
-                                Square::artifact(0)
-                            }
-                        }
-                        Square::Occupied { .. } => {
-                            unreachable!("Board editor should not contain occupied tiles")
-                        }
-                        Square::Fog { .. } => {
-                            unreachable!("Board editor should not contain fog")
-                        }
-                    };
-
                     self.board.squares[recip.y][recip.x] = mirrored_state;
                 }
 
@@ -356,10 +532,32 @@ impl<'a> EditorUI<'a> {
             }
         });
 
+        if let Some(tool) = tool {
+            if self.editor_state.apply(tool, self.board) {
+                let aesthetics = AestheticDepot {
+                    theme: theme.clone(),
+                    qs_tick: 0,
+                    map_texture: map_texture.clone(),
+                    player_colors: self.player_colors.clone(),
+                    destruction_tick: 0.0,
+                    destruction_duration: 0.0,
+                };
+                self.mapped_board.remap_texture(
+                    ui.ctx(),
+                    &aesthetics,
+                    &TimingDepot::default(),
+                    None,
+                    None,
+                    self.board,
+                );
+                edited = true;
+            }
+        }
+
         if edited {
             Some(PlayerMessage::EditBoard(self.board.clone()))
         } else {
-            msg
+            None
         }
     }
 }
@@ -14,6 +14,7 @@ use truncate_core::{
 use crate::utils::{
     depot::TruncateDepot,
     game_evals::get_main_dict,
+    locale::ui_text,
     tex::{render_tex_quad, tiles},
     text::TextHelper,
     Lighten,
@@ -217,7 +218,7 @@ impl DictionaryUI {
             depot.ui_state.dictionary_showing_definition = false;
 
             if !depot.ui_state.dictionary_focused && self.focus_in_n_frames == 0 {
-                let text = TextHelper::heavy("Search", 20.0, None, ui);
+                let text = TextHelper::heavy(ui_text("search"), 20.0, None, ui);
                 text.paint_within(
                     input.response.rect,
                     Align2::CENTER_CENTER,
@@ -12,7 +12,7 @@ mod timer;
 
 pub use battle::BattleUI;
 pub use board::BoardUI;
-pub use board_editor::EditorUI;
+pub use board_editor::{BoardEditorState, EditorUI};
 pub use board_editor_square::EditorSquareUI;
 pub use changes_splash::ChangelogSplashUI;
 pub use dictionary::DictionaryUI;
@@ -8,7 +8,7 @@ use truncate_core::{
 
 use crate::{
     app_outer::{Backchannel, ShareType},
-    utils::{depot::TruncateDepot, text::TextHelper, Theme},
+    utils::{depot::TruncateDepot, locale::ui_text, text::TextHelper, Theme},
 };
 
 use super::{msg_mock::ShareMessageMock, ResultModalAction};
@@ -65,6 +65,7 @@ impl DailyActions {
                     id: "UNAVAILABLE".to_string(),
                     moves: player_move_count,
                     won: game.winner == Some(depot.gameplay.player_number as usize),
+                    ..Default::default()
                 },
             )
         });
@@ -138,7 +139,7 @@ impl DailyActions {
         ui.allocate_ui_with_layout(ui.available_size(), Layout::bottom_up(Align::LEFT), |ui| {
             ui.add_space(ui.available_height() * 0.05);
 
-            let text = TextHelper::heavy("PLAY AGAIN", 12.0, None, ui);
+            let text = TextHelper::heavy(ui_text("play_again"), 12.0, None, ui);
             let try_again_button =
                 text.centered_button(theme.button_secondary, theme.text, map_texture, ui);
             if try_again_button.clicked() {
@@ -147,16 +148,16 @@ impl DailyActions {
 
             ui.allocate_ui_with_layout(ui.available_size(), Layout::top_down(Align::LEFT), |ui| {
                 let share_text = if self.share_copied_at.is_some() {
-                    "COPIED TEXT!"
+                    ui_text("copied_text")
                 } else {
-                    "SHARE SUMMARY"
+                    ui_text("share_summary")
                 };
                 let share_button_text = TextHelper::heavy(share_text, 12.0, None, ui);
 
                 let replay_text = if self.replay_copied_at.is_some() {
-                    "COPIED LINK!"
+                    ui_text("copied_link_excited")
                 } else {
-                    "SHARE REPLAY LINK"
+                    ui_text("share_replay_link")
                 };
                 let replay_button_text = TextHelper::heavy(replay_text, 12.0, None, ui);
 
@@ -254,7 +255,7 @@ impl DailyActions {
         ui.allocate_ui_with_layout(ui.available_size(), Layout::bottom_up(Align::LEFT), |ui| {
             ui.add_space(ui.available_height() * 0.05);
 
-            let text = TextHelper::heavy("PLAY AGAIN", 12.0, None, ui);
+            let text = TextHelper::heavy(ui_text("play_again"), 12.0, None, ui);
             let try_again_button =
                 text.centered_button(theme.button_secondary, theme.text, map_texture, ui);
             if try_again_button.clicked() {
@@ -273,7 +274,7 @@ impl DailyActions {
 
             ui.add_space(ui.available_height() * 0.05);
 
-            let text = TextHelper::heavy("SHARE »", 12.0, None, ui);
+            let text = TextHelper::heavy(ui_text("share"), 12.0, None, ui);
             let share_buton =
                 text.centered_button(theme.button_primary, theme.text, map_texture, ui);
 
@@ -1,106 +1,142 @@
-use eframe::egui::{self};
+use eframe::egui::{self, Align, Layout, Sense};
 use epaint::{
-    emath::Align2, hex_color, pos2, textures::TextureOptions, vec2, Color32, ColorImage, Mesh,
-    Rect, Shape, Stroke, TextureHandle,
+    emath::Align2, pos2, textures::TextureOptions, vec2, Color32, ColorImage, Mesh, Rect, Shape,
+    Stroke, TextureHandle,
 };
 use instant::Duration;
-use truncate_core::messages::DailyStats;
+use truncate_core::messages::{DailyAttempt, DailyStats};
 
-use crate::utils::text::TextHelper;
-
-/*
+use crate::utils::{
+    daily::{get_puzzle_day, DAILY_PUZZLE_DAY_ZERO},
+    locale::{ui_format, ui_text},
+    text::TextHelper,
+    Theme,
+};
 
-TODOs for the splash graph:
-- Pull all of the colours from a central theme once we refactor general theming
-- Add interaction to tap on a day and see the notes label
-- Once the graph grows larger, we will need to only show a subset of it
-  - And thus we will need to implement some horizontal scrolling and zooming
+use super::ResultModalAction;
 
- */
+/// How many days the graph shows before the player zooms in or out
+const DEFAULT_VISIBLE_DAYS: f32 = 30.0;
+/// The furthest the player can zoom in
+const MIN_VISIBLE_DAYS: f32 = 7.0;
+/// Only the most recent attempts on a day fit below the graph
+const MAX_LISTED_ATTEMPTS: usize = 6;
+/// The fewest days the graph spans, so new players still see a bit of history
+const MIN_GRAPHED_DAYS: usize = 10;
+/// Caps the graph textures, which have a column per day, if stats reach back too far
+const MAX_GRAPHED_DAYS: usize = 1000;
 
 #[derive(Clone)]
-struct DayHighlight {
-    day_from_right: usize,
-    label: String,
-    bar_height: f32,
+struct GraphDay {
+    puzzle_day: i64,
+    attempts: Vec<DailyAttempt>,
+}
+
+impl GraphDay {
+    fn best_win(&self) -> Option<&DailyAttempt> {
+        self.attempts
+            .iter()
+            .filter(|a| a.won)
+            .min_by_key(|a| a.moves)
+    }
+
+    /// How many moves tall this day's bar is on the graph
+    fn total_moves(&self) -> usize {
+        if let Some(best_win) = self.best_win() {
+            return best_win.moves as usize;
+        };
+
+        self.attempts.iter().map(|a| a.moves as usize).sum()
+    }
+
+    fn date(&self) -> time::Date {
+        let date = time::OffsetDateTime::UNIX_EPOCH
+            + time::Duration::days(DAILY_PUZZLE_DAY_ZERO as i64 + self.puzzle_day);
+        date.date()
+    }
 }
 
 #[derive(Clone)]
 pub struct DailySplashGraph {
     moves_graph_texture: TextureHandle,
     streak_graph_texture: TextureHandle,
-    date_labels: (String, String),
-    highlighted_day: Option<DayHighlight>,
-    days_played: usize,
+    /// One entry per column of the graph textures, oldest first
+    days: Vec<GraphDay>,
+    max_total_moves: usize,
+    today: i64,
+    selected_day: usize,
+    /// How many columns currently fit across the graph
+    visible_days: f32,
+    /// The column position of the graph's right edge, counted from the oldest day
+    view_end: f32,
 }
 
 impl DailySplashGraph {
-    pub fn new(ui: &mut egui::Ui, stats: &DailyStats, current_time: Duration) -> Self {
-        let days_played = stats.days.len().clamp(10, 1000);
-        let max_total_moves = stats
-            .days
-            .values()
-            .map(|day| {
-                if let Some(best_win) = day
-                    .attempts
-                    .iter()
-                    .filter(|a| a.won)
-                    .min_by_key(|a| a.moves)
-                {
-                    return best_win.moves as usize;
-                };
-
-                day.attempts.iter().map(|a| a.moves as usize).sum()
+    pub fn new(
+        ui: &mut egui::Ui,
+        stats: &DailyStats,
+        theme: &Theme,
+        current_time: Duration,
+    ) -> Self {
+        let today = get_puzzle_day(current_time) as i64;
+        let first_day = stats.days.keys().next().map(|d| *d as i64).unwrap_or(today);
+        let last_day = stats.days.keys().last().map(|d| *d as i64).unwrap_or(today);
+        let days_played =
+            ((last_day - first_day + 1).max(0) as usize).clamp(MIN_GRAPHED_DAYS, MAX_GRAPHED_DAYS);
+
+        // We fill from the right since we might have made the canvas
+        // larger than the amount of days we have.
+        let days: Vec<_> = (0..days_played)
+            .map(|col| {
+                let puzzle_day = last_day - (days_played - col - 1) as i64;
+                let attempts = u32::try_from(puzzle_day)
+                    .ok()
+                    .and_then(|day| stats.days.get(&day))
+                    .map(|day| day.attempts.clone())
+                    .unwrap_or_default();
+                GraphDay {
+                    puzzle_day,
+                    attempts,
+                }
             })
+            .collect();
+
+        let max_total_moves = days
+            .iter()
+            .map(GraphDay::total_moves)
             .max()
-            .unwrap_or(30);
+            .unwrap_or_default()
+            .max(1);
 
         // Both graph images are small textures stretched to paint the graph,
-        // so we start with images with a width of the number of days to show.
+        // so we start with images with a width of the number of days we have.
 
         let mut moves_image_base =
             ColorImage::new([days_played, max_total_moves], Color32::TRANSPARENT);
-        let mut streak_image_base = ColorImage::new([days_played, 1], hex_color!("#333333"));
-
-        stats
-            .days
-            .values()
-            .rev()
-            .enumerate()
-            .for_each(|(col, day)| {
-                if days_played == col {
-                    return;
+        let mut streak_image_base = ColorImage::new([days_played, 1], theme.graph_empty);
+
+        days.iter().enumerate().for_each(|(day_pixel_index, day)| {
+            let mut current_row = max_total_moves - 1; // Start drawing each day from the bottom
+
+            if let Some(best_win) = day.best_win() {
+                for _ in 0..best_win.moves as usize {
+                    moves_image_base[(day_pixel_index, current_row)] = theme.graph_win;
+                    current_row = current_row.saturating_sub(1);
                 }
-                // We draw from the right since we might have made the canvas
-                // larger than the amount of days we have.
-                let day_pixel_index = days_played - col - 1;
-                let mut current_row = max_total_moves - 1; // Start drawing each day from the bottom
-
-                let best_win = day
-                    .attempts
-                    .iter()
-                    .filter(|a| a.won)
-                    .min_by_key(|a| a.moves);
-
-                if let Some(best_win) = best_win {
-                    for _ in 0..best_win.moves as usize {
-                        moves_image_base[(day_pixel_index, current_row)] = hex_color!("#6DAF6B");
+                streak_image_base[(day_pixel_index, 0)] = theme.graph_win;
+            } else {
+                day.attempts.iter().enumerate().for_each(|(i, attempt)| {
+                    let attempt_color = match i % 2 {
+                        0 => theme.graph_loss, // Alternate failure colors to make them distinct
+                        _ => theme.graph_loss_alt,
+                    };
+                    for _ in 0..attempt.moves as usize {
+                        moves_image_base[(day_pixel_index, current_row)] = attempt_color;
                         current_row = current_row.saturating_sub(1);
                     }
-                    streak_image_base[(day_pixel_index, 0)] = hex_color!("#6DAF6B");
-                } else {
-                    day.attempts.iter().enumerate().for_each(|(i, attempt)| {
-                        let attempt_color = match i % 2 {
-                            0 => hex_color!("#944D5E"), // Alternate failure colors to make them distinct
-                            _ => hex_color!("#A75E6F"),
-                        };
-                        for _ in 0..attempt.moves as usize {
-                            moves_image_base[(day_pixel_index, current_row)] = attempt_color;
-                            current_row = current_row.saturating_sub(1);
-                        }
-                    });
-                }
-            });
+                });
+            }
+        });
 
         let moves_graph_texture = ui.ctx().load_texture(
             "daily_moves_graph",
@@ -113,62 +149,124 @@ impl DailySplashGraph {
             TextureOptions::NEAREST,
         );
 
-        // TODO: Once we have interactions, we'll need these date strings to be reactive
-        let rightmost_date_string = "Today".to_string();
-        let today_date = time::OffsetDateTime::from_unix_timestamp(current_time.as_secs() as i64)
-            .expect("Current time should be valid");
-        let leftmost_date = today_date - time::Duration::days(days_played as i64);
-        let leftmost_date_string = leftmost_date
+        let total_days = days.len() as f32;
+
+        Self {
+            moves_graph_texture,
+            streak_graph_texture,
+            selected_day: days.len() - 1,
+            days,
+            max_total_moves,
+            today,
+            visible_days: DEFAULT_VISIBLE_DAYS.min(total_days),
+            view_end: total_days,
+        }
+    }
+
+    fn date_label(&self, day: &GraphDay) -> String {
+        if day.puzzle_day == self.today {
+            return ui_text("today").to_string();
+        }
+
+        day.date()
             .format(time::macros::format_description!(
                 "[month repr:long] [day] [year]"
             ))
-            .unwrap();
+            .unwrap()
+    }
 
-        // TODO: Once we have interactions, we'll need this highlight info to be reactive
-        let today = stats.days.values().last().cloned().unwrap_or_default();
-        let best_win = today
-            .attempts
-            .iter()
-            .filter(|a| a.won)
-            .min_by_key(|a| a.moves);
-
-        let (moves, today_label) = if let Some(best_win) = best_win {
-            (
-                best_win.moves,
-                format!(
-                    "Won! Personal best: {} move{}",
-                    best_win.moves,
-                    if best_win.moves == 1 { "" } else { "s" }
-                ),
-            )
+    fn day_label(&self, day: &GraphDay) -> String {
+        let is_today = day.puzzle_day == self.today;
+
+        let summary = if let Some(best_win) = day.best_win() {
+            let key = if best_win.moves == 1 {
+                "personal_best_one"
+            } else {
+                "personal_best_many"
+            };
+            ui_format(key, &[("moves", &best_win.moves)])
+        } else if day.attempts.is_empty() {
+            ui_text("not_played").to_string()
         } else {
-            let attempts = today.attempts.len();
-            (
-                today.attempts.iter().map(|a| a.moves).sum(),
-                format!(
-                    "No win yet! {} attempt{}",
-                    attempts,
-                    if attempts == 1 { "" } else { "s" }
-                ),
-            )
+            let attempts = day.attempts.len();
+            let key = match (is_today, attempts == 1) {
+                (true, true) => "no_win_yet_one",
+                (true, false) => "no_win_yet_many",
+                (false, true) => "no_win_one",
+                (false, false) => "no_win_many",
+            };
+            ui_format(key, &[("attempts", &attempts)])
         };
 
-        let today_height: f32 = moves as f32 / max_total_moves as f32;
+        if is_today {
+            summary
+        } else {
+            let date = day
+                .date()
+                .format(time::macros::format_description!(
+                    "[month repr:short] [day]"
+                ))
+                .unwrap();
+            ui_format("dated_summary", &[("date", &date), ("summary", &summary)])
+        }
+    }
 
-        Self {
-            moves_graph_texture,
-            streak_graph_texture,
-            date_labels: (leftmost_date_string, rightmost_date_string),
-            highlighted_day: Some(DayHighlight {
-                day_from_right: 0,
-                label: today_label,
-                bar_height: today_height,
-            }),
-            days_played,
+    /// Pans and zooms the visible window of days, and selects days that are tapped
+    fn handle_interaction(&mut self, ui: &mut egui::Ui, interact_rect: Rect) {
+        let total_days = self.days.len() as f32;
+        let min_visible_days = MIN_VISIBLE_DAYS.min(total_days);
+        let graph_resp = ui.interact(
+            interact_rect,
+            ui.id().with("daily_graph"),
+            Sense::click_and_drag(),
+        );
+
+        let column_width = interact_rect.width() / self.visible_days;
+
+        if graph_resp.dragged() {
+            self.view_end -= graph_resp.drag_delta().x / column_width;
+        }
+
+        if graph_resp.hovered() {
+            let (zoom_delta, scroll_delta) = ui.input(|i| (i.zoom_delta(), i.smooth_scroll_delta));
+
+            // Horizontal scrolling pans, vertical scrolling and pinching zoom
+            self.view_end -= scroll_delta.x / column_width;
+            let zoom = zoom_delta * (scroll_delta.y * 0.005).exp();
+
+            if zoom != 1.0 {
+                // Keep whichever day is under the pointer in place while zooming
+                let anchor = graph_resp
+                    .hover_pos()
+                    .map(|pos| (pos.x - interact_rect.left()) / interact_rect.width())
+                    .unwrap_or(1.0)
+                    .clamp(0.0, 1.0);
+                let anchor_day = self.view_end - self.visible_days * (1.0 - anchor);
+
+                self.visible_days = (self.visible_days / zoom).clamp(min_visible_days, total_days);
+                self.view_end = anchor_day + self.visible_days * (1.0 - anchor);
+            }
+        }
+
+        self.view_end = self.view_end.clamp(self.visible_days, total_days);
+
+        if graph_resp.clicked() {
+            if let Some(pos) = graph_resp.interact_pointer_pos() {
+                let column_width = interact_rect.width() / self.visible_days;
+                let view_start = self.view_end - self.visible_days;
+                let column = view_start + (pos.x - interact_rect.left()) / column_width;
+                self.selected_day = (column.max(0.0) as usize).min(self.days.len() - 1);
+            }
         }
     }
 
-    pub fn render(&self, ui: &mut egui::Ui, graph_rect: Rect) {
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        graph_rect: Rect,
+    ) -> Option<ResultModalAction> {
         let fz = 16.0; // Label font size, should come from a theme.
 
         let mut moves_graph_rect = graph_rect.clone();
@@ -179,45 +277,107 @@ impl DailySplashGraph {
         streak_graph_rect.set_bottom(moves_graph_rect.bottom() + 6.0);
         streak_graph_rect.set_top(moves_graph_rect.bottom() + 2.0);
 
-        let date = TextHelper::light(&self.date_labels.1, fz, None, ui);
-        date.paint_within(graph_rect, Align2::RIGHT_BOTTOM, Color32::WHITE, ui);
+        self.handle_interaction(ui, moves_graph_rect.union(streak_graph_rect));
+
+        let total_days = self.days.len() as f32;
+        let view_start = self.view_end - self.visible_days;
+        let column_width = moves_graph_rect.width() / self.visible_days;
+
+        let first_visible = (view_start.round() as usize).min(self.days.len() - 1);
+        let last_visible = ((self.view_end.round() as usize).max(1) - 1).min(self.days.len() - 1);
 
-        let date = TextHelper::light(&self.date_labels.0, fz, None, ui);
-        date.paint_within(graph_rect, Align2::LEFT_BOTTOM, Color32::WHITE, ui);
+        let date_string = self.date_label(&self.days[last_visible]);
+        let date = TextHelper::light(&date_string, fz, None, ui);
+        date.paint_within(graph_rect, Align2::RIGHT_BOTTOM, theme.graph_label, ui);
 
-        if let Some(highlight) = &self.highlighted_day {
-            let notes = TextHelper::light(&highlight.label, fz, None, ui);
-            // TODO: This will need to align to the correct day when highlighting !today
-            notes.paint_within(graph_rect, Align2::RIGHT_TOP, Color32::WHITE, ui);
+        let date_string = self.date_label(&self.days[first_visible]);
+        let date = TextHelper::light(&date_string, fz, None, ui);
+        date.paint_within(graph_rect, Align2::LEFT_BOTTOM, theme.graph_label, ui);
 
-            let day_column_width = graph_rect.width() / self.days_played as f32;
-            let right_offset =
-                (highlight.day_from_right as f32) * day_column_width + (day_column_width / 2.0);
+        let selected = &self.days[self.selected_day];
+        let highlight_x =
+            moves_graph_rect.left() + (self.selected_day as f32 + 0.5 - view_start) * column_width;
+        let highlight_visible =
+            highlight_x >= moves_graph_rect.left() && highlight_x <= moves_graph_rect.right();
 
-            let line_start = graph_rect.right_top() + vec2(-right_offset, 15.0);
-            let line_end = line_start
-                + vec2(
-                    0.0,
-                    11.0 + (moves_graph_rect.height() * (1.0 - highlight.bar_height)),
-                );
+        // Keep the notes over the highlighted day, without running off the graph
+        let notes_string = self.day_label(selected);
+        let notes = TextHelper::light(&notes_string, fz, None, ui);
+        let notes_width = notes.mesh_size().x;
+        let notes_left = (highlight_x - notes_width / 2.0)
+            .min(graph_rect.right() - notes_width)
+            .max(graph_rect.left());
+        notes.paint_at(pos2(notes_left, graph_rect.top()), theme.graph_label, ui);
+
+        if highlight_visible {
+            let bar_height = selected.total_moves() as f32 / self.max_total_moves as f32;
+            let line_start = pos2(highlight_x, graph_rect.top() + 15.0);
+            let line_end =
+                line_start + vec2(0.0, 11.0 + (moves_graph_rect.height() * (1.0 - bar_height)));
             ui.painter()
-                .line_segment([line_start, line_end], Stroke::new(1.0, Color32::WHITE));
+                .line_segment([line_start, line_end], Stroke::new(1.0, theme.graph_label));
         }
 
-        let mut mesh = Mesh::with_texture(self.moves_graph_texture.id());
-        mesh.add_rect_with_uv(
-            moves_graph_rect,
-            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
-            Color32::WHITE,
+        let visible_uv = Rect::from_min_max(
+            pos2(view_start / total_days, 0.0),
+            pos2(self.view_end / total_days, 1.0),
         );
+
+        let mut mesh = Mesh::with_texture(self.moves_graph_texture.id());
+        mesh.add_rect_with_uv(moves_graph_rect, visible_uv, Color32::WHITE);
         ui.painter().add(Shape::mesh(mesh));
 
         let mut mesh = Mesh::with_texture(self.streak_graph_texture.id());
-        mesh.add_rect_with_uv(
-            streak_graph_rect,
-            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
-            Color32::WHITE,
-        );
+        mesh.add_rect_with_uv(streak_graph_rect, visible_uv, Color32::WHITE);
         ui.painter().add(Shape::mesh(mesh));
+
+        self.render_attempts(ui, theme, map_texture)
+    }
+
+    /// Lists the selected day's attempts, each of which can be tapped to watch its replay
+    fn render_attempts(
+        &self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+    ) -> Option<ResultModalAction> {
+        let mut msg = None;
+        let attempts = &self.days[self.selected_day].attempts;
+
+        let (row_rect, _) =
+            ui.allocate_exact_size(vec2(ui.available_width(), 32.0), Sense::hover());
+        ui.allocate_ui_at_rect(row_rect.shrink2(vec2(10.0, 0.0)), |ui| {
+            ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
+                ui.spacing_mut().item_spacing = vec2(6.0, 0.0);
+
+                if attempts.is_empty() {
+                    let text = TextHelper::light(ui_text("no_attempts"), 12.0, None, ui);
+                    text.paint(theme.graph_label, ui, false);
+                    return;
+                }
+
+                let text = TextHelper::light(ui_text("replays"), 12.0, None, ui);
+                text.paint(theme.graph_label, ui, false);
+
+                let skipped = attempts.len().saturating_sub(MAX_LISTED_ATTEMPTS);
+                for attempt in attempts.iter().skip(skipped) {
+                    let button_color = if attempt.won {
+                        theme.graph_win
+                    } else {
+                        theme.graph_loss
+                    };
+                    let moves_string = attempt.moves.to_string();
+                    let text = TextHelper::heavy(&moves_string, 10.0, None, ui);
+                    if text
+                        .button(button_color, theme.text, map_texture, ui)
+                        .clicked()
+                    {
+                        msg = Some(ResultModalAction::ViewReplay(attempt.id.clone()));
+                    }
+                }
+            });
+        });
+
+        msg
     }
 }
@@ -13,6 +13,7 @@ use crate::{
     app_outer::Backchannel,
     utils::{
         depot::TruncateDepot,
+        locale::ui_text,
         tex::{render_tex_quad, tiles, Tint},
         text::TextHelper,
         Lighten, Theme,
@@ -47,6 +48,8 @@ pub struct ResultModalUnique {
 #[derive(Clone)]
 pub struct ResultModalResigning {
     msg: String,
+    /// Spectators can only leave the game, rather than resign from it
+    leaving: bool,
 }
 
 #[derive(Clone)]
@@ -102,7 +105,12 @@ impl ResultModalUI {
 
         ResultModalUI::seed_animations(ui);
 
-        let graph = DailySplashGraph::new(ui, &stats, depot.timing.current_time);
+        let graph = DailySplashGraph::new(
+            ui,
+            &stats,
+            &depot.aesthetics.theme,
+            depot.timing.current_time,
+        );
         let daily_actions = DailyActions::new(
             best_game.unwrap_or(game),
             player_move_count,
@@ -143,7 +151,18 @@ impl ResultModalUI {
         ResultModalUI::seed_animations(ui);
 
         Self {
-            contents: ResultModalVariant::Resigning(ResultModalResigning { msg }),
+            contents: ResultModalVariant::Resigning(ResultModalResigning {
+                msg,
+                leaving: false,
+            }),
+        }
+    }
+
+    pub fn new_leaving(ui: &mut egui::Ui, msg: String) -> Self {
+        ResultModalUI::seed_animations(ui);
+
+        Self {
+            contents: ResultModalVariant::Resigning(ResultModalResigning { msg, leaving: true }),
         }
     }
 
@@ -195,8 +214,10 @@ pub enum ResultModalAction {
     NewPuzzle,
     Dismiss,
     Resign,
+    Leave,
     SharedText,
     SharedReplay,
+    ViewReplay(String),
 }
 
 impl ResultModalUI {
@@ -388,7 +409,7 @@ impl ResultModalUI {
                         );
                     }
                     ResultModalVariant::Loading(_l) => {
-                        let summary_text = TextHelper::heavy("Loading", 12.0, None, &mut ui);
+                        let summary_text = TextHelper::heavy(ui_text("loading"), 12.0, None, ui);
 
                         summary_text.paint_within(
                             heading_rect.translate(vec2(0.0, -(heading_rect.height() / 2.0 + 8.0))),
@@ -397,7 +418,7 @@ impl ResultModalUI {
                             ui,
                         );
 
-                        let summary_text = TextHelper::heavy("Statistics", 12.0, None, &mut ui);
+                        let summary_text = TextHelper::heavy(ui_text("statistics"), 12.0, None, ui);
 
                         summary_text.paint_within(
                             heading_rect.translate(vec2(0.0, heading_rect.height() / 2.0 + 8.0)),
@@ -420,7 +441,14 @@ impl ResultModalUI {
                         vec2(ui.available_width(), ui.available_height() / 5.0),
                         Sense::hover(),
                     );
-                    daily.graph.render(ui, graph_rect.shrink2(vec2(10.0, 0.0)));
+                    if let Some(action) = daily.graph.render(
+                        ui,
+                        theme,
+                        map_texture,
+                        graph_rect.shrink2(vec2(10.0, 0.0)),
+                    ) {
+                        msg = Some(action);
+                    }
                 }
 
                 match &mut self.contents {
@@ -446,7 +474,7 @@ impl ResultModalUI {
                             Layout::bottom_up(Align::LEFT),
                             |ui| {
                                 ui.add_space(ui.available_height() * 0.05);
-                                let text = TextHelper::heavy("NEW PUZZLE", 12.0, None, ui);
+                                let text = TextHelper::heavy(ui_text("new_puzzle"), 12.0, None, ui);
                                 let new_puzzle_button = text.centered_button(
                                     theme.button_primary,
                                     theme.text,
@@ -458,7 +486,7 @@ impl ResultModalUI {
                                 }
 
                                 ui.add_space(ui.available_height() * 0.05);
-                                let text = TextHelper::heavy("TRY AGAIN", 12.0, None, ui);
+                                let text = TextHelper::heavy(ui_text("try_again"), 12.0, None, ui);
                                 let try_again_button = text.centered_button(
                                     theme.button_primary,
                                     theme.text,
@@ -471,9 +499,9 @@ impl ResultModalUI {
 
                                 ui.add_space(ui.available_height() * 0.05);
                                 let button_text = if unique.share_copied_at.is_some() {
-                                    "COPIED TEXT!"
+                                    ui_text("copied_text")
                                 } else {
-                                    "SHARE PUZZLE"
+                                    ui_text("share_puzzle")
                                 };
                                 let text = TextHelper::heavy(button_text, 12.0, None, ui);
                                 let share_button = text.centered_button(
@@ -515,17 +543,22 @@ impl ResultModalUI {
                             },
                         );
                     }
-                    ResultModalVariant::Resigning(_r) => {
+                    ResultModalVariant::Resigning(r) => {
                         ui.add_space(20.0);
-                        let text = TextHelper::heavy("RESIGN", 12.0, None, ui);
+                        let (label, action) = if r.leaving {
+                            (ui_text("stop_watching"), ResultModalAction::Leave)
+                        } else {
+                            (ui_text("resign"), ResultModalAction::Resign)
+                        };
+                        let text = TextHelper::heavy(label, 12.0, None, ui);
                         let try_again_button =
                             text.centered_button(theme.button_primary, theme.text, map_texture, ui);
                         if try_again_button.clicked() {
-                            msg = Some(ResultModalAction::Resign);
+                            msg = Some(action);
                         }
 
                         ui.add_space(10.0);
-                        let text = TextHelper::heavy("CONTINUE PLAYING", 12.0, None, ui);
+                        let text = TextHelper::heavy(ui_text("continue_playing"), 12.0, None, ui);
                         let new_puzzle_button = text.centered_button(
                             theme.water.lighten().lighten(),
                             theme.text,
@@ -539,12 +572,13 @@ impl ResultModalUI {
                     ResultModalVariant::Loading(_l) => {
                         ui.add_space(50.0);
 
-                        let summary_text = TextHelper::heavy("Waiting for", 10.0, None, &mut ui);
+                        let summary_text =
+                            TextHelper::heavy(ui_text("waiting_for"), 10.0, None, ui);
 
                         summary_text.paint(Color32::WHITE, ui, true);
 
                         let summary_text =
-                            TextHelper::heavy("network connection", 10.0, None, &mut ui);
+                            TextHelper::heavy(ui_text("network_connection"), 10.0, None, ui);
 
                         summary_text.paint(Color32::WHITE, ui, true);
                     }
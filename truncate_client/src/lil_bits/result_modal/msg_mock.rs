@@ -1,7 +1,7 @@
 use eframe::egui::{self, Layout, Sense};
 use epaint::{
     emath::{Align, NumExt},
-    hex_color, vec2, Color32, TextureHandle, Vec2,
+    vec2, TextureHandle, Vec2,
 };
 use truncate_core::{
     game::Game,
@@ -10,13 +10,6 @@ use truncate_core::{
 
 use crate::utils::{depot::TruncateDepot, Theme};
 
-/*
-
-TODOs for the message mock:
-- Pull all of the colours from a central theme once we refactor general theming
-
- */
-
 #[derive(Clone)]
 pub struct ShareMessageMock {
     pub share_text: String,
@@ -35,9 +28,11 @@ impl ShareMessageMock {
     ) -> Self {
         let share_prefix =
             ShareMessageMock::daily_share_message(day, first_win, best_win, latest_attempt);
-        let emoji_board = game
-            .board
-            .emojify(depot.gameplay.player_number as usize, game.winner);
+        let emoji_board = game.board.emojify(
+            depot.gameplay.player_number as usize,
+            game.winner,
+            depot.aesthetics.theme.palette.emoji(),
+        );
         let share_text = format!("{share_prefix}\n{emoji_board}");
 
         let _this_attempt = stats
@@ -54,9 +49,11 @@ impl ShareMessageMock {
 
     pub fn new_unique(game: &Game, depot: &TruncateDepot) -> Self {
         let share_prefix = ShareMessageMock::unique_share_message(game, depot);
-        let emoji_board = game
-            .board
-            .emojify(depot.gameplay.player_number as usize, game.winner);
+        let emoji_board = game.board.emojify(
+            depot.gameplay.player_number as usize,
+            game.winner,
+            depot.aesthetics.theme.palette.emoji(),
+        );
         let share_text = format!("{share_prefix}\n{emoji_board}");
 
         Self {
@@ -65,7 +62,7 @@ impl ShareMessageMock {
         }
     }
 
-    pub fn render(&mut self, ui: &mut egui::Ui, _theme: &Theme, _map_texture: &TextureHandle) {
+    pub fn render(&mut self, ui: &mut egui::Ui, theme: &Theme, _map_texture: &TextureHandle) {
         let target_height = 120.0.at_most(ui.available_height());
 
         let (mut message_bounds, _) = ui.allocate_exact_size(
@@ -85,16 +82,16 @@ impl ShareMessageMock {
         }
 
         ui.painter()
-            .rect_filled(message_bounds, 15.0, hex_color!("#444444"));
+            .rect_filled(message_bounds, 15.0, theme.message_bubble);
 
         let mut tail = message_bounds.translate(vec2(message_bounds.width() - 7.0, 0.0));
         tail.set_right(tail.left() + 20.0);
         tail.set_top(tail.bottom() - 30.0);
 
-        ui.painter().rect_filled(tail, 10.0, hex_color!("#444444"));
+        ui.painter().rect_filled(tail, 10.0, theme.message_bubble);
         tail = tail.translate(vec2(7.0, -3.0));
         tail.set_top(tail.bottom() - 40.0);
-        ui.painter().rect_filled(tail, 10.0, hex_color!("#111111"));
+        ui.painter().rect_filled(tail, 10.0, theme.message_shadow);
 
         message_bounds = message_bounds.shrink(10.0);
 
@@ -120,14 +117,7 @@ impl ShareMessageMock {
                             ui.add_space((ui.available_width() - full_line) / 2.0);
                         }
                         for emoji in line.chars() {
-                            let color = match emoji {
-                                '🟦' => hex_color!("#4F55E2"), // TODO: Pull from theming palette
-                                '🟩' => hex_color!("#6DAF6B"),
-                                '🟨' => hex_color!("#D7AE1D"),
-                                '🟫' => hex_color!("#A7856F"),
-                                '🟪' => hex_color!("#D27CFF"),
-                                _ => Color32::BLACK,
-                            };
+                            let color = theme.emoji_colors.for_emoji(emoji);
                             let (emoji_rect, _) =
                                 ui.allocate_exact_size(Vec2::splat(emoji_size), Sense::hover());
                             let emoji_rect = emoji_rect.shrink(emoji_rect.width() * 0.1);
@@ -148,6 +138,24 @@ impl ShareMessageMock {
         latest_attempt: (u32, &DailyAttempt),
     ) -> String {
         let plur = |num: u32| if num == 1 { "" } else { "s" };
+        let assists = |attempt: &DailyAttempt| {
+            let mut used = vec![];
+            if attempt.hints > 0 {
+                used.push(format!("{} hint{}", attempt.hints, plur(attempt.hints)));
+            }
+            if attempt.takebacks > 0 {
+                used.push(format!(
+                    "{} takeback{}",
+                    attempt.takebacks,
+                    plur(attempt.takebacks)
+                ));
+            }
+            if used.is_empty() {
+                String::new()
+            } else {
+                format!(" with {}", used.join(" and "))
+            }
+        };
 
         let header = if matches!(option_env!("TR_ENV"), Some("outpost")) {
             format!("-- Truncate Outpost Day #{day} --")
@@ -157,10 +165,11 @@ impl ShareMessageMock {
 
         let Some(first_win) = first_win else {
             return format!(
-                "{header}\nLost in {} move{} on attempt #{}",
+                "{header}\nLost in {} move{} on attempt #{}{}",
                 latest_attempt.1.moves,
                 plur(latest_attempt.1.moves),
                 latest_attempt.0 + 1,
+                assists(latest_attempt.1),
             );
         };
 
@@ -168,16 +177,18 @@ impl ShareMessageMock {
 
         let first_win_message = if first_win.0 == 0 {
             format!(
-                "Won first try in {} move{}",
+                "Won first try in {} move{}{}",
                 first_win.1.moves,
-                plur(first_win.1.moves)
+                plur(first_win.1.moves),
+                assists(first_win.1)
             )
         } else {
             format!(
-                "Won on attempt #{} in {} move{}",
+                "Won on attempt #{} in {} move{}{}",
                 first_win.0 + 1,
                 first_win.1.moves,
-                plur(first_win.1.moves)
+                plur(first_win.1.moves),
+                assists(first_win.1)
             )
         };
 
@@ -185,9 +196,10 @@ impl ShareMessageMock {
             format!("{header}\n{first_win_message}")
         } else {
             format!(
-                "{header}\n{first_win_message}\nPersonal best: {} move{}",
+                "{header}\n{first_win_message}\nPersonal best: {} move{}{}",
                 best_win.moves,
-                plur(best_win.moves)
+                plur(best_win.moves),
+                assists(best_win)
             )
         }
     }
@@ -200,6 +212,18 @@ impl ShareMessageMock {
 
         let plur = |num: u32| if num == 1 { "" } else { "s" };
 
+        let counts = format!(
+            " in {} move{}",
+            game.player_turn_count[player],
+            plur(game.player_turn_count[player]),
+        );
+
+        if let Some(code) = &depot.board_info.puzzle_code {
+            let share_link = format!("Play Puzzle: https://truncate.town/puzzle/?j=CUSTOM:{code}");
+            let result = if player_won { "Won" } else { "Lost" };
+            return format!("Truncate Town Custom Puzzle\n{result}{counts}\n{share_link}");
+        }
+
         let (Some(seed), Some(npc)) = (&depot.board_info.board_seed, &depot.gameplay.npc) else {
             if player_won {
                 return format!("Won puzzle");
@@ -218,12 +242,6 @@ impl ShareMessageMock {
             player
         );
 
-        let counts = format!(
-            " in {} move{}",
-            game.player_turn_count[player],
-            plur(game.player_turn_count[player]),
-        );
-
         if player_won {
             format!("Truncate Town Puzzle\nWon{counts}\n{share_link}")
         } else {
@@ -135,6 +135,25 @@ impl<'a> TimerUI<'a> {
             _ => {}
         };
 
+        if let Some(forfeits_at) = self
+            .depot
+            .gameplay
+            .disconnected_players
+            .get(&(self.player.index as u64))
+        {
+            return match forfeits_at {
+                Some(forfeits_at) => {
+                    let now = self.depot.timing.current_time.as_secs();
+                    let remaining = forfeits_at.saturating_sub(now) as i64;
+                    format!(
+                        "Disconnected, forfeits in {}",
+                        TimerUI::human_time(remaining, true)
+                    )
+                }
+                None => "Disconnected".into(),
+            };
+        }
+
         if self.depot.timing.paused {
             return format!("Game is paused!");
         }
@@ -1,14 +1,76 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use eframe::egui::Context;
 use futures::channel::{mpsc, oneshot};
 use futures::SinkExt;
-use futures_util::{future, pin_mut, StreamExt};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use futures_util::{
+    future::{self, Either},
+    pin_mut, StreamExt,
+};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream,
+};
+
+use truncate_core::messages::{GameMessage, Nonce, NoncedPlayerMessage, PlayerMessage};
+
+use crate::utils::macros::current_time;
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(16);
+
+async fn websocket_connect(
+    connect_addr: &String,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, ()> {
+    tracing::info!("Connecting to {connect_addr}");
 
-use truncate_core::messages::{GameMessage, PlayerMessage};
+    let Ok((ws_stream, _)) = connect_async(connect_addr).await else {
+        tracing::warn!("Failed to connect to {connect_addr}");
+        return Err(());
+    };
+
+    tracing::info!("Connected");
 
-/*
- TODO: Implement the pending_messages retry flow from web_comms
-*/
+    Ok(ws_stream)
+}
+
+/// Swaps the token in a remembered login, or builds one from the details sent
+/// when the player was created, so that reconnections log in as the right player
+fn login_as(
+    player_token: String,
+    login: Option<PlayerMessage>,
+    anonymous_player: Option<PlayerMessage>,
+) -> Option<PlayerMessage> {
+    match login.or(anonymous_player)? {
+        PlayerMessage::Login {
+            screen_width,
+            screen_height,
+            user_agent,
+            referrer,
+            locale,
+            ..
+        }
+        | PlayerMessage::CreateAnonymousPlayer {
+            screen_width,
+            screen_height,
+            user_agent,
+            referrer,
+            locale,
+            ..
+        } => Some(PlayerMessage::Login {
+            player_token,
+            screen_width,
+            screen_height,
+            user_agent,
+            referrer,
+            locale,
+        }),
+        _ => None,
+    }
+}
 
 pub async fn connect(
     connect_addr: String,
@@ -18,44 +80,238 @@ pub async fn connect(
     rx_context: oneshot::Receiver<Context>,
 ) {
     let mut context: Option<Context> = None;
-
-    let (ws_stream, _) = connect_async(connect_addr)
-        .await
-        .expect("Failed to connect");
-
     if let Ok(ctx) = rx_context.await {
         context = Some(ctx);
     }
 
-    let (outgoing, incoming) = ws_stream.split();
+    let most_recent_game_token: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+    let most_recent_login: Arc<Mutex<Option<PlayerMessage>>> = Arc::new(Mutex::new(None));
+    let anonymous_player: Arc<Mutex<Option<PlayerMessage>>> = Arc::new(Mutex::new(None));
+
+    let requested_login = AtomicBool::new(false);
+
+    type NonceQueue = VecDeque<(Option<Nonce>, Message)>;
+    let mut pending_messages: NonceQueue = VecDeque::new();
+    let unconfirmed_messages: Arc<Mutex<NonceQueue>> = Arc::new(Mutex::new(VecDeque::new()));
+
+    let mut current_nonce = 0_u64;
+    let mut get_nonce = || {
+        current_nonce += 1;
+        Nonce {
+            generated_at: current_time!().as_secs(),
+            id: current_nonce,
+        }
+    };
+
+    let mut outgoing_msg_stream = rx_player.map(|message| {
+        // Store a token that we're interacting with, in case we need to
+        // recreate the connection.
+        if let PlayerMessage::RejoinGame(token) = &message {
+            *most_recent_game_token.lock().unwrap() = Some(token.to_string());
+        }
+
+        if let PlayerMessage::Login { .. } = &message {
+            *most_recent_login.lock().unwrap() = Some(message.clone());
+        }
 
-    let game_messages = {
-        incoming.for_each(|msg| async {
-            let parsed_msg: GameMessage =
-                serde_json::from_str(msg.unwrap().to_text().expect("Was not valid UTF-8"))
-                    .expect("Was not valid JSON");
+        if let PlayerMessage::CreateAnonymousPlayer { .. } = &message {
+            *anonymous_player.lock().unwrap() = Some(message.clone());
+        }
 
-            if matches!(parsed_msg, GameMessage::Ping) {
-                _ = tx_player.clone().send(PlayerMessage::Ping).await;
+        match &message {
+            // Avoid noncing pings since we don't care about any individual ping.
+            // Avoid noncing pre-login methods, as nonces don't work if the player is not logged in.
+            PlayerMessage::Ping
+            | PlayerMessage::Login { .. }
+            | PlayerMessage::CreateAnonymousPlayer { .. } => (
+                None,
+                Message::Text(serde_json::to_string(&message).unwrap()),
+            ),
+            _ => {
+                let nonce = get_nonce();
+
+                let wrapped_msg = NoncedPlayerMessage {
+                    nonce: nonce.clone(),
+                    message,
+                };
+
+                (
+                    Some(nonce),
+                    Message::Text(serde_json::to_string(&wrapped_msg).unwrap()),
+                )
             }
+        }
+    });
+
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    let mut first_attempt = true;
+
+    loop {
+        // Back off between every attempt, including after connections that dropped straight away
+        if !first_attempt {
+            tracing::warn!("Waiting {}ms to reconnect", retry_delay.as_millis());
+            tokio::time::sleep(retry_delay).await;
+            retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+        }
+        first_attempt = false;
+
+        let Ok(ws_stream) = websocket_connect(&connect_addr).await else {
+            continue;
+        };
+        // Only a connection that the server actually talks over resets the backoff
+        let heard_from_server = AtomicBool::new(false);
+
+        let (mut outgoing, mut incoming) = ws_stream.split();
+
+        let login = most_recent_login.lock().unwrap().clone();
+        if let Some(login) = login {
+            let encoded_login_msg = Message::Text(serde_json::to_string(&login).unwrap());
+            if outgoing.send(encoded_login_msg).await.is_err() {
+                continue;
+            };
+        }
+
+        let game_token = most_recent_game_token.lock().unwrap().clone();
+        if let Some(token) = game_token {
+            let reconnection_msg = PlayerMessage::RejoinGame(token);
+            let encoded_reconnection_msg =
+                Message::Text(serde_json::to_string(&reconnection_msg).unwrap());
+            if outgoing.send(encoded_reconnection_msg).await.is_err() {
+                continue;
+            };
+        }
+
+        {
+            let mut unconfirmed = unconfirmed_messages.lock().unwrap();
+            unconfirmed.extend(pending_messages.drain(..));
+            std::mem::swap(&mut *unconfirmed, &mut pending_messages);
+        }
+
+        let game_messages = async {
+            while let Some(msg) = incoming.next().await {
+                let parsed_msg = match msg {
+                    Ok(Message::Text(msg)) => serde_json::from_str::<GameMessage>(&msg),
+                    Ok(Message::Binary(msg)) => serde_json::from_slice::<GameMessage>(&msg),
+                    Ok(Message::Close(_)) | Err(_) => break,
+                    Ok(_) => continue,
+                };
+                let parsed_msg = match parsed_msg {
+                    Ok(parsed_msg) => {
+                        heard_from_server.store(true, Ordering::Relaxed);
+                        parsed_msg
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            "Ignoring a message from the server that wasn't valid JSON: {err}"
+                        );
+                        continue;
+                    }
+                };
 
-            tx_game
-                .clone()
-                .send(parsed_msg)
-                .await
-                .expect("Message should have been able to go into the unbounded channel");
-            if let Some(context) = context.as_ref() {
-                context.request_repaint();
+                match &parsed_msg {
+                    GameMessage::Ping => {
+                        _ = tx_player.clone().send(PlayerMessage::Ping).await;
+                    }
+                    GameMessage::PleaseLogin => {
+                        requested_login.store(true, Ordering::Relaxed);
+                    }
+                    GameMessage::Ack(nonce) => {
+                        let mut msgs = unconfirmed_messages.lock().unwrap();
+                        if let Some(pos) = msgs
+                            .iter()
+                            .position(|(n, _)| n.as_ref().is_some_and(|n| n == nonce))
+                        {
+                            if pos != 0 {
+                                tracing::warn!("Received an out of order ack from server at {pos}");
+                            }
+
+                            for _ in 0..=pos {
+                                msgs.pop_front();
+                            }
+                        }
+                    }
+                    GameMessage::JoinedLobby(_, _, _, _, _, token) => {
+                        // Store a token that we're interacting with, in case we need to
+                        // recreate the connection.
+                        *most_recent_game_token.lock().unwrap() = Some(token.to_string());
+                    }
+                    GameMessage::LoggedInAs { token, .. } => {
+                        let mut login = most_recent_login.lock().unwrap();
+                        let anonymous_player = anonymous_player.lock().unwrap().clone();
+                        *login = login_as(token.clone(), login.take(), anonymous_player);
+                    }
+                    _ => { /* no processing needed */ }
+                }
+
+                if tx_game.clone().send(parsed_msg).await.is_err() {
+                    // Nobody is left to read messages once the app has shut down
+                    return true;
+                }
+                if let Some(context) = context.as_ref() {
+                    context.request_repaint();
+                }
             }
-        })
-    };
+            false
+        };
 
-    let player_messages = {
-        rx_player
-            .map(|msg| Ok(Message::Text(serde_json::to_string(&msg).unwrap())))
-            .forward(outgoing)
-    };
+        let player_messages = async {
+            loop {
+                if pending_messages.is_empty() {
+                    match outgoing_msg_stream.next().await {
+                        Some(msg) => {
+                            pending_messages.push_back(msg);
+                        }
+                        None => {
+                            // The app has shut down, so there is nothing left to send
+                            return true;
+                        }
+                    }
+                };
+
+                if requested_login.load(Ordering::Relaxed) {
+                    let login = most_recent_login.lock().unwrap().clone();
+                    if let Some(login) = login {
+                        requested_login.store(false, Ordering::Relaxed);
+                        pending_messages.push_front((
+                            None,
+                            Message::Text(serde_json::to_string(&login).unwrap()),
+                        ))
+                    }
+                }
 
-    pin_mut!(game_messages, player_messages);
-    future::select(game_messages, player_messages).await;
+                if let Some(msg) = pending_messages.front().cloned() {
+                    match outgoing.send(msg.1).await {
+                        Ok(()) => {
+                            if let (Some(nonce), msg) = pending_messages
+                                .pop_front()
+                                .expect("nothing else should remove from pending_messages")
+                            {
+                                let mut unconfirmed = unconfirmed_messages.lock().unwrap();
+                                unconfirmed.push_back((Some(nonce), msg))
+                            }
+                        }
+                        Err(err) => {
+                            // Leave the message pending and reconnect to resend it
+                            tracing::debug!("Send err: {err:?}");
+                            return false;
+                        }
+                    }
+                }
+            }
+        };
+
+        pin_mut!(game_messages, player_messages);
+        let app_closed = matches!(
+            future::select(game_messages, player_messages).await,
+            Either::Left((true, _)) | Either::Right((true, _))
+        );
+        if app_closed {
+            return;
+        }
+
+        if heard_from_server.load(Ordering::Relaxed) {
+            retry_delay = INITIAL_RETRY_DELAY;
+        }
+        tracing::warn!("Lost connection to the server, reconnecting");
+    }
 }
@@ -0,0 +1,102 @@
+use eframe::egui::{self, Align, RichText};
+use epaint::{vec2, Color32, TextureHandle};
+
+use crate::utils::{locale::ui_text, text::TextHelper, Lighten, Theme};
+
+pub enum AccountAction {
+    ClaimName(String),
+    RequestLoginLink(String),
+    BackToMenu,
+}
+
+pub struct AccountState {
+    pub name_input: String,
+    pub email_input: String,
+    /// The outcome of the last thing the player did on this screen
+    pub status: Option<String>,
+}
+
+impl AccountState {
+    pub fn new(name: String, status: Option<String>) -> Self {
+        Self {
+            name_input: name,
+            email_input: String::new(),
+            status,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        map_texture: &TextureHandle,
+        error: Option<&String>,
+    ) -> Option<AccountAction> {
+        let mut action = None;
+
+        ui.style_mut().spacing.item_spacing = vec2(6.0, 6.0);
+
+        let text = TextHelper::heavy(ui_text("back_to_menu"), 14.0, None, ui);
+        if text
+            .full_button(theme.button_primary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            action = Some(AccountAction::BackToMenu);
+        }
+
+        ui.add_space(12.0);
+
+        ui.label(RichText::new("Your name:").color(Color32::WHITE));
+        text_input(&mut self.name_input, theme, ui);
+        let text = TextHelper::heavy(ui_text("claim_name"), 12.0, None, ui);
+        if text
+            .full_button(theme.button_secondary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            action = Some(AccountAction::ClaimName(self.name_input.clone()));
+        }
+
+        ui.add_space(12.0);
+
+        ui.label(RichText::new("Your email, to log in on other devices:").color(Color32::WHITE));
+        text_input(&mut self.email_input, theme, ui);
+        let text = TextHelper::heavy(ui_text("email_login_link"), 12.0, None, ui);
+        if text
+            .full_button(theme.button_secondary, theme.text, map_texture, ui)
+            .clicked()
+        {
+            action = Some(AccountAction::RequestLoginLink(self.email_input.clone()));
+        }
+
+        ui.add_space(12.0);
+
+        if let Some(error) = error {
+            TextHelper::light(&error.to_uppercase(), 12.0, Some(ui.available_width()), ui).paint(
+                theme.text.lighten(),
+                ui,
+                true,
+            );
+        } else if let Some(status) = &self.status {
+            TextHelper::heavy(status, 12.0, Some(ui.available_width()), ui).paint(
+                Color32::WHITE,
+                ui,
+                true,
+            );
+        }
+
+        action
+    }
+}
+
+fn text_input(value: &mut String, theme: &Theme, ui: &mut egui::Ui) {
+    ui.add(
+        egui::TextEdit::singleline(value)
+            .min_size(vec2(0.0, theme.letter_size * 0.75))
+            .text_color(Color32::WHITE)
+            .vertical_align(Align::BOTTOM)
+            .font(egui::FontId::new(
+                theme.letter_size / 2.0,
+                egui::FontFamily::Name("Truncate-Heavy".into()),
+            )),
+    );
+}
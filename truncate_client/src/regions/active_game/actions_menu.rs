@@ -9,7 +9,13 @@ use eframe::{
 
 use crate::{
     lil_bits::DictionaryUI,
-    utils::{text::TextHelper, urls::back_to_menu},
+    utils::{
+        depot::{AssistRequest, ThemeRequest},
+        locale::ui_text,
+        text::TextHelper,
+        theming::Palette,
+        urls::back_to_menu,
+    },
 };
 
 use super::{ActiveGame, GameLocation};
@@ -65,7 +71,7 @@ impl ActiveGame {
                 ui.expand_to_include_rect(inner_actions_area);
                 ui.with_layout(Layout::bottom_up(Align::RIGHT), |ui| {
                     if self.depot.ui_state.is_mobile {
-                        let text = TextHelper::heavy("VIEW BATTLES", 14.0, None, ui);
+                        let text = TextHelper::heavy(ui_text("view_battles"), 14.0, None, ui);
                         if text
                             .button(
                                 self.depot.aesthetics.theme.button_secondary,
@@ -82,9 +88,9 @@ impl ActiveGame {
                     }
 
                     let text = if self.depot.audio.muted {
-                        TextHelper::heavy("UNMUTE SOUNDS", 14.0, None, ui)
+                        TextHelper::heavy(ui_text("unmute_sounds"), 14.0, None, ui)
                     } else {
-                        TextHelper::heavy("MUTE SOUNDS", 14.0, None, ui)
+                        TextHelper::heavy(ui_text("mute_sounds"), 14.0, None, ui)
                     };
 
                     if text
@@ -108,13 +114,38 @@ impl ActiveGame {
                         }
                     }
 
+                    let colour_blind_label =
+                        if self.depot.aesthetics.theme.palette == Palette::ColourBlind {
+                            ui_text("standard_colours")
+                        } else {
+                            ui_text("colour_blind_colours")
+                        };
+                    for (label, request) in [
+                        (ui_text("change_theme"), ThemeRequest::NextTheme),
+                        (colour_blind_label, ThemeRequest::ToggleColourBlind),
+                    ] {
+                        ui.add_space(menu_spacing);
+                        let text = TextHelper::heavy(label, 14.0, None, ui);
+                        if text
+                            .button(
+                                self.depot.aesthetics.theme.button_secondary,
+                                self.depot.aesthetics.theme.text,
+                                &self.depot.aesthetics.map_texture,
+                                ui,
+                            )
+                            .clicked()
+                        {
+                            self.depot.ui_state.theme_request = Some(request);
+                        }
+                    }
+
                     if matches!(self.location, GameLocation::Online) {
                         ui.add_space(menu_spacing);
 
                         let text = if self.depot.timing.paused {
-                            TextHelper::heavy("UNPAUSE", 14.0, None, ui)
+                            TextHelper::heavy(ui_text("unpause"), 14.0, None, ui)
                         } else {
-                            TextHelper::heavy("PAUSE", 14.0, None, ui)
+                            TextHelper::heavy(ui_text("pause"), 14.0, None, ui)
                         };
 
                         if text
@@ -134,6 +165,30 @@ impl ActiveGame {
                         }
                     }
 
+                    let players_turn = self.depot.gameplay.next_player_number
+                        == Some(self.depot.gameplay.player_number);
+                    if matches!(self.location, GameLocation::Local) && players_turn {
+                        for (label, request) in [
+                            (ui_text("take_back_move"), AssistRequest::Takeback),
+                            (ui_text("hint"), AssistRequest::Hint),
+                        ] {
+                            ui.add_space(menu_spacing);
+                            let text = TextHelper::heavy(label, 14.0, None, ui);
+                            if text
+                                .button(
+                                    self.depot.aesthetics.theme.button_secondary,
+                                    self.depot.aesthetics.theme.text,
+                                    &self.depot.aesthetics.map_texture,
+                                    ui,
+                                )
+                                .clicked()
+                            {
+                                self.depot.ui_state.assist_request = Some(request);
+                                self.depot.ui_state.actions_menu_open = false;
+                            }
+                        }
+                    }
+
                     // TODO: Resigning is largely implented for multiplayer games as well, but we need to:
                     // - Resolve why the update isn't being sent from the server
                     // - Show the confirmation modal inside active_game (we only show it in single player)
@@ -141,7 +196,7 @@ impl ActiveGame {
                     // This intentionally excludes the tutorial
                     if matches!(self.location, GameLocation::Local) {
                         ui.add_space(menu_spacing);
-                        let text = TextHelper::heavy("RESIGN", 14.0, None, ui);
+                        let text = TextHelper::heavy(ui_text("resign"), 14.0, None, ui);
                         if text
                             .button(
                                 self.depot.aesthetics.theme.button_primary,
@@ -158,7 +213,7 @@ impl ActiveGame {
 
                     ui.add_space(menu_spacing);
 
-                    let text = TextHelper::heavy("BACK TO MENU", 14.0, None, ui);
+                    let text = TextHelper::heavy(ui_text("back_to_menu"), 14.0, None, ui);
                     if text
                         .button(
                             self.depot.aesthetics.theme.button_primary,
@@ -10,6 +10,7 @@ use eframe::{
 use crate::{
     lil_bits::{DictionaryUI, HandUI},
     utils::{
+        locale::ui_text,
         tex::{render_tex_quad, tiles},
         text::TextHelper,
     },
@@ -117,7 +118,7 @@ impl ActiveGame {
 
                     if self.depot.gameplay.winner.is_some() {
                         if matches!(self.location, GameLocation::Online) {
-                            let text = TextHelper::heavy("REMATCH", 12.0, None, ui);
+                            let text = TextHelper::heavy(ui_text("rematch"), 12.0, None, ui);
                             if text
                                 .centered_button(
                                     self.depot.aesthetics.theme.button_primary,
@@ -133,7 +134,7 @@ impl ActiveGame {
                             ui.add_space(20.0);
                         }
                         if matches!(self.location, GameLocation::Local) {
-                            let text = TextHelper::heavy("VIEW RESULTS", 12.0, None, ui);
+                            let text = TextHelper::heavy(ui_text("view_results"), 12.0, None, ui);
                             if text
                                 .centered_button(
                                     self.depot.aesthetics.theme.button_primary,
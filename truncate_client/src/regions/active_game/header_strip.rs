@@ -171,7 +171,7 @@ impl ActiveGame {
 
                             let active_player = self.depot.gameplay.player_number;
                             let summary = if let Some(game) = game_ref {
-                                format!(
+                                let moves = format!(
                                     "{} move{}",
                                     game.player_turn_count[active_player as usize],
                                     if game.player_turn_count[active_player as usize] == 1 {
@@ -179,7 +179,11 @@ impl ActiveGame {
                                     } else {
                                         "s"
                                     },
-                                )
+                                );
+                                match self.depot.gameplay.remaining_turns {
+                                    Some(remaining) => format!("{moves}, {remaining} left"),
+                                    None => moves,
+                                }
                             } else {
                                 "".to_string()
                             };
@@ -1,4 +1,4 @@
-use epaint::{Color32, TextureHandle};
+use epaint::TextureHandle;
 use instant::Duration;
 use truncate_core::{
     board::{Board, Coordinate},
@@ -51,6 +51,8 @@ pub enum GameLocation {
     Tutorial,
     Local,
     Online,
+    /// Watching someone else's online game, without being able to act in it
+    Spectating,
 }
 
 #[derive(Clone)]
@@ -89,7 +91,7 @@ impl ActiveGame {
     ) -> Self {
         let player_colors = players
             .iter()
-            .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2))
+            .map(|p| theme.player_color(p.color))
             .collect::<Vec<_>>();
 
         let mut depot = TruncateDepot {
@@ -114,6 +116,7 @@ impl ActiveGame {
                 last_battle_origin: None,
                 npc,
                 remaining_turns,
+                disconnected_players: Default::default(),
             },
             aesthetics: AestheticDepot {
                 theme: theme.clone(),
@@ -160,6 +163,24 @@ impl ActiveGame {
             dictionary_ui: None,
         }
     }
+
+    /// Switches to a theme chosen mid-game, redrawing the board in its colours
+    pub fn apply_theme(&mut self, ctx: &egui::Context, theme: &Theme) {
+        self.depot.aesthetics.theme = theme.clone();
+        self.depot.aesthetics.player_colors = self
+            .players
+            .iter()
+            .map(|p| theme.player_color(p.color))
+            .collect();
+        self.mapped_board = MappedBoard::new(
+            ctx,
+            &self.depot.aesthetics,
+            &self.board,
+            2,
+            self.depot.gameplay.player_number as usize,
+            theme.daytime,
+        );
+    }
 }
 
 impl ActiveGame {
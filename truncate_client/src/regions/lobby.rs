@@ -7,14 +7,19 @@ use truncate_core::{
     board::Board,
     generation::BoardSeed,
     messages::{LobbyPlayerMessage, PlayerMessage, RoomCode},
+    rules::{
+        RuleSettings, SwappingPreset, TimingPreset, TruncationPreset, VisibilityPreset,
+        WinMetricPreset,
+    },
 };
 
 use eframe::egui::{self, Layout, Order, RichText, ScrollArea};
 
 use crate::{
-    lil_bits::EditorUI,
+    lil_bits::{BoardEditorState, EditorUI},
     utils::{
         depot::{AestheticDepot, TimingDepot},
+        locale::ui_text,
         mapper::MappedBoard,
         text::TextHelper,
         Diaphanize, Lighten, Theme,
@@ -29,6 +34,87 @@ pub enum BoardEditingMode {
     Artifact(usize),
 }
 
+/// Picks the option after `current`, wrapping back to the start
+pub fn cycle<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let index = options.iter().position(|o| *o == current).unwrap_or(0);
+    options[(index + 1) % options.len()]
+}
+
+fn describe_settings(settings: &RuleSettings) -> [(&'static str, &'static str); 5] {
+    [
+        (
+            ui_text("timing"),
+            match settings.timing {
+                TimingPreset::Untimed => ui_text("untimed"),
+                TimingPreset::Blitz => ui_text("timing_blitz"),
+                TimingPreset::Rapid => ui_text("timing_rapid"),
+                TimingPreset::Classical => ui_text("timing_classical"),
+            },
+        ),
+        (
+            ui_text("visibility"),
+            match settings.visibility {
+                VisibilityPreset::Standard => ui_text("visibility_standard"),
+                VisibilityPreset::TileFog => ui_text("tile_fog"),
+                VisibilityPreset::LandFog => ui_text("land_fog"),
+                VisibilityPreset::OnlyHouseFog => ui_text("town_fog"),
+            },
+        ),
+        (
+            ui_text("swapping"),
+            match settings.swapping {
+                SwappingPreset::Contiguous => ui_text("adjacent_tiles"),
+                SwappingPreset::Universal => ui_text("any_tiles"),
+                SwappingPreset::Disabled => ui_text("swapping_none"),
+            },
+        ),
+        (
+            ui_text("truncation"),
+            match settings.truncation {
+                TruncationPreset::Root => ui_text("setting_on"),
+                TruncationPreset::Disabled => ui_text("setting_off"),
+            },
+        ),
+        (
+            ui_text("win_metric"),
+            match settings.win_metric {
+                WinMetricPreset::Unlimited => ui_text("no_turn_limit"),
+                WinMetricPreset::TownProximity => ui_text("town_proximity"),
+                WinMetricPreset::ObeliskProximity => ui_text("obelisk_proximity"),
+            },
+        ),
+    ]
+}
+
+/// The settings that result from clicking on the given row of `describe_settings`
+fn next_settings(settings: &RuleSettings, row: usize) -> RuleSettings {
+    use SwappingPreset as S;
+    use TimingPreset as T;
+    use TruncationPreset as Tr;
+    use VisibilityPreset as V;
+    use WinMetricPreset as W;
+
+    let mut next = *settings;
+    match row {
+        0 => next.timing = cycle(&[T::Untimed, T::Blitz, T::Rapid, T::Classical], next.timing),
+        1 => {
+            next.visibility = cycle(
+                &[V::Standard, V::TileFog, V::LandFog, V::OnlyHouseFog],
+                next.visibility,
+            )
+        }
+        2 => next.swapping = cycle(&[S::Contiguous, S::Universal, S::Disabled], next.swapping),
+        3 => next.truncation = cycle(&[Tr::Root, Tr::Disabled], next.truncation),
+        _ => {
+            next.win_metric = cycle(
+                &[W::Unlimited, W::TownProximity, W::ObeliskProximity],
+                next.win_metric,
+            )
+        }
+    }
+    next
+}
+
 #[derive(Clone)]
 pub struct Lobby {
     pub board: Board,
@@ -36,8 +122,12 @@ pub struct Lobby {
     pub room_code: RoomCode,
     pub players: Vec<LobbyPlayerMessage>,
     pub player_index: u64,
+    pub spectators: u64,
+    /// Rules the game will start with, for lobbies where the host can customise them
+    pub rule_settings: Option<RuleSettings>,
     pub mapped_board: MappedBoard,
     pub editing_mode: BoardEditingMode,
+    pub board_editor: BoardEditorState,
     pub copied_code: bool,
     pub aesthetics: AestheticDepot,
     pub timing: TimingDepot,
@@ -51,14 +141,15 @@ impl Lobby {
         player_index: u64,
         board: Board,
         map_texture: TextureHandle,
+        theme: &Theme,
     ) -> Self {
         let player_colors: Vec<_> = players
             .iter()
-            .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2))
+            .map(|p| theme.player_color(p.color))
             .collect();
 
         let aesthetics = AestheticDepot {
-            theme: Theme::day(),
+            theme: theme.clone(),
             qs_tick: 0,
             map_texture,
             player_colors,
@@ -72,8 +163,11 @@ impl Lobby {
             mapped_board: MappedBoard::new(ctx, &aesthetics, &board, 1, 1, true),
             players,
             player_index,
+            spectators: 0,
+            rule_settings: None,
             board,
             editing_mode: BoardEditingMode::None,
+            board_editor: BoardEditorState::default(),
             copied_code: false,
             aesthetics,
             timing: TimingDepot::default(),
@@ -120,11 +214,11 @@ impl Lobby {
 
                     if self.players.len() == 1 {
                         if self.copied_code {
-                            let text = TextHelper::heavy("Copied link", 10.0, None, ui);
+                            let text = TextHelper::heavy(ui_text("copied_link"), 10.0, None, ui);
                             text.paint(Color32::WHITE, ui, false);
                         }
 
-                        let text = TextHelper::heavy("COPY GAME LINK", 14.0, None, ui);
+                        let text = TextHelper::heavy(ui_text("copy_game_link"), 14.0, None, ui);
                         if text
                             .full_button(
                                 theme.button_primary,
@@ -156,7 +250,7 @@ impl Lobby {
                         theme.text.lighten().lighten()
                     };
 
-                    let text = TextHelper::heavy("START GAME", 14.0, None, ui);
+                    let text = TextHelper::heavy(ui_text("start_game"), 14.0, None, ui);
                     if text
                         .full_button(
                             start_button_color,
@@ -223,9 +317,51 @@ impl Lobby {
                         ));
                     }
 
+                    if self.spectators > 0 {
+                        ui.add_space(12.0);
+                        let label = if self.spectators == 1 {
+                            "1 spectator watching".to_string()
+                        } else {
+                            format!("{} spectators watching", self.spectators)
+                        };
+                        ui.label(RichText::new(label).color(Color32::WHITE));
+                    }
+
+                    if let Some(settings) = &self.rule_settings {
+                        ui.add_space(12.0);
+                        let is_host = self.player_index == 0;
+                        let label = if is_host {
+                            "Rules (click to change):"
+                        } else {
+                            "Rules:"
+                        };
+                        ui.label(RichText::new(label).color(Color32::WHITE));
+
+                        for (row, (name, value)) in describe_settings(settings).iter().enumerate() {
+                            let line = format!("{name}: {value}");
+                            let text = TextHelper::heavy(&line, 10.0, None, ui);
+                            if is_host {
+                                if text
+                                    .button(
+                                        Color32::WHITE.diaphanize(),
+                                        theme.text,
+                                        &self.aesthetics.map_texture,
+                                        ui,
+                                    )
+                                    .clicked()
+                                {
+                                    msg =
+                                        Some(PlayerMessage::SetRules(next_settings(settings, row)));
+                                }
+                            } else {
+                                text.paint(Color32::WHITE, ui, false);
+                            }
+                        }
+                    }
+
                     ui.add_space(32.0);
 
-                    let text = TextHelper::heavy("EDIT BOARD", 10.0, None, ui);
+                    let text = TextHelper::heavy(ui_text("edit_board"), 10.0, None, ui);
                     if text
                         .button(
                             Color32::WHITE.diaphanize(),
@@ -260,9 +396,10 @@ impl Lobby {
                 &mut self.board,
                 &mut self.mapped_board,
                 &mut self.editing_mode,
+                &mut self.board_editor,
                 &self.aesthetics.player_colors,
             )
-            .render(true, &mut lobby_ui, theme, &self.aesthetics.map_texture)
+            .render(&mut lobby_ui, theme, &self.aesthetics.map_texture)
             {
                 msg = Some(board_update);
                 self.mapped_board.remap_texture(
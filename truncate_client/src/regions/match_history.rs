@@ -0,0 +1,103 @@
+use eframe::egui::{self, ScrollArea};
+use epaint::{vec2, Color32, TextureHandle};
+use truncate_core::messages::{MatchHistory, MatchHistoryEntry};
+
+use crate::utils::{
+    locale::{ui_format, ui_text},
+    text::TextHelper,
+    Lighten, Theme,
+};
+
+pub enum MatchHistoryAction {
+    Replay(String),
+    BackToMenu,
+}
+
+fn describe_game(game: &MatchHistoryEntry) -> (String, String) {
+    let opponents = if game.opponents.is_empty() {
+        ui_text("nobody").to_string()
+    } else {
+        game.opponents.join(", ").to_uppercase()
+    };
+    let result = if game.won {
+        ui_text("match_won")
+    } else {
+        ui_text("match_lost")
+    };
+    let rating_change = match game.rating_change {
+        Some(change) => format!(" ({change:+})"),
+        None => String::new(),
+    };
+    let duration = ui_format(
+        "minutes_seconds",
+        &[
+            ("minutes", &(game.duration_secs / 60)),
+            ("seconds", &format!("{:02}", game.duration_secs % 60)),
+        ],
+    );
+
+    (
+        ui_format(
+            "match_result",
+            &[("result", &result), ("opponents", &opponents)],
+        ),
+        ui_format(
+            "match_duration",
+            &[("duration", &duration), ("rating_change", &rating_change)],
+        ),
+    )
+}
+
+pub fn render_match_history(
+    history: &MatchHistory,
+    ui: &mut egui::Ui,
+    theme: &Theme,
+    map_texture: &TextureHandle,
+) -> Option<MatchHistoryAction> {
+    let mut action = None;
+
+    ui.style_mut().spacing.item_spacing = vec2(6.0, 6.0);
+
+    let text = TextHelper::heavy(ui_text("back_to_menu"), 14.0, None, ui);
+    if text
+        .full_button(theme.button_primary, theme.text, map_texture, ui)
+        .clicked()
+    {
+        action = Some(MatchHistoryAction::BackToMenu);
+    }
+
+    let rating = ui_format("rating", &[("rating", &history.rating)]);
+    TextHelper::heavy(&rating, 14.0, None, ui).paint(Color32::WHITE, ui, true);
+
+    if history.games.is_empty() {
+        TextHelper::light(ui_text("no_finished_games"), 14.0, None, ui).paint(
+            theme.text.lighten(),
+            ui,
+            true,
+        );
+    }
+
+    ScrollArea::new([false, true]).show(ui, |ui| {
+        for game in &history.games {
+            let (headline, byline) = describe_game(game);
+            TextHelper::heavy(&headline, 12.0, Some(ui.available_width()), ui).paint(
+                Color32::WHITE,
+                ui,
+                false,
+            );
+            TextHelper::light(&byline, 12.0, None, ui).paint(Color32::WHITE, ui, false);
+
+            let text = TextHelper::heavy(ui_text("watch_replay"), 10.0, None, ui);
+            if text
+                .full_button(theme.button_secondary, theme.text, map_texture, ui)
+                .clicked()
+            {
+                action = Some(MatchHistoryAction::Replay(game.game_id.clone()));
+            }
+
+            ui.add_space(12.0);
+        }
+    });
+
+    action
+}
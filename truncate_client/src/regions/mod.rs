@@ -1,7 +1,10 @@
+pub mod account;
 pub mod active_game;
 pub mod generator;
 pub mod lobby;
+pub mod match_history;
 pub mod native_menu;
+pub mod puzzle_editor;
 pub mod replayer;
 pub mod single_player;
 pub mod tutorial;
@@ -1,18 +1,23 @@
 use eframe::egui;
 
 use truncate_core::{
-    board::Board, generation::BoardSeed, messages::LobbyPlayerMessage,
-    npc::scoring::NPCPersonality, rules::GameRules,
+    board::Board,
+    game::{GAME_COLOR_BLUE, GAME_COLOR_RED},
+    generation::BoardSeed,
+    messages::LobbyPlayerMessage,
+    npc::scoring::NPCPersonality,
+    rules::GameRules,
 };
 
 use crate::{
     app_inner::GameStatus,
     app_outer::OuterApplication,
     regions::{
-        active_game::HeaderType, generator::GeneratorState, lobby::Lobby,
-        single_player::SinglePlayerState, tutorial::TutorialState,
+        account::AccountState, active_game::HeaderType, generator::GeneratorState, lobby::Lobby,
+        puzzle_editor::PuzzleEditorState, single_player::SinglePlayerState,
+        tutorial::TutorialState,
     },
-    utils,
+    utils::{self, macros::current_time},
 };
 
 use truncate_core::messages::PlayerMessage;
@@ -38,7 +43,7 @@ pub fn render_native_menu_if_required(
             if ui.button("Tutorial: Rules").clicked() {
                 return Some(GameStatus::Tutorial(TutorialState::new(
                     "rules".to_string(),
-                    utils::includes::rules(outer.launched_at_day),
+                    utils::includes::rules(outer.locale, outer.launched_at_day),
                     ui.ctx(),
                     outer.map_texture.clone(),
                     &outer.theme,
@@ -55,17 +60,27 @@ pub fn render_native_menu_if_required(
                         LobbyPlayerMessage {
                             name: "You".into(),
                             index: 0,
-                            color: (128, 128, 255),
+                            color: GAME_COLOR_BLUE,
                         },
                         LobbyPlayerMessage {
                             name: "Computer".into(),
                             index: 1,
-                            color: (255, 80, 80),
+                            color: GAME_COLOR_RED,
                         },
                     ],
                     0,
                     board,
                     outer.map_texture.clone(),
+                    &outer.theme,
+                )));
+            }
+            if ui.button("Puzzle Editor").clicked() {
+                return Some(GameStatus::PuzzleEditor(PuzzleEditorState::new(
+                    ui.ctx(),
+                    outer.map_texture.clone(),
+                    &outer.theme,
+                    GameRules::latest(Some(outer.launched_at_day)).0,
+                    (current_time!().as_micros() % 243985691) as u32,
                 )));
             }
             if ui.button("Behemoth").clicked() {
@@ -96,6 +111,27 @@ pub fn render_native_menu_if_required(
                 });
                 return Some(GameStatus::PendingCreate);
             }
+            if let Some(player_token) = outer.logged_in_as.clone() {
+                if ui.button("Find Match").clicked() {
+                    send_to_server(PlayerMessage::FindMatch {
+                        player_token,
+                        player_name: outer.name.clone(),
+                        effective_day: outer.launched_at_day,
+                        rules_generation: None,
+                    });
+                    return Some(GameStatus::PendingMatch(None));
+                }
+                if ui.button("Match History").clicked() {
+                    send_to_server(PlayerMessage::RequestMatchHistory(player_token));
+                    return Some(GameStatus::MatchHistory(None));
+                }
+                if ui.button("Account").clicked() {
+                    return Some(GameStatus::Account(AccountState::new(
+                        outer.name.clone(),
+                        None,
+                    )));
+                }
+            }
             ui.text_edit_singleline(room_code);
             if ui.button("Join Game").clicked() {
                 send_to_server(PlayerMessage::JoinGame(
@@ -105,6 +141,10 @@ pub fn render_native_menu_if_required(
                 ));
                 return Some(GameStatus::PendingJoin(room_code.clone()));
             }
+            if ui.button("Spectate Game").clicked() {
+                send_to_server(PlayerMessage::SpectateGame(room_code.clone()));
+                return Some(GameStatus::PendingJoin(room_code.clone()));
+            }
             if let Some(existing_token) = token {
                 ui.label("Existing game found, would you like to rejoin?");
                 if ui.button("Rejoin").clicked() {
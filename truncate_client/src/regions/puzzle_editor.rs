@@ -0,0 +1,314 @@
+use eframe::egui::{self, Align, Layout, RichText};
+use epaint::{vec2, Color32, TextureHandle};
+
+use truncate_core::{
+    board::Board,
+    game::{Game, GAME_COLOR_BLUE, GAME_COLOR_RED},
+    puzzle::{CustomPuzzle, MAX_HAND_SIZE},
+    rules::GameRules,
+};
+
+use crate::{
+    app_outer::{Backchannel, BackchannelMsg, ShareType},
+    lil_bits::{BoardEditorState, EditorUI},
+    utils::{
+        depot::{AestheticDepot, TimingDepot},
+        locale::{ui_format, ui_text},
+        mapper::MappedBoard,
+        text::TextHelper,
+        Lighten, Theme,
+    },
+};
+
+use super::lobby::{cycle, BoardEditingMode};
+
+const NPCS: [&str; 4] = ["jet", "mellite", "opal", "quartz"];
+const MOVE_LIMITS: [Option<u32>; 6] = [None, Some(2), Some(3), Some(5), Some(8), Some(12)];
+
+pub enum PuzzleEditorAction {
+    Play(CustomPuzzle),
+    BackToMenu,
+}
+
+pub struct PuzzleEditorState {
+    pub board: Board,
+    /// The tiles each player starts with, indexed by player number
+    pub hands: [String; 2],
+    npc: &'static str,
+    human_player: usize,
+    move_limit: Option<u32>,
+    rules_generation: u32,
+    tile_seed: u32,
+    mapped_board: MappedBoard,
+    editing_mode: BoardEditingMode,
+    board_editor: BoardEditorState,
+    aesthetics: AestheticDepot,
+    timing: TimingDepot,
+    copied_code: bool,
+}
+
+fn player_colors(theme: &Theme, human_player: usize) -> Vec<Color32> {
+    let colors = [GAME_COLOR_BLUE, GAME_COLOR_RED].map(|color| theme.player_color(color));
+    if human_player == 0 {
+        colors.to_vec()
+    } else {
+        colors.into_iter().rev().collect()
+    }
+}
+
+pub fn puzzle_link(code: &str) -> String {
+    #[cfg(target_arch = "wasm32")]
+    let host = web_sys::window()
+        .unwrap()
+        .location()
+        .host()
+        .unwrap_or_else(|_| "truncate.town".into());
+    #[cfg(not(target_arch = "wasm32"))]
+    let host = "truncate.town";
+
+    format!("https://{host}/puzzle/?j=CUSTOM:{code}")
+}
+
+impl PuzzleEditorState {
+    pub fn new(
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        theme: &Theme,
+        rules_generation: u32,
+        tile_seed: u32,
+    ) -> Self {
+        let mut board = Board::new(9, 9);
+        board.grow();
+
+        // Start from a regular opening hand for each player, for the author to tweak
+        let mut game = Game::new(
+            9,
+            9,
+            Some(tile_seed as u64),
+            GameRules::generation(rules_generation),
+        );
+        game.add_player(ui_text("player_you").into());
+        game.add_player(ui_text("player_computer").into());
+        let hands = [0, 1].map(|p| game.players[p].hand.to_string());
+
+        let aesthetics = AestheticDepot {
+            theme: theme.clone(),
+            qs_tick: 0,
+            map_texture,
+            player_colors: player_colors(theme, 0),
+            destruction_tick: 0.0,
+            destruction_duration: 0.0,
+        };
+
+        Self {
+            mapped_board: MappedBoard::new(ctx, &aesthetics, &board, 1, 1, true),
+            board,
+            hands,
+            npc: NPCS[0],
+            human_player: 0,
+            move_limit: None,
+            rules_generation,
+            tile_seed,
+            editing_mode: BoardEditingMode::None,
+            board_editor: BoardEditorState::default(),
+            aesthetics,
+            timing: TimingDepot::default(),
+            copied_code: false,
+        }
+    }
+
+    pub fn puzzle(&self) -> CustomPuzzle {
+        CustomPuzzle {
+            board: self.board.clone(),
+            hands: self.hands.iter().map(|h| h.chars().collect()).collect(),
+            npc: self.npc.to_string(),
+            human_player: self.human_player,
+            move_limit: self.move_limit,
+            rules_generation: self.rules_generation,
+            tile_seed: self.tile_seed,
+        }
+    }
+
+    fn remap_board(&mut self, ctx: &egui::Context) {
+        self.mapped_board.remap_texture(
+            ctx,
+            &self.aesthetics,
+            &self.timing,
+            None,
+            None,
+            &self.board,
+        );
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        backchannel: &Backchannel,
+    ) -> Option<PuzzleEditorAction> {
+        let render_space = ui.available_rect_before_wrap();
+
+        if !matches!(self.editing_mode, BoardEditingMode::None) {
+            let mut editor_ui = ui.child_ui(render_space, Layout::bottom_up(Align::RIGHT));
+            if EditorUI::new(
+                &mut self.board,
+                &mut self.mapped_board,
+                &mut self.editing_mode,
+                &mut self.board_editor,
+                &self.aesthetics.player_colors,
+            )
+            .render(&mut editor_ui, theme, &self.aesthetics.map_texture)
+            .is_some()
+            {
+                self.copied_code = false;
+                self.remap_board(ui.ctx());
+            }
+            return None;
+        }
+
+        let mut action = None;
+        let map_texture = self.aesthetics.map_texture.clone();
+        let mut ui = ui.child_ui(render_space, Layout::top_down(Align::LEFT));
+        ui.style_mut().spacing.item_spacing = vec2(6.0, 6.0);
+
+        let text = TextHelper::heavy(ui_text("back_to_menu"), 14.0, None, &mut ui);
+        if text
+            .full_button(theme.button_primary, theme.text, &map_texture, &mut ui)
+            .clicked()
+        {
+            action = Some(PuzzleEditorAction::BackToMenu);
+        }
+
+        ui.add_space(12.0);
+
+        let text = TextHelper::heavy(ui_text("edit_board"), 12.0, None, &mut ui);
+        if text
+            .full_button(theme.button_secondary, theme.text, &map_texture, &mut ui)
+            .clicked()
+        {
+            self.editing_mode = BoardEditingMode::Land;
+        }
+
+        ui.add_space(12.0);
+
+        let npc_player = (self.human_player + 1) % 2;
+        for (label, player) in [
+            (ui_text("your_starting_tiles"), self.human_player),
+            (ui_text("computer_starting_tiles"), npc_player),
+        ] {
+            ui.label(RichText::new(label).color(Color32::WHITE));
+            if hand_input(&mut self.hands[player], theme, &mut ui).changed() {
+                self.hands[player] = self.hands[player]
+                    .chars()
+                    .filter(|c| c.is_ascii_alphabetic())
+                    .map(|c| c.to_ascii_uppercase())
+                    .take(MAX_HAND_SIZE)
+                    .collect();
+                self.copied_code = false;
+            }
+        }
+
+        ui.add_space(12.0);
+        ui.label(RichText::new(ui_text("puzzle_settings")).color(Color32::WHITE));
+
+        let settings = [
+            ui_format(
+                "puzzle_computer",
+                &[("npc", &self.npc.to_ascii_uppercase())],
+            ),
+            if self.human_player == 0 {
+                ui_text("you_move_first").to_string()
+            } else {
+                ui_text("you_move_second").to_string()
+            },
+            match self.move_limit {
+                Some(limit) => ui_format("goal_win_in", &[("limit", &limit)]),
+                None => ui_text("goal_win").to_string(),
+            },
+        ];
+        for (row, line) in settings.iter().enumerate() {
+            let text = TextHelper::heavy(line, 10.0, None, &mut ui);
+            if text
+                .button(Color32::WHITE, theme.text, &map_texture, &mut ui)
+                .clicked()
+            {
+                match row {
+                    0 => self.npc = cycle(&NPCS, self.npc),
+                    1 => {
+                        // Swap the hands along with the seats, so each side keeps its tiles
+                        self.human_player = npc_player;
+                        self.hands.swap(0, 1);
+                        self.aesthetics.player_colors =
+                            player_colors(&self.aesthetics.theme, self.human_player);
+                        self.remap_board(ui.ctx());
+                    }
+                    _ => self.move_limit = cycle(&MOVE_LIMITS, self.move_limit),
+                }
+                self.copied_code = false;
+            }
+        }
+
+        ui.add_space(12.0);
+
+        let puzzle = self.puzzle();
+        let code = puzzle.to_code();
+        if CustomPuzzle::from_code(&code).is_err() {
+            TextHelper::light(
+                ui_text("puzzle_needs_town_artifact_tiles"),
+                12.0,
+                Some(ui.available_width()),
+                &mut ui,
+            )
+            .paint(theme.text.lighten(), &mut ui, true);
+            return action;
+        }
+
+        let text = TextHelper::heavy(ui_text("play_puzzle"), 14.0, None, &mut ui);
+        if text
+            .full_button(theme.button_primary, theme.text, &map_texture, &mut ui)
+            .clicked()
+        {
+            action = Some(PuzzleEditorAction::Play(puzzle));
+        }
+
+        let text = TextHelper::heavy(ui_text("copy_puzzle_link"), 14.0, None, &mut ui);
+        if text
+            .full_button(theme.button_secondary, theme.text, &map_texture, &mut ui)
+            .clicked()
+        {
+            let link = puzzle_link(&code);
+            if backchannel.is_open() {
+                backchannel.send_msg(BackchannelMsg::Copy {
+                    text: link,
+                    share: ShareType::Url,
+                });
+            } else {
+                ui.ctx().output_mut(|o| o.copied_text = link);
+            }
+            self.copied_code = true;
+        }
+
+        if self.copied_code {
+            TextHelper::heavy(ui_text("copied_link"), 10.0, None, &mut ui).paint(
+                Color32::WHITE,
+                &mut ui,
+                false,
+            );
+        }
+
+        action
+    }
+}
+
+fn hand_input(value: &mut String, theme: &Theme, ui: &mut egui::Ui) -> egui::Response {
+    ui.add(
+        egui::TextEdit::singleline(value)
+            .min_size(vec2(0.0, theme.letter_size * 0.75))
+            .text_color(Color32::WHITE)
+            .vertical_align(Align::BOTTOM)
+            .font(egui::FontId::new(
+                theme.letter_size / 2.0,
+                egui::FontFamily::Name("Truncate-Heavy".into()),
+            )),
+    )
+}
@@ -1,17 +1,28 @@
-use eframe::egui;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, OnceLock};
+#[cfg(not(target_arch = "wasm32"))]
+use truncate_core::judge::WordDict;
+
+use eframe::egui::{self, Align, Layout};
 use epaint::{vec2, Color32, TextureHandle};
 use instant::Duration;
 use truncate_core::{
     game::Game,
     moves::Move,
+    npc::{
+        analysis::MoveAnalysis,
+        scoring::{NPCParams, NPCPersonality, ScoreComponent},
+    },
     reporting::{BoardChange, BoardChangeAction, BoardChangeDetail, Change},
 };
 
 use crate::{
-    app_outer::Backchannel,
+    app_outer::{Backchannel, BackchannelMsg},
+    lil_bits::BattleUI,
     utils::{
-        depot::{AestheticDepot, GameplayDepot, TimingDepot},
+        depot::{AestheticDepot, GameplayDepot, TimingDepot, TruncateDepot},
         game_evals::get_main_dict,
+        locale::{ui_format, ui_text},
         mapper::MappedBoard,
         text::TextHelper,
         timing::get_qs_tick,
@@ -37,11 +48,159 @@ impl PlaybackSpeed {
     }
 }
 
+/// How many moves apart the cached copies of the game are, so that jumping around
+/// the replay doesn't need to re-simulate from the very first move
+const SNAPSHOT_INTERVAL: usize = 10;
+
+/// Reviews each move of a replay against the NPC's best move, one move at a time
+#[derive(Clone)]
+struct Analysis {
+    /// The game just before the next move to be analysed
+    game: Game,
+    /// The review of each move analysed so far, in the order they were played
+    results: Vec<Option<MoveAnalysis>>,
+    waiting_on_backchannel: Option<String>,
+    /// The review of the next move, filled in by a worker thread when there's no backchannel
+    #[cfg(not(target_arch = "wasm32"))]
+    waiting_on_thread: Option<Arc<OnceLock<Option<MoveAnalysis>>>>,
+    /// A copy of the dictionary for the worker threads, so they don't hold the shared one
+    #[cfg(not(target_arch = "wasm32"))]
+    dict: Option<Arc<WordDict>>,
+}
+
+impl Analysis {
+    fn new(base_game: &Game) -> Self {
+        Self {
+            game: base_game.clone(),
+            results: vec![],
+            waiting_on_backchannel: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            waiting_on_thread: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            dict: None,
+        }
+    }
+
+    fn finished(&self, move_sequence: &[Move]) -> bool {
+        self.results.len() >= move_sequence.len()
+    }
+
+    /// Makes progress on analysing the next move, in the background if the outer host allows
+    fn step(&mut self, move_sequence: &[Move], backchannel: &Backchannel) {
+        let Some(played) = move_sequence.get(self.results.len()) else {
+            return;
+        };
+        let npc_params = NPCPersonality::jet().params;
+
+        let analysis = if backchannel.is_open() {
+            match &self.waiting_on_backchannel {
+                Some(pending_msg) => {
+                    let Some(msg_response) = backchannel.send_msg(BackchannelMsg::QueryFor {
+                        id: pending_msg.clone(),
+                    }) else {
+                        return;
+                    };
+                    self.waiting_on_backchannel = None;
+                    serde_json::from_str(&msg_response)
+                        .expect("Backchannel should be sending valid JSON")
+                }
+                None => {
+                    self.waiting_on_backchannel =
+                        backchannel.send_msg(BackchannelMsg::AnalyseMove {
+                            game: self.game.clone(),
+                            played: played.clone(),
+                            npc_params,
+                        });
+                    return;
+                }
+            }
+        } else {
+            let Some(analysis) = self.analyse_locally(played, npc_params) else {
+                return;
+            };
+            analysis
+        };
+
+        self.results.push(analysis);
+
+        let dict_lock = get_main_dict();
+        let dict = dict_lock.as_ref().unwrap();
+        _ = self
+            .game
+            .play_turn(played.clone(), Some(dict), Some(dict), None);
+    }
+
+    /// Analyses the move on a worker thread so the replay keeps drawing,
+    /// returning the analysis once the thread has finished
+    #[cfg(not(target_arch = "wasm32"))]
+    fn analyse_locally(
+        &mut self,
+        played: &Move,
+        npc_params: NPCParams,
+    ) -> Option<Option<MoveAnalysis>> {
+        if let Some(pending) = &self.waiting_on_thread {
+            let analysis = pending.get()?.clone();
+            self.waiting_on_thread = None;
+            return Some(analysis);
+        }
+
+        let dict = self
+            .dict
+            .get_or_insert_with(|| {
+                Arc::new(
+                    get_main_dict()
+                        .clone()
+                        .expect("Dictionaries should be loaded"),
+                )
+            })
+            .clone();
+        let pending = Arc::new(OnceLock::new());
+        self.waiting_on_thread = Some(pending.clone());
+
+        let game = self.game.clone();
+        let played = played.clone();
+        std::thread::spawn(move || {
+            _ = pending.set(game.analyse_move(&played, &dict, &npc_params));
+        });
+
+        None
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn analyse_locally(
+        &mut self,
+        played: &Move,
+        npc_params: NPCParams,
+    ) -> Option<Option<MoveAnalysis>> {
+        Some(crate::utils::game_evals::client_analyse_move(
+            &self.game,
+            played,
+            &npc_params,
+        ))
+    }
+}
+
+/// Explains what a player gains by scoring well on a component of the NPC's scoring
+fn describe_reason(component: &ScoreComponent) -> &'static str {
+    match component {
+        ScoreComponent::RacedDefense => ui_text("reason_raced_defense"),
+        ScoreComponent::RacedAttack => ui_text("reason_raced_attack"),
+        ScoreComponent::SelfDefense => ui_text("reason_self_defense"),
+        ScoreComponent::SelfAttack => ui_text("reason_self_attack"),
+        ScoreComponent::DirectDefence => ui_text("reason_direct_defence"),
+        ScoreComponent::DirectAttack => ui_text("reason_direct_attack"),
+        ScoreComponent::WordValidity => ui_text("reason_word_validity"),
+        ScoreComponent::WordLength => ui_text("reason_word_length"),
+        ScoreComponent::WordExtensibility => ui_text("reason_word_extensibility"),
+    }
+}
+
 #[derive(Clone)]
 pub struct ReplayerState {
-    as_player: usize,
     base_game: Game,
     game: Game,
+    /// The game after every `SNAPSHOT_INTERVAL` moves, starting with the state after the first interval
+    snapshots: Vec<Game>,
     map_texture: TextureHandle,
     mapped_board: MappedBoard,
     theme: Theme,
@@ -49,6 +208,8 @@ pub struct ReplayerState {
     next_move: usize,
     played_at_tick: Option<u64>,
     playback_speed: PlaybackSpeed,
+    paused: bool,
+    analysis: Option<Analysis>,
     aesthetics: AestheticDepot,
     timing: TimingDepot,
     gameplay: GameplayDepot,
@@ -68,11 +229,11 @@ impl ReplayerState {
         let player_colors: Vec<_> = game
             .players
             .iter()
-            .map(|p| Color32::from_rgb(p.color.0, p.color.1, p.color.2))
+            .map(|p| theme.player_color(p.color))
             .collect();
 
         let aesthetics = AestheticDepot {
-            theme: Theme::day(),
+            theme: theme.clone(),
             qs_tick: 0,
             map_texture: map_texture.clone(),
             player_colors,
@@ -91,14 +252,15 @@ impl ReplayerState {
             last_battle_origin: None,
             npc: None,
             remaining_turns: None,
+            disconnected_players: Default::default(),
         };
 
         game.start();
 
         Self {
-            as_player,
             base_game: game.clone(),
             game,
+            snapshots: vec![],
             map_texture,
             mapped_board,
             theme,
@@ -106,15 +268,18 @@ impl ReplayerState {
             next_move: 0,
             played_at_tick: None,
             playback_speed: PlaybackSpeed::Regular,
+            paused: false,
+            analysis: None,
             aesthetics,
             timing: TimingDepot::default(),
             gameplay,
         }
     }
 
-    pub fn play_next_turn(&mut self, current_time: Duration, qs_tick: u64) {
+    /// Plays the next move of the sequence, returning false if there are none left
+    fn apply_next_move(&mut self) -> bool {
         let Some(next_move) = self.move_sequence.get(self.next_move) else {
-            return;
+            return false;
         };
 
         let dict_lock = get_main_dict();
@@ -125,33 +290,58 @@ impl ReplayerState {
 
         self.next_move += 1;
 
+        if self.next_move == (self.snapshots.len() + 1) * SNAPSHOT_INTERVAL {
+            self.snapshots.push(self.game.clone());
+        }
+
+        true
+    }
+
+    pub fn play_next_turn(&mut self, current_time: Duration, qs_tick: u64) {
+        if self.apply_next_move() {
+            self.show_current_turn(current_time, qs_tick);
+        }
+    }
+
+    /// Rewinds or fast-forwards the replay to just after the given number of moves have been played
+    pub fn jump_to_turn(&mut self, turn: usize, current_time: Duration, qs_tick: u64) {
+        let turn = turn.min(self.move_sequence.len());
+
+        // Start from the latest snapshot before the target turn's move,
+        // so that playing that move shows its changes on the board.
+        let snapshot = (turn.saturating_sub(1) / SNAPSHOT_INTERVAL).min(self.snapshots.len());
+        self.game = match snapshot {
+            0 => self.base_game.clone(),
+            n => self.snapshots[n - 1].clone(),
+        };
+        self.next_move = snapshot * SNAPSHOT_INTERVAL;
+
+        while self.next_move < turn && self.apply_next_move() {}
+
+        self.show_current_turn(current_time, qs_tick);
+    }
+
+    /// Updates what's being shown to match the most recently played move
+    fn show_current_turn(&mut self, current_time: Duration, qs_tick: u64) {
         self.timing.last_turn_change = current_time;
 
         self.gameplay.next_player_number = self.game.next_player.map(|p| p as u64);
-        self.gameplay.changes = self.game.recent_changes.clone();
+        self.gameplay.changes = if self.next_move == 0 {
+            vec![]
+        } else {
+            self.game.recent_changes.clone()
+        };
 
         let battle_occurred = self
-            .game
-            .recent_changes
+            .gameplay
+            .changes
             .iter()
             .any(|change| matches!(change, Change::Battle(_)));
 
-        self.gameplay.last_battle_origin =
-            self.game
-                .recent_changes
-                .iter()
-                .find_map(|change| match change {
-                    Change::Board(BoardChange {
-                        detail: BoardChangeDetail { coordinate, .. },
-                        action: BoardChangeAction::Added,
-                    }) => Some(*coordinate),
-                    _ => None,
-                });
-
         if battle_occurred {
             self.gameplay.last_battle_origin =
-                self.game
-                    .recent_changes
+                self.gameplay
+                    .changes
                     .iter()
                     .find_map(|change| match change {
                         Change::Board(BoardChange {
@@ -172,12 +362,255 @@ impl ReplayerState {
         }
     }
 
+    fn render_controls(&mut self, ui: &mut egui::Ui, theme: &Theme, current_time: Duration) {
+        let now = get_qs_tick(current_time);
+        let total_turns = self.move_sequence.len();
+        let finished = self.next_move >= total_turns;
+
+        ui.horizontal(|ui| {
+            let text = TextHelper::heavy(ui_text("prev"), 12.0, None, ui);
+            if text
+                .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.paused = true;
+                self.jump_to_turn(self.next_move.saturating_sub(1), current_time, now);
+            }
+
+            let label = if self.paused || finished {
+                ui_text("play")
+            } else {
+                ui_text("pause")
+            };
+            let text = TextHelper::heavy(label, 12.0, None, ui);
+            if text
+                .button(theme.button_primary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                if finished {
+                    self.jump_to_turn(0, current_time, now);
+                    self.paused = false;
+                } else {
+                    self.paused = !self.paused;
+                }
+            }
+
+            let text = TextHelper::heavy(ui_text("next"), 12.0, None, ui);
+            if text
+                .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.paused = true;
+                self.play_next_turn(current_time, now);
+            }
+
+            let text = TextHelper::heavy(ui_text("restart"), 12.0, None, ui);
+            if text
+                .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+                .clicked()
+            {
+                self.jump_to_turn(0, current_time, now);
+            }
+
+            if self.analysis.is_none() {
+                let text = TextHelper::heavy(ui_text("analyse"), 12.0, None, ui);
+                if text
+                    .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+                    .clicked()
+                {
+                    self.analysis = Some(Analysis::new(&self.base_game));
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+
+        let mut turn = self.next_move;
+        let timeline = ui.add(egui::Slider::new(&mut turn, 0..=total_turns).show_value(false));
+        if timeline.changed() {
+            self.paused = true;
+            self.jump_to_turn(turn, current_time, now);
+        }
+
+        let progress = ui_format(
+            "turn_of_total",
+            &[("turn", &self.next_move), ("total", &total_turns)],
+        );
+        TextHelper::light(&progress, 12.0, None, ui).paint(Color32::WHITE, ui, false);
+    }
+
+    fn render_analysis(&mut self, ui: &mut egui::Ui, theme: &Theme, current_time: Duration) {
+        let Some(analysis) = &self.analysis else {
+            return;
+        };
+        let now = get_qs_tick(current_time);
+
+        ui.add_space(10.0);
+
+        if !analysis.finished(&self.move_sequence) {
+            let progress = ui_format(
+                "analysing_move",
+                &[
+                    ("move", &(analysis.results.len() + 1)),
+                    ("total", &self.move_sequence.len()),
+                ],
+            );
+            TextHelper::light(&progress, 12.0, None, ui).paint(Color32::WHITE, ui, false);
+        }
+
+        let blunders: Vec<_> = analysis
+            .results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.as_ref().is_some_and(|r| r.is_blunder()))
+            .map(|(index, _)| index + 1)
+            .collect();
+
+        let mut jump_to = None;
+        ui.horizontal_wrapped(|ui| {
+            let label = if blunders.is_empty() {
+                ui_text("no_blunders_found")
+            } else {
+                ui_text("blunders")
+            };
+            TextHelper::light(label, 12.0, None, ui).paint(Color32::WHITE, ui, false);
+
+            for turn in blunders {
+                let label = ui_format("turn_number", &[("turn", &turn)]);
+                let text = TextHelper::heavy(&label, 10.0, None, ui);
+                if text
+                    .button(theme.button_secondary, theme.text, &self.map_texture, ui)
+                    .clicked()
+                {
+                    jump_to = Some(turn);
+                }
+            }
+        });
+
+        let current_result = self
+            .next_move
+            .checked_sub(1)
+            .and_then(|index| analysis.results.get(index))
+            .and_then(|result| result.as_ref());
+
+        if let Some(result) = current_result {
+            let player_name = self
+                .base_game
+                .players
+                .get(result.player)
+                .map(|p| p.name.to_uppercase())
+                .unwrap_or_default();
+
+            let summary = ui_format(
+                "win_chance",
+                &[
+                    ("player", &player_name),
+                    ("before", &format!("{:.0}", result.eval_before * 100.0)),
+                    ("after", &format!("{:.0}", result.eval_played * 100.0)),
+                ],
+            );
+            TextHelper::light(&summary, 12.0, Some(ui.available_width()), ui).paint(
+                Color32::WHITE,
+                ui,
+                false,
+            );
+
+            let verdict = match &result.suggestion {
+                Some(suggestion) if result.is_blunder() => {
+                    let alternative = match suggestion {
+                        Move::Place { tile, position, .. } => {
+                            ui_format("placing_tile", &[("tile", tile), ("position", position)])
+                        }
+                        Move::Swap {
+                            positions: [from, to],
+                            ..
+                        } => ui_format("swapping_tiles", &[("from", from), ("to", to)]),
+                    };
+                    let reasons: Vec<_> = result.reasons.iter().map(describe_reason).collect();
+                    if result.eval_suggested >= 1.0 {
+                        ui_format("blunder_winning", &[("alternative", &alternative)])
+                    } else if reasons.is_empty() {
+                        ui_format(
+                            "blunder_win_chance",
+                            &[
+                                ("alternative", &alternative),
+                                ("chance", &format!("{:.0}", result.eval_suggested * 100.0)),
+                            ],
+                        )
+                    } else {
+                        ui_format(
+                            "blunder_reasons",
+                            &[
+                                ("alternative", &alternative),
+                                ("reasons", &reasons.join(ui_text("reasons_joiner"))),
+                            ],
+                        )
+                    }
+                }
+                Some(_) => ui_text("reasonable_move").to_string(),
+                None => ui_text("same_as_computer").to_string(),
+            };
+            TextHelper::heavy(&verdict, 12.0, Some(ui.available_width()), ui).paint(
+                Color32::WHITE,
+                ui,
+                false,
+            );
+        }
+
+        if let Some(turn) = jump_to {
+            self.paused = true;
+            self.jump_to_turn(turn, current_time, now);
+        }
+    }
+
+    fn render_battles(&self, ui: &mut egui::Ui) {
+        let battles: Vec<_> = self
+            .gameplay
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Battle(battle) => Some(battle),
+                _ => None,
+            })
+            .collect();
+        if battles.is_empty() {
+            return;
+        }
+
+        // Battles are rendered in the same way as a live game, which needs the full set of depots
+        let mut depot = TruncateDepot {
+            interactions: Default::default(),
+            regions: Default::default(),
+            ui_state: Default::default(),
+            board_info: Default::default(),
+            timing: self.timing.clone(),
+            gameplay: self.gameplay.clone(),
+            aesthetics: self.aesthetics.clone(),
+            audio: Default::default(),
+        };
+
+        let desired_battle_width = ui.available_width().min(550.0);
+        let inset = (ui.available_width() - desired_battle_width) / 2.0;
+
+        for battle in battles {
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.add_space(inset);
+                ui.allocate_ui_with_layout(
+                    vec2(desired_battle_width, 0.0),
+                    Layout::top_down(Align::LEFT),
+                    |ui| BattleUI::new(battle, true).render(ui, &mut depot),
+                );
+            });
+        }
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
         theme: &Theme,
         current_time: Duration,
-        _backchannel: &Backchannel,
+        backchannel: &Backchannel,
     ) {
         let start = self
             .played_at_tick
@@ -187,13 +620,20 @@ impl ReplayerState {
 
         self.timing.current_time = current_time.clone();
 
-        if elapsed >= self.playback_speed.ticks() {
+        if !self.paused && elapsed >= self.playback_speed.ticks() {
             self.play_next_turn(current_time, now);
         }
 
+        if let Some(analysis) = &mut self.analysis {
+            analysis.step(&self.move_sequence, backchannel);
+            if !analysis.finished(&self.move_sequence) {
+                ui.ctx().request_repaint();
+            }
+        }
+
         ui.add_space(20.0);
 
-        let text = TextHelper::heavy("BACK TO MENU", 12.0, None, ui);
+        let text = TextHelper::heavy(ui_text("back_to_menu"), 12.0, None, ui);
         if text
             .centered_button(theme.button_primary, theme.text, &self.map_texture, ui)
             .clicked()
@@ -201,22 +641,11 @@ impl ReplayerState {
             back_to_menu();
         }
 
-        ui.add_space(20.0);
+        ui.add_space(10.0);
 
-        let text = TextHelper::heavy("RESTART REPLAY", 12.0, None, ui);
-        if text
-            .centered_button(theme.button_primary, theme.text, &self.map_texture, ui)
-            .clicked()
-        {
-            *self = Self::new(
-                ui.ctx(),
-                self.map_texture.clone(),
-                theme.clone(),
-                self.base_game.clone(),
-                self.move_sequence.clone(),
-                self.as_player,
-            );
-        }
+        self.render_controls(ui, theme, current_time);
+        self.render_analysis(ui, theme, current_time);
+        self.render_battles(ui);
 
         self.mapped_board.remap_texture(
             ui.ctx(),
@@ -8,18 +8,22 @@ use truncate_core::{
     messages::{DailyStats, GamePlayerMessage, GameStateMessage, PlayerMessage},
     moves::Move,
     npc::scoring::NPCPersonality,
-    reporting::WordMeaning,
+    puzzle::CustomPuzzle,
+    reporting::{Change, WordMeaning},
     rules::GameRules,
 };
 
 use crate::{
-    app_outer::{Backchannel, EventDispatcher},
+    app_outer::{Backchannel, BackchannelMsg, EventDispatcher},
     lil_bits::{
         result_modal::{ResultModalAction, ResultModalDaily, ResultModalVariant},
         ResultModalUI,
     },
     utils::{
+        daily::QueuedDailyAttempt,
+        depot::AssistRequest,
         game_evals::{client_best_move, forget, get_main_dict, remember},
+        locale::{ui_format, ui_text},
         text::TextHelper,
         Theme,
     },
@@ -27,6 +31,14 @@ use crate::{
 
 use super::active_game::{ActiveGame, GameLocation, HeaderType};
 
+/// The state to return to if the player takes back their latest move
+#[derive(Clone)]
+struct TakebackPoint {
+    game: Game,
+    turns: usize,
+    turn_reports: usize,
+}
+
 #[derive(Clone)]
 pub struct SinglePlayerState {
     pub name: String,
@@ -49,6 +61,28 @@ pub struct SinglePlayerState {
     hide_splash: bool,
     pub move_sequence: Vec<Move>,
     event_dispatcher: EventDispatcher,
+    takeback_points: Vec<TakebackPoint>,
+    pub hints_used: u32,
+    pub takebacks_used: u32,
+    waiting_on_hint: Option<String>,
+    /// The hand-built puzzle being played, if this isn't a generated board
+    custom_puzzle: Option<CustomPuzzle>,
+}
+
+fn add_players(game: &mut Game, human_starts: bool) {
+    if human_starts {
+        game.add_player(ui_text("player_you").into());
+        game.add_player(ui_text("player_computer").into());
+
+        game.players[0].color = GAME_COLOR_BLUE;
+        game.players[1].color = GAME_COLOR_RED;
+    } else {
+        game.add_player(ui_text("player_computer").into());
+        game.add_player(ui_text("player_you").into());
+
+        game.players[0].color = GAME_COLOR_RED;
+        game.players[1].color = GAME_COLOR_BLUE;
+    }
 }
 
 impl SinglePlayerState {
@@ -73,19 +107,7 @@ impl SinglePlayerState {
             seed.clone().map(|s| s.seed as u64),
             GameRules::generation(rules_generation),
         );
-        if human_starts {
-            game.add_player("You".into());
-            game.add_player("Computer".into());
-
-            game.players[0].color = GAME_COLOR_BLUE;
-            game.players[1].color = GAME_COLOR_RED;
-        } else {
-            game.add_player("Computer".into());
-            game.add_player("You".into());
-
-            game.players[0].color = GAME_COLOR_RED;
-            game.players[1].color = GAME_COLOR_BLUE;
-        }
+        add_players(&mut game, human_starts);
 
         board.cache_special_squares();
         game.board = board.clone();
@@ -136,9 +158,65 @@ impl SinglePlayerState {
             hide_splash: false,
             move_sequence: vec![],
             event_dispatcher,
+            takeback_points: vec![],
+            hints_used: 0,
+            takebacks_used: 0,
+            waiting_on_hint: None,
+            custom_puzzle: None,
         }
     }
 
+    /// Starts a hand-built puzzle, such as one loaded from a shared puzzle code
+    pub fn new_custom(
+        puzzle: CustomPuzzle,
+        ctx: &egui::Context,
+        map_texture: TextureHandle,
+        theme: Theme,
+        event_dispatcher: EventDispatcher,
+    ) -> Self {
+        let header = HeaderType::Summary {
+            title: match puzzle.move_limit {
+                Some(1) => ui_text("win_in_one_move").to_string(),
+                Some(limit) => ui_format("win_in_moves", &[("limit", &limit)]),
+                None => ui_text("custom_puzzle").to_string(),
+            },
+            attempt: None,
+        };
+
+        let mut state = Self::new(
+            "custom".to_string(),
+            ctx,
+            map_texture,
+            theme,
+            puzzle.board.clone(),
+            None,
+            puzzle.rules_generation,
+            puzzle.human_player == 0,
+            header,
+            puzzle.npc_personality(),
+            event_dispatcher,
+        );
+        state.start_custom(puzzle, ctx);
+        state
+    }
+
+    fn start_custom(&mut self, puzzle: CustomPuzzle, ctx: &egui::Context) {
+        let mut game = Game::new(
+            9,
+            9,
+            Some(puzzle.tile_seed as u64),
+            GameRules::generation(puzzle.rules_generation),
+        );
+        add_players(&mut game, self.human_starts);
+        puzzle.apply_to(&mut game);
+        game.start();
+
+        self.install_game(game, None, ctx);
+        self.active_game.depot.board_info.puzzle_code = Some(puzzle.to_code());
+        self.custom_puzzle = Some(puzzle);
+        self.active_game.depot.gameplay.remaining_turns = self.remaining_moves().map(u64::from);
+    }
+
     fn sub_event(&mut self, event: String) {
         self.event_dispatcher
             .event(format!("single_player_{}_{}", self.name, event));
@@ -150,6 +228,12 @@ impl SinglePlayerState {
         ctx: &egui::Context,
         backchannel: &Backchannel,
     ) {
+        if let Some(puzzle) = self.custom_puzzle.clone() {
+            self.sub_event("replay".to_string());
+            self.start_custom(puzzle, ctx);
+            return self.forget_words(backchannel);
+        }
+
         if let Some(seed) = &self.active_game.depot.board_info.board_seed {
             if seed.day.is_some() {
                 match &mut self.header {
@@ -185,19 +269,7 @@ impl SinglePlayerState {
             GameRules::generation(self.rules_generation),
         );
         self.human_starts = human_starts;
-        if self.human_starts {
-            game.add_player("You".into());
-            game.add_player("Computer".into());
-
-            game.players[0].color = GAME_COLOR_BLUE;
-            game.players[1].color = GAME_COLOR_RED;
-        } else {
-            game.add_player("Computer".into());
-            game.add_player("You".into());
-
-            game.players[0].color = GAME_COLOR_RED;
-            game.players[1].color = GAME_COLOR_BLUE;
-        }
+        add_players(&mut game, human_starts);
 
         let mut rand_board = truncate_core::generation::generate_board(seed.clone())
             .expect("Standard seeds should always generate a board")
@@ -207,10 +279,17 @@ impl SinglePlayerState {
         game.board = rand_board;
         game.start();
 
+        self.sub_event("replay".to_string());
+        self.install_game(game, Some(seed), ctx);
+        self.forget_words(backchannel);
+    }
+
+    /// Swaps in a freshly started game, clearing everything left over from the last one
+    fn install_game(&mut self, game: Game, seed: Option<BoardSeed>, ctx: &egui::Context) {
         let mut active_game = ActiveGame::new(
             ctx,
             "SINGLE_PLAYER".into(),
-            Some(seed),
+            seed,
             Some(self.npc.clone()),
             game.players
                 .iter()
@@ -230,8 +309,6 @@ impl SinglePlayerState {
         );
         active_game.depot.ui_state.game_header = self.header.clone();
 
-        self.sub_event("replay".to_string());
-
         self.game = game;
         self.active_game = active_game;
         self.turns = 0;
@@ -239,7 +316,14 @@ impl SinglePlayerState {
         self.winner = None;
         self.move_sequence = vec![];
         self.event_dispatcher = self.event_dispatcher.clone();
+        self.takeback_points = vec![];
+        self.hints_used = 0;
+        self.takebacks_used = 0;
+        self.waiting_on_hint = None;
+    }
 
+    /// The NPC starts each game without the words it learned in the last one
+    fn forget_words(&self, backchannel: &Backchannel) {
         if backchannel.is_open() {
             backchannel.send_msg(crate::app_outer::BackchannelMsg::Forget);
         } else {
@@ -247,6 +331,12 @@ impl SinglePlayerState {
         }
     }
 
+    /// Switches to a newly chosen theme, keeping it for any games we reset to
+    pub fn apply_theme(&mut self, ctx: &egui::Context, theme: &Theme) {
+        self.theme = theme.clone();
+        self.active_game.apply_theme(ctx, theme);
+    }
+
     /// If the server sent through some new word definitions,
     /// dig deep and update all past battles to reference the definitions
     pub fn hydrate_meanings(&mut self, definitions: Vec<(String, Option<Vec<WordMeaning>>)>) {
@@ -280,6 +370,14 @@ impl SinglePlayerState {
         track_events: bool,
     ) -> Result<Vec<String>, ()> {
         let human_player = if self.human_starts { 0 } else { 1 };
+        let human_moving = match next_move {
+            Move::Place { player, .. } | Move::Swap { player, .. } => player == human_player,
+        };
+        let takeback_point = human_moving.then(|| TakebackPoint {
+            game: self.game.clone(),
+            turns: self.turns,
+            turn_reports: self.active_game.turn_reports.len(),
+        });
 
         self.turns += 1;
         let dict_lock = get_main_dict();
@@ -291,8 +389,19 @@ impl SinglePlayerState {
             Ok(winner) => {
                 self.winner = winner;
 
+                if let Some(takeback_point) = takeback_point {
+                    self.takeback_points.push(takeback_point);
+                    self.clear_hint();
+                }
+
+                // Running out of moves in a puzzle with a move limit counts as a loss
+                if human_moving && winner.is_none() && self.remaining_moves() == Some(0) {
+                    self.game.resign_player(human_player);
+                    self.winner = self.game.winner;
+                }
+
                 if track_events {
-                    if let Some(winner) = winner {
+                    if let Some(winner) = self.winner {
                         if winner == human_player {
                             self.sub_event("won".to_string())
                         } else {
@@ -353,35 +462,194 @@ impl SinglePlayerState {
                     }
                 }
 
-                let room_code = self.active_game.depot.gameplay.room_code.clone();
-                let state_message = GameStateMessage {
-                    room_code,
-                    players: self
-                        .game
-                        .players
-                        .iter()
-                        .map(|p| GamePlayerMessage::new(p, &self.game))
-                        .collect(),
-                    player_number: human_player as u64,
-                    next_player_number: self.game.next_player.map(|p| p as u64),
-                    board: self.game.board.clone(),
-                    hand: self.game.players[human_player].hand.clone(),
-                    changes,
-                    game_ends_at: None,
-                    paused: false,
-                    remaining_turns: None,
-                };
+                let state_message = self.state_message(changes);
                 self.active_game.apply_new_state(state_message);
 
                 return Ok(battle_words);
             }
             Err(msg) => {
-                self.active_game.depot.gameplay.error_msg = Some(msg);
+                self.active_game.depot.gameplay.error_msg = Some(msg.to_string());
                 return Err(());
             }
         }
     }
 
+    fn state_message(&self, changes: Vec<Change>) -> GameStateMessage {
+        let human_player = if self.human_starts { 0 } else { 1 };
+
+        GameStateMessage {
+            room_code: self.active_game.depot.gameplay.room_code.clone(),
+            players: self
+                .game
+                .players
+                .iter()
+                .map(|p| GamePlayerMessage::new(p, &self.game))
+                .collect(),
+            player_number: human_player as u64,
+            next_player_number: self.game.next_player.map(|p| p as u64),
+            board: self.game.board.clone(),
+            hand: self.game.players[human_player].hand.clone(),
+            changes,
+            game_ends_at: None,
+            paused: false,
+            remaining_turns: self.remaining_moves().map(u64::from),
+        }
+    }
+
+    /// How many more moves the player can make before failing a puzzle with a move limit
+    fn remaining_moves(&self) -> Option<u32> {
+        let human_player = if self.human_starts { 0 } else { 1 };
+        let move_limit = self.custom_puzzle.as_ref()?.move_limit?;
+
+        Some(move_limit.saturating_sub(self.game.player_turn_count[human_player]))
+    }
+
+    /// Rewinds the game to before the player's latest move,
+    /// undoing the computer's reply along with it
+    fn take_back(&mut self) -> bool {
+        let Some(takeback_point) = self.takeback_points.pop() else {
+            return false;
+        };
+        let human_player = if self.human_starts { 0 } else { 1 };
+
+        self.game = takeback_point.game;
+        self.turns = takeback_point.turns;
+        self.move_sequence.truncate(self.game.turn_count as usize);
+        self.winner = None;
+        self.next_response_at = None;
+        self.waiting_on_backchannel = None;
+        self.takebacks_used += 1;
+        self.clear_hint();
+
+        let state_message = self.state_message(vec![]);
+        self.active_game.apply_new_state(state_message);
+        self.active_game.hand = self.game.players[human_player].hand.clone();
+        self.active_game
+            .turn_reports
+            .truncate(takeback_point.turn_reports);
+
+        self.sub_event("takeback".to_string());
+        true
+    }
+
+    /// Asks the computer what it would play in the player's position
+    fn request_hint(&mut self, backchannel: &Backchannel) {
+        let human_player = if self.human_starts { 0 } else { 1 };
+        let (filtered_board, _) = self.game.filter_game_to_player(human_player);
+
+        self.hints_used += 1;
+        self.sub_event("hint".to_string());
+
+        if backchannel.is_open() {
+            self.waiting_on_hint = backchannel.send_msg(BackchannelMsg::EvalGame {
+                board: filtered_board,
+                rules: self.game.rules.clone(),
+                players: self.game.players.clone(),
+                next_player: human_player,
+                npc_params: self.npc.params,
+            });
+        } else {
+            let mut evaluation_game = self.game.clone();
+            evaluation_game.board = filtered_board;
+
+            let hint = client_best_move(&evaluation_game, &self.npc.params);
+            self.show_hint(hint);
+        }
+    }
+
+    fn show_hint(&mut self, hint: PlayerMessage) {
+        let interactions = &mut self.active_game.depot.interactions;
+        match hint {
+            PlayerMessage::Place(position, tile) => {
+                interactions.highlight_tiles = Some(vec![tile]);
+                interactions.highlight_squares = Some(vec![position]);
+            }
+            PlayerMessage::Swap(from, to) => {
+                interactions.highlight_tiles = None;
+                interactions.highlight_squares = Some(vec![from, to]);
+            }
+            _ => {}
+        }
+    }
+
+    fn clear_hint(&mut self) {
+        self.waiting_on_hint = None;
+        self.active_game.depot.interactions.highlight_tiles = None;
+        self.active_game.depot.interactions.highlight_squares = None;
+    }
+
+    /// Saves the moves of a daily puzzle attempt to the player's account
+    fn persist_daily_moves(&mut self, logged_in_as: &Option<String>) -> Option<PlayerMessage> {
+        let human_player = if self.human_starts { 0 } else { 1 };
+        let day = self.active_game.depot.board_info.board_seed.as_ref()?.day?;
+        let token = logged_in_as.as_ref()?;
+        let attempt = match &self.header {
+            HeaderType::Summary { attempt, .. } => attempt.map(|a| a as u32),
+            _ => None,
+        };
+
+        // Ensure we never pull up an old splash screen without this move
+        self.daily_stats = None;
+
+        Some(PlayerMessage::PersistPuzzleMoves {
+            player_token: token.clone(),
+            day,
+            attempt,
+            human_player: human_player as u32,
+            moves: self.move_sequence.clone(),
+            won: self.winner == Some(human_player),
+            hints: self.hints_used,
+            takebacks: self.takebacks_used,
+        })
+    }
+
+    /// The current daily puzzle attempt, in the form it is queued while offline
+    pub fn queued_daily_attempt(&self) -> Option<QueuedDailyAttempt> {
+        let human_player = if self.human_starts { 0 } else { 1 };
+        let day = self.active_game.depot.board_info.board_seed.as_ref()?.day?;
+        let HeaderType::Summary {
+            attempt: Some(attempt),
+            ..
+        } = &self.header
+        else {
+            return None;
+        };
+        if self.move_sequence.is_empty() {
+            return None;
+        }
+
+        Some(QueuedDailyAttempt::new(
+            day,
+            *attempt,
+            human_player as u32,
+            &self.move_sequence,
+            self.winner == Some(human_player),
+            self.hints_used,
+            self.takebacks_used,
+        ))
+    }
+
+    /// Plays through the moves of an earlier session without animating battles,
+    /// leaving the game untouched if any of them can't be played
+    pub fn resume_moves(&mut self, moves: Vec<Move>, backchannel: &Backchannel) -> bool {
+        let unplayed = self.clone();
+        self.move_sequence = moves.clone();
+
+        let delay = self.game.rules.battle_delay;
+        self.game.rules.battle_delay = 0;
+        let resumed = moves
+            .into_iter()
+            .all(|next_move| self.handle_move(next_move, backchannel, false).is_ok());
+        self.game.rules.battle_delay = delay;
+
+        if !resumed {
+            *self = unplayed;
+        }
+        self.active_game.depot.ui_state.game_header = self.header.clone();
+
+        resumed
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
@@ -408,9 +676,9 @@ impl SinglePlayerState {
             let mut banner_ui = ui.child_ui(top_banner, Layout::left_to_right(Align::Center));
 
             let text = if self.debugging_npc {
-                TextHelper::heavy("CLOSE NPC DEBUGGER", 12.0, None, ui)
+                TextHelper::heavy(ui_text("close_npc_debugger"), 12.0, None, ui)
             } else {
-                TextHelper::heavy("NPC DEBUGGER", 12.0, None, ui)
+                TextHelper::heavy(ui_text("npc_debugger"), 12.0, None, ui)
             };
             if text
                 .centered_button(
@@ -445,13 +713,19 @@ impl SinglePlayerState {
                     GameLocation::Tutorial | GameLocation::Local => {
                         self.splash = Some(ResultModalUI::new_resigning(
                             &mut ui,
-                            "Start again?".to_string(),
+                            ui_text("start_again").to_string(),
                         ))
                     }
                     GameLocation::Online => {
                         self.splash = Some(ResultModalUI::new_resigning(
                             &mut ui,
-                            "Resign this game?".to_string(),
+                            ui_text("resign_this_game").to_string(),
+                        ))
+                    }
+                    GameLocation::Spectating => {
+                        self.splash = Some(ResultModalUI::new_leaving(
+                            &mut ui,
+                            ui_text("stop_watching_this_game").to_string(),
                         ))
                     }
                 }
@@ -460,6 +734,34 @@ impl SinglePlayerState {
             msgs_to_server.push(PlayerMessage::RequestDefinitions(words.clone()));
         }
 
+        let players_turn = self.winner.is_none() && self.game.next_player == Some(human_player);
+        match self.active_game.depot.ui_state.assist_request.take() {
+            Some(AssistRequest::Takeback) if players_turn => {
+                if self.take_back() {
+                    msgs_to_server.extend(self.persist_daily_moves(logged_in_as));
+                } else {
+                    self.active_game.depot.gameplay.error_msg =
+                        Some(ui_text("no_moves_to_take_back").to_string());
+                }
+            }
+            Some(AssistRequest::Hint) if players_turn && self.waiting_on_hint.is_none() => {
+                self.request_hint(backchannel);
+            }
+            _ => {}
+        }
+
+        if let Some(pending_msg) = &self.waiting_on_hint {
+            let msg_response = backchannel.send_msg(BackchannelMsg::QueryFor {
+                id: pending_msg.clone(),
+            });
+            if let Some(msg_response) = msg_response {
+                let hint: PlayerMessage = serde_json::from_str(&msg_response)
+                    .expect("Backchannel should be sending valid JSON");
+                self.waiting_on_hint = None;
+                self.show_hint(hint);
+            }
+        }
+
         if let Some(splash) = &mut self.splash {
             if self.hide_splash == false {
                 let splash_msg = splash.render(
@@ -496,12 +798,20 @@ impl SinglePlayerState {
                         self.game.resign_player(human_player);
                         self.winner = Some(npc_player);
                     }
+                    Some(ResultModalAction::Leave) => {
+                        self.splash = None;
+                        msgs_to_server.push(PlayerMessage::StopSpectating);
+                    }
                     Some(ResultModalAction::SharedText) => {
                         self.sub_event("shared_text".to_string());
                     }
                     Some(ResultModalAction::SharedReplay) => {
                         self.sub_event("shared_replay".to_string());
                     }
+                    Some(ResultModalAction::ViewReplay(attempt_id)) => {
+                        self.sub_event("view_replay".to_string());
+                        msgs_to_server.push(PlayerMessage::LoadReplay(attempt_id));
+                    }
                     None => {}
                 }
             }
@@ -517,7 +827,8 @@ impl SinglePlayerState {
                 .map(|s| s.day)
                 .flatten();
 
-            if let Some(puzzle_day) = is_daily_puzzle {
+            // Offline daily puzzles have no stats to show, so finish like any other puzzle
+            if let (Some(puzzle_day), Some(_)) = (is_daily_puzzle, logged_in_as) {
                 if let Some(token) = logged_in_as {
                     if self.splash.is_none() {
                         msgs_to_server.push(PlayerMessage::RequestStats(token.clone()));
@@ -665,22 +976,7 @@ impl SinglePlayerState {
             if let Ok(battle_words) = self.handle_move(next_move.clone(), backchannel, true) {
                 self.move_sequence.push(next_move.clone());
 
-                if let Some(seed) = &self.active_game.depot.board_info.board_seed {
-                    if seed.day.is_some() {
-                        if let Some(token) = logged_in_as {
-                            msgs_to_server.push(PlayerMessage::PersistPuzzleMoves {
-                                player_token: token.clone(),
-                                day: seed.day.unwrap(),
-                                human_player: human_player as u32,
-                                moves: self.move_sequence.clone(),
-                                won: self.winner == Some(human_player),
-                            });
-
-                            // Ensure we never pull up an old splash screen without this move
-                            self.daily_stats = None;
-                        }
-                    }
-                }
+                msgs_to_server.extend(self.persist_daily_moves(logged_in_as));
                 let delay = if battle_words.is_empty() { 650 } else { 2000 };
 
                 if !battle_words.is_empty() {
@@ -21,6 +21,7 @@ use crate::{
     utils::{
         game_evals::get_main_dict,
         includes::{Scenario, ScenarioStep, Tutorial},
+        locale::ui_text,
         tex::{render_tex_quad, tiles},
         text::TextHelper,
         urls::back_to_menu,
@@ -262,7 +263,7 @@ impl TutorialState {
                 rules: GameRules::latest(Some(tutorial.effective_day)).1,
                 players: vec![
                     Player {
-                        name: "You".into(),
+                        name: ui_text("player_you").into(),
                         index: 0,
                         hand: Hand(scenario.player_hand.chars().collect()),
                         hand_capacity: scenario.player_hand.len(),
@@ -277,7 +278,7 @@ impl TutorialState {
                         seen_tiles: HashSet::new(),
                     },
                     Player {
-                        name: "Computer".into(),
+                        name: ui_text("player_computer").into(),
                         index: 1,
                         hand: Hand(scenario.computer_hand.chars().collect()),
                         hand_capacity: scenario.computer_hand.len(),
@@ -344,6 +345,17 @@ impl TutorialState {
         TutorialState::get_nth_scenario(&self.tutorial, self.stage_index + 1).is_some()
     }
 
+    /// Redraws the current stage in a newly chosen theme
+    pub fn apply_theme(&mut self, ctx: &egui::Context, theme: &Theme) {
+        if let Some(stage) = &mut self.stage {
+            stage.active_game.apply_theme(ctx, theme);
+        }
+    }
+
+    pub fn active_game_mut(&mut self) -> Option<&mut ActiveGame> {
+        self.stage.as_mut().map(|stage| &mut stage.active_game)
+    }
+
     fn increment_stage(&mut self, ctx: &egui::Context, map_texture: TextureHandle, theme: &Theme) {
         self.stage_index += 1;
         self.stage =
@@ -659,7 +671,7 @@ impl TutorialState {
                                     let mut dialog_rect = dialog_resp.rect;
                                     dialog_rect.set_top(dialog_rect.bottom() - button_spacing);
 
-                                    let text = TextHelper::heavy("NEXT", 14.0, None, ui);
+                                    let text = TextHelper::heavy(ui_text("next"), 14.0, None, ui);
                                     ui.allocate_ui_at_rect(dialog_rect, |ui| {
                                         ui.with_layout(
                                             Layout::centered_and_justified(
@@ -711,7 +723,7 @@ impl TutorialState {
                                     let mut dialog_rect = dialog_resp.rect;
                                     dialog_rect.set_top(dialog_rect.bottom() - button_spacing);
 
-                                    let text = TextHelper::heavy("NEXT", 14.0, None, ui);
+                                    let text = TextHelper::heavy(ui_text("next"), 14.0, None, ui);
                                     ui.allocate_ui_at_rect(dialog_rect, |ui| {
                                         ui.with_layout(
                                             Layout::centered_and_justified(
@@ -766,7 +778,12 @@ impl TutorialState {
                                     let mut dialog_rect = dialog_resp.rect;
                                     dialog_rect.set_top(dialog_rect.bottom() - button_spacing);
 
-                                    let text = TextHelper::heavy("RETURN TO MENU", 14.0, None, ui);
+                                    let text = TextHelper::heavy(
+                                        ui_text("return_to_menu"),
+                                        14.0,
+                                        None,
+                                        ui,
+                                    );
                                     ui.allocate_ui_at_rect(dialog_rect, |ui| {
                                         ui.with_layout(
                                             Layout::centered_and_justified(
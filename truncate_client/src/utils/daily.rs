@@ -7,6 +7,11 @@ use instant::Duration;
 use serde::{Deserialize, Serialize};
 use truncate_core::{
     generation::{generate_board, get_game_verification, BoardSeed},
+    messages::PlayerMessage,
+    moves::{
+        packing::{pack_moves, unpack_moves},
+        Move,
+    },
     npc::scoring::NPCPersonality,
     rules::GameRules,
 };
@@ -122,3 +127,139 @@ pub fn get_playable_daily_puzzle(
 
     game_state
 }
+
+/// A daily puzzle attempt played without a connection,
+/// waiting to be saved to the player's account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedDailyAttempt {
+    pub day: u32,
+    /// Which attempt this was on the player's device, so that retries aren't merged together
+    pub attempt: usize,
+    pub human_player: u32,
+    /// Moves packed with `pack_moves`
+    pub moves: String,
+    pub won: bool,
+    pub hints: u32,
+    pub takebacks: u32,
+}
+
+impl QueuedDailyAttempt {
+    pub fn new(
+        day: u32,
+        attempt: usize,
+        human_player: u32,
+        moves: &[Move],
+        won: bool,
+        hints: u32,
+        takebacks: u32,
+    ) -> Self {
+        Self {
+            day,
+            attempt,
+            human_player,
+            moves: pack_moves(moves, 2),
+            won,
+            hints,
+            takebacks,
+        }
+    }
+
+    pub fn moves(&self) -> Vec<Move> {
+        unpack_moves(&self.moves, 2).unwrap_or_default()
+    }
+
+    fn persist_message(&self, player_token: String) -> PlayerMessage {
+        PlayerMessage::PersistPuzzleMoves {
+            player_token,
+            day: self.day,
+            attempt: Some(self.attempt as u32),
+            human_player: self.human_player,
+            moves: self.moves(),
+            won: self.won,
+            hints: self.hints,
+            takebacks: self.takebacks,
+        }
+    }
+}
+
+/// Daily puzzle attempts that haven't reached the server yet,
+/// kept in local storage on the web so they survive a reload
+#[derive(Debug, Default)]
+pub struct OfflineDailyQueue {
+    attempts: Vec<QueuedDailyAttempt>,
+}
+
+impl OfflineDailyQueue {
+    pub fn load() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            if let Some(stored) = local_storage.get_item("truncate_offline_daily").unwrap() {
+                if let Ok(attempts) = serde_json::from_str(&stored) {
+                    return Self { attempts };
+                }
+            }
+        }
+
+        Self::default()
+    }
+
+    fn save(&self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            if self.attempts.is_empty() {
+                local_storage.remove_item("truncate_offline_daily").unwrap();
+            } else if let Ok(stored) = serde_json::to_string(&self.attempts) {
+                local_storage
+                    .set_item("truncate_offline_daily", &stored)
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Stores the latest state of an attempt, replacing any earlier state of the same attempt
+    pub fn record(&mut self, attempt: QueuedDailyAttempt) {
+        let existing = self
+            .attempts
+            .iter_mut()
+            .find(|a| a.day == attempt.day && a.attempt == attempt.attempt);
+
+        match existing {
+            Some(existing) if *existing == attempt => return,
+            Some(existing) => *existing = attempt,
+            None => self.attempts.push(attempt),
+        }
+        self.save();
+    }
+
+    /// The attempt to resume when the given day's puzzle is opened again while offline
+    pub fn latest_for_day(&self, day: u32) -> Option<&QueuedDailyAttempt> {
+        self.attempts
+            .iter()
+            .filter(|a| a.day == day)
+            .max_by_key(|a| a.attempt)
+    }
+
+    /// Messages that save each queued attempt, oldest first.
+    /// Attempts stay queued until the server confirms it has saved them.
+    pub fn persist_messages(&self, player_token: &str) -> Vec<PlayerMessage> {
+        self.attempts
+            .iter()
+            .map(|a| a.persist_message(player_token.to_string()))
+            .collect()
+    }
+
+    /// Forgets an attempt once the server has saved it
+    pub fn saved(&mut self, day: u32, attempt: Option<u32>) {
+        let Some(attempt) = attempt else {
+            return;
+        };
+        let queued = self.attempts.len();
+        self.attempts
+            .retain(|a| a.day != day || a.attempt as u32 != attempt);
+        if self.attempts.len() != queued {
+            self.save();
+        }
+    }
+}
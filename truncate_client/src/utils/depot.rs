@@ -1,5 +1,6 @@
 use epaint::{vec2, Color32, Rect, TextureHandle, Vec2};
 use instant::Duration;
+use std::collections::HashMap;
 use truncate_core::{
     board::{Coordinate, Square},
     generation::BoardSeed,
@@ -46,6 +47,20 @@ pub struct RegionDepot {
     pub headers_total_rect: Option<Rect>,
 }
 
+/// Help that the player can ask for in single player games
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssistRequest {
+    Hint,
+    Takeback,
+}
+
+/// Changes to the theme that the player can make from the settings menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThemeRequest {
+    NextTheme,
+    ToggleColourBlind,
+}
+
 #[derive(Clone, Default)]
 pub struct UIStateDepot {
     pub sidebar_toggled: bool,
@@ -61,11 +76,17 @@ pub struct UIStateDepot {
     pub dictionary_opened_by_keyboard: bool,
     pub dictionary_showing_definition: bool,
     pub hand_height_last_frame: f32,
+    /// Set when the player asks for help, until the game handles it
+    pub assist_request: Option<AssistRequest>,
+    /// Set when the player changes the theme, until the app applies it
+    pub theme_request: Option<ThemeRequest>,
 }
 
 #[derive(Clone)]
 pub struct BoardDepot {
     pub board_seed: Option<BoardSeed>,
+    /// The shareable code for hand-built puzzles, which have no seed
+    pub puzzle_code: Option<String>,
     pub board_moved: bool,
     pub board_zoom: f32,
     pub board_pan: Vec2,
@@ -75,6 +96,7 @@ impl Default for BoardDepot {
     fn default() -> Self {
         Self {
             board_seed: None,
+            puzzle_code: None,
             board_moved: false,
             board_zoom: 1.0,
             board_pan: vec2(0.0, 0.0),
@@ -101,6 +123,8 @@ pub struct GameplayDepot {
     pub last_battle_origin: Option<Coordinate>,
     pub npc: Option<NPCPersonality>,
     pub remaining_turns: Option<u64>,
+    /// Players who have lost their connection, and when they will forfeit if known
+    pub disconnected_players: HashMap<u64, Option<u64>>,
 }
 
 #[derive(Clone)]
@@ -4,7 +4,11 @@ use truncate_core::{
     game::Game,
     judge::{WordData, WordDict},
     messages::PlayerMessage,
-    npc::scoring::{NPCParams, NPCVocab},
+    moves::Move,
+    npc::{
+        analysis::MoveAnalysis,
+        scoring::{NPCParams, NPCVocab},
+    },
 };
 
 pub static TRUNCATE_DICT: &str = include_str!("../../../dict_builder/final_wordlist.txt");
@@ -21,10 +25,10 @@ fn ensure_dicts() {
     let mut large_vocab_dict = LARGE_VOCAB_DICT_UNSAFE.lock().unwrap();
 
     if total_dict.is_none() {
-        let mut valid_words = std::collections::HashMap::new();
-        let mut small_vocab_words = std::collections::HashMap::new();
-        let mut medium_vocab_words = std::collections::HashMap::new();
-        let mut large_vocab_words = std::collections::HashMap::new();
+        let mut valid_words = WordDict::new();
+        let mut small_vocab_words = WordDict::new();
+        let mut medium_vocab_words = WordDict::new();
+        let mut large_vocab_words = WordDict::new();
         let lines = TRUNCATE_DICT.lines();
 
         for line in lines {
@@ -117,6 +121,8 @@ pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
 
     let mut arb = truncate_core::npc::Arborist::pruning();
     arb.capped(npc_params.evaluation_cap);
+    #[cfg(not(target_arch = "wasm32"))]
+    arb.parallel();
 
     let (best_move, _score) = truncate_core::game::Game::best_move(
         game,
@@ -136,8 +142,21 @@ pub fn client_best_move(game: &Game, npc_params: &NPCParams) -> PlayerMessage {
     best_move
 }
 
+/// Reviews a played move against what the NPC would have played,
+/// with both judged against the full dictionary
+pub fn client_analyse_move(
+    game: &Game,
+    played: &Move,
+    npc_params: &NPCParams,
+) -> Option<MoveAnalysis> {
+    let dict_lock = get_main_dict();
+    let dict = dict_lock.as_ref().unwrap();
+
+    game.analyse_move(played, dict, npc_params)
+}
+
 /// Adds the given word to the static dictionaries for the NPC
-pub fn remember(word: &String) {
+pub fn remember(word: &str) {
     ensure_dicts();
 
     let total_dict = TOTAL_DICT.lock().unwrap();
@@ -149,18 +168,18 @@ pub fn remember(word: &String) {
         large_dict
             .as_mut()
             .unwrap()
-            .insert(word.clone(), word_data.clone());
+            .insert(word.to_string(), word_data.clone());
 
         // We don't want the NPC to learn bad words from the player
         if !word_data.objectionable {
             medium_dict
                 .as_mut()
                 .unwrap()
-                .insert(word.clone(), word_data.clone());
+                .insert(word.to_string(), word_data.clone());
             small_dict
                 .as_mut()
                 .unwrap()
-                .insert(word.clone(), word_data.clone());
+                .insert(word.to_string(), word_data.clone());
         }
     }
 }
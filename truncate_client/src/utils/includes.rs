@@ -1,5 +1,6 @@
 use serde::Deserialize;
 use std::collections::HashMap;
+use truncate_core::locale::Locale;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Tutorial {
@@ -52,31 +53,66 @@ pub enum ChangePriority {
     Low,
 }
 
-pub fn rules(for_day: u32) -> Tutorial {
-    [
-        serde_yaml::from_slice::<Tutorial>(include_bytes!("../../tutorials/rules_2.yml"))
-            .expect("Tutorial should match Tutorial format"),
-        serde_yaml::from_slice::<Tutorial>(include_bytes!("../../tutorials/rules_1.yml"))
-            .expect("Tutorial should match Tutorial format"),
-        serde_yaml::from_slice::<Tutorial>(include_bytes!("../../tutorials/rules_0.yml"))
-            .expect("Tutorial should match Tutorial format"),
-    ]
-    .into_iter()
-    .find(|r| r.effective_day <= for_day || r.effective_day == 0)
-    .expect("Some ruleset should apply for any given day")
+pub fn rules(locale: Locale, for_day: u32) -> Tutorial {
+    let rulesets: [&[u8]; 3] = match locale {
+        Locale::English => [
+            include_bytes!("../../tutorials/en/rules_2.yml"),
+            include_bytes!("../../tutorials/en/rules_1.yml"),
+            include_bytes!("../../tutorials/en/rules_0.yml"),
+        ],
+    };
+
+    rulesets
+        .into_iter()
+        .map(|ruleset| {
+            serde_yaml::from_slice::<Tutorial>(ruleset)
+                .expect("Tutorial should match Tutorial format")
+        })
+        .find(|r| r.effective_day <= for_day || r.effective_day == 0)
+        .expect("Some ruleset should apply for any given day")
+}
+
+pub fn changelogs(locale: Locale) -> HashMap<&'static str, Tutorial> {
+    let changelogs: [(&str, &[u8]); 2] = match locale {
+        Locale::English => [
+            (
+                "update_01",
+                include_bytes!("../../tutorials/en/update_01.yml"),
+            ),
+            (
+                "update_02",
+                include_bytes!("../../tutorials/en/update_02.yml"),
+            ),
+        ],
+    };
+
+    changelogs
+        .into_iter()
+        .map(|(name, changelog)| {
+            (
+                name,
+                serde_yaml::from_slice(changelog).expect("Tutorial should match Tutorial format"),
+            )
+        })
+        .collect()
+}
+
+fn strings_file(locale: Locale) -> &'static [u8] {
+    match locale {
+        Locale::English => include_bytes!("../../strings/en.yml"),
+    }
 }
 
-pub fn changelogs() -> HashMap<&'static str, Tutorial> {
-    HashMap::from([
-        (
-            "update_01",
-            serde_yaml::from_slice(include_bytes!("../../tutorials/update_01.yml"))
-                .expect("Tutorial should match Tutorial format"),
-        ),
-        (
-            "update_02",
-            serde_yaml::from_slice(include_bytes!("../../tutorials/update_02.yml"))
-                .expect("Tutorial should match Tutorial format"),
-        ),
-    ])
+/// The table of UI strings for a locale, keyed by their name in `strings/en.yml`
+pub fn strings(locale: Locale) -> HashMap<String, String> {
+    // Start from English so that any strings yet to be translated still read sensibly
+    let mut table: HashMap<String, String> = serde_yaml::from_slice(strings_file(Locale::English))
+        .expect("Strings should be a map of names to text");
+    if locale != Locale::English {
+        table.extend(
+            serde_yaml::from_slice::<HashMap<String, String>>(strings_file(locale))
+                .expect("Strings should be a map of names to text"),
+        );
+    }
+    table
 }
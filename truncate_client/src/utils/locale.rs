@@ -0,0 +1,84 @@
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+use truncate_core::{locale::Locale, messages::ServerError};
+
+use super::includes::strings;
+
+static UI_STRINGS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// The locale the player's browser or system asks for,
+/// falling back to English for any languages we don't have yet
+pub fn detect_locale() -> Locale {
+    #[cfg(target_arch = "wasm32")]
+    let tag = web_sys::window().unwrap().navigator().language();
+    #[cfg(not(target_arch = "wasm32"))]
+    let tag = std::env::var("LANG").ok();
+
+    tag.and_then(|tag| Locale::from_tag(&tag))
+        .unwrap_or_default()
+}
+
+/// Loads the UI strings for the given locale. This can only happen once,
+/// so should be done at startup before any text is shown.
+pub fn use_locale(locale: Locale) {
+    _ = UI_STRINGS.set(strings(locale));
+}
+
+/// Looks up a UI string by its name, showing the name itself if it is missing
+pub fn ui_text(name: &str) -> &str {
+    UI_STRINGS
+        .get_or_init(|| strings(Locale::default()))
+        .get(name)
+        .map(String::as_str)
+        .unwrap_or(name)
+}
+
+/// Looks up a UI string by its name, filling in each `{placeholder}` it contains
+pub fn ui_format(name: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(ui_text(name).to_string(), |text, (placeholder, value)| {
+            text.replace(&format!("{{{placeholder}}}"), &value.to_string())
+        })
+}
+
+/// Describes an error from the server in the player's language
+pub fn server_error_text(err: &ServerError) -> String {
+    match err {
+        ServerError::RoomNotFound(room) => ui_format(
+            "error_room_not_found",
+            &[("room", &room.to_ascii_uppercase())],
+        ),
+        ServerError::RoomFull(room) => {
+            ui_format("error_room_full", &[("room", &room.to_ascii_uppercase())])
+        }
+        ServerError::CannotJoinRoom(room) => ui_format(
+            "error_cannot_join_room",
+            &[("room", &room.to_ascii_uppercase())],
+        ),
+        ServerError::CannotRejoin => ui_text("error_cannot_rejoin").to_string(),
+        ServerError::NotInLobby => ui_text("error_not_in_lobby").to_string(),
+        ServerError::NotInGame => ui_text("error_not_in_game").to_string(),
+        ServerError::PlayerNotFound => ui_text("error_player_not_found").to_string(),
+        ServerError::NotHost => ui_text("error_not_host").to_string(),
+        ServerError::GameStarted => ui_text("error_game_started").to_string(),
+        ServerError::GameUnfinished => ui_text("error_game_unfinished").to_string(),
+        ServerError::SpectatorsCannotPlay => ui_text("error_spectators_cannot_play").to_string(),
+        ServerError::AlreadyPlaying => ui_text("error_already_playing").to_string(),
+        ServerError::ObeliskRequired => ui_text("error_obelisk_required").to_string(),
+        ServerError::UnknownRules => ui_text("error_unknown_rules").to_string(),
+        ServerError::InvalidToken => ui_text("error_invalid_token").to_string(),
+        ServerError::InvalidGameToken => ui_text("error_invalid_game_token").to_string(),
+        ServerError::LoginRequired => ui_text("error_login_required").to_string(),
+        ServerError::InvalidReplayId => ui_text("error_invalid_replay_id").to_string(),
+        ServerError::ReplayNotFound => ui_text("error_replay_not_found").to_string(),
+        ServerError::PuzzleComplete => ui_text("error_puzzle_complete").to_string(),
+        ServerError::InvalidName => ui_text("error_invalid_name").to_string(),
+        ServerError::NameTaken => ui_text("error_name_taken").to_string(),
+        ServerError::InvalidEmail => ui_text("error_invalid_email").to_string(),
+        ServerError::InvalidLoginLink => ui_text("error_invalid_login_link").to_string(),
+        ServerError::TooManyLoginLinks => ui_text("error_too_many_login_links").to_string(),
+        ServerError::DatabaseOffline => ui_text("error_database_offline").to_string(),
+        ServerError::BadRequest => ui_text("error_bad_request").to_string(),
+        ServerError::Internal => ui_text("error_internal").to_string(),
+    }
+}
@@ -4,6 +4,7 @@ pub mod depot;
 pub mod game_evals;
 pub mod glyph_utils;
 pub mod includes;
+pub mod locale;
 pub mod macros;
 pub mod mapper;
 pub mod tex;
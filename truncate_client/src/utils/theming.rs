@@ -2,26 +2,167 @@ use std::ops::Range;
 
 use eframe::egui::{self, Margin};
 use epaint::{hex_color, Color32, Hsva};
+use serde::{Deserialize, Serialize};
+use truncate_core::{emojification::EmojiPalette, game::GAME_COLORS};
 
-#[derive(Debug, Clone)]
+/// Colours are written to theme files as `#RRGGBB` or `#RRGGBBAA` strings
+mod hex_colour {
+    use epaint::Color32;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_hex())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Color32::from_hex(&hex)
+            .map_err(|e| D::Error::custom(format!("{hex} is not a colour: {e:?}")))
+    }
+}
+
+/// Which colours players and shared boards are drawn in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    #[default]
+    Standard,
+    ColourBlind,
+}
+
+/// Stand-ins for each of `GAME_COLORS`, from the Okabe-Ito colour-blind-safe set
+const COLOUR_BLIND_PLAYER_COLORS: [Color32; 5] = [
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(0, 114, 178),
+    Color32::from_rgb(204, 121, 167),
+    Color32::from_rgb(213, 94, 0),
+    Color32::from_rgb(240, 228, 66),
+];
+
+impl Palette {
+    pub fn player_color(&self, color: (u8, u8, u8)) -> Color32 {
+        let standard = Color32::from_rgb(color.0, color.1, color.2);
+        match self {
+            Palette::Standard => standard,
+            Palette::ColourBlind => GAME_COLORS
+                .iter()
+                .position(|c| *c == color)
+                .map(|i| COLOUR_BLIND_PLAYER_COLORS[i])
+                .unwrap_or(standard),
+        }
+    }
+
+    pub fn emoji(&self) -> EmojiPalette {
+        match self {
+            Palette::Standard => EmojiPalette::Standard,
+            Palette::ColourBlind => EmojiPalette::ColourBlind,
+        }
+    }
+}
+
+/// How each emoji square looks when we mock up a shared message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmojiColors {
+    #[serde(with = "hex_colour")]
+    pub blue: Color32,
+    #[serde(with = "hex_colour")]
+    pub green: Color32,
+    #[serde(with = "hex_colour")]
+    pub yellow: Color32,
+    #[serde(with = "hex_colour")]
+    pub orange: Color32,
+    #[serde(with = "hex_colour")]
+    pub brown: Color32,
+    #[serde(with = "hex_colour")]
+    pub purple: Color32,
+    #[serde(with = "hex_colour")]
+    pub white: Color32,
+    #[serde(with = "hex_colour")]
+    pub black: Color32,
+}
+
+impl Default for EmojiColors {
+    fn default() -> Self {
+        Self {
+            blue: hex_color!("#4F55E2"),
+            green: hex_color!("#6DAF6B"),
+            yellow: hex_color!("#D7AE1D"),
+            orange: hex_color!("#E8833A"),
+            brown: hex_color!("#A7856F"),
+            purple: hex_color!("#D27CFF"),
+            white: hex_color!("#E6E6E6"),
+            black: hex_color!("#2B2B2B"),
+        }
+    }
+}
+
+impl EmojiColors {
+    pub fn for_emoji(&self, emoji: char) -> Color32 {
+        match emoji {
+            '🟦' => self.blue,
+            '🟩' => self.green,
+            '🟨' => self.yellow,
+            '🟧' => self.orange,
+            '🟫' => self.brown,
+            '🟪' => self.purple,
+            '⬜' => self.white,
+            _ => self.black,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Theme {
     pub use_old_art: bool, // TODO: Remove after art change has flushed through
     pub daytime: bool,
+    pub palette: Palette,
+    #[serde(with = "hex_colour")]
     pub water: Color32,
+    #[serde(with = "hex_colour")]
     pub grass: Color32,
+    #[serde(with = "hex_colour")]
     pub text: Color32,
+    #[serde(with = "hex_colour")]
     pub faded: Color32,
+    #[serde(with = "hex_colour")]
     pub button_primary: Color32,
+    #[serde(with = "hex_colour")]
     pub button_secondary: Color32,
+    #[serde(with = "hex_colour")]
     pub button_scary: Color32,
+    #[serde(with = "hex_colour")]
     pub ring_selected: Color32,
+    #[serde(with = "hex_colour")]
     pub ring_selected_hovered: Color32,
+    #[serde(with = "hex_colour")]
     pub ring_hovered: Color32,
+    #[serde(with = "hex_colour")]
     pub ring_added: Color32,
+    #[serde(with = "hex_colour")]
     pub ring_modified: Color32,
+    #[serde(with = "hex_colour")]
     pub word_valid: Color32,
+    #[serde(with = "hex_colour")]
     pub word_invalid: Color32,
+    #[serde(with = "hex_colour")]
     pub gold_medal: Color32,
+    #[serde(with = "hex_colour")]
+    pub message_bubble: Color32,
+    #[serde(with = "hex_colour")]
+    pub message_shadow: Color32,
+    pub emoji_colors: EmojiColors,
+    #[serde(with = "hex_colour")]
+    pub graph_win: Color32,
+    #[serde(with = "hex_colour")]
+    pub graph_loss: Color32,
+    #[serde(with = "hex_colour")]
+    pub graph_loss_alt: Color32,
+    #[serde(with = "hex_colour")]
+    pub graph_empty: Color32,
+    #[serde(with = "hex_colour")]
+    pub graph_label: Color32,
     pub grid_size: f32,
     pub letter_size: f32,
     pub tile_margin: f32,
@@ -35,6 +176,7 @@ impl Theme {
         Self {
             use_old_art: false,
             daytime: true,
+            palette: Palette::Standard,
             water: hex_color!("#0BADFF"),
             grass: hex_color!("#7BCB69"),
             text: hex_color!("#333333"),
@@ -50,6 +192,14 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            message_bubble: hex_color!("#444444"),
+            message_shadow: hex_color!("#111111"),
+            emoji_colors: EmojiColors::default(),
+            graph_win: hex_color!("#6DAF6B"),
+            graph_loss: hex_color!("#944D5E"),
+            graph_loss_alt: hex_color!("#A75E6F"),
+            graph_empty: hex_color!("#333333"),
+            graph_label: Color32::WHITE,
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -63,6 +213,7 @@ impl Theme {
         Self {
             use_old_art: true,
             daytime: true,
+            palette: Palette::Standard,
             water: hex_color!("#50a7e8"),
             grass: hex_color!("#7BCB69"),
             text: hex_color!("#333333"),
@@ -78,6 +229,14 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            message_bubble: hex_color!("#444444"),
+            message_shadow: hex_color!("#111111"),
+            emoji_colors: EmojiColors::default(),
+            graph_win: hex_color!("#6DAF6B"),
+            graph_loss: hex_color!("#944D5E"),
+            graph_loss_alt: hex_color!("#A75E6F"),
+            graph_empty: hex_color!("#333333"),
+            graph_label: Color32::WHITE,
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -91,6 +250,7 @@ impl Theme {
         Self {
             use_old_art: false,
             daytime: true,
+            palette: Palette::Standard,
             water: hex_color!("#000000"),
             grass: hex_color!("#7BCB69"),
             text: hex_color!("#333333"),
@@ -106,6 +266,14 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            message_bubble: hex_color!("#444444"),
+            message_shadow: hex_color!("#111111"),
+            emoji_colors: EmojiColors::default(),
+            graph_win: hex_color!("#6DAF6B"),
+            graph_loss: hex_color!("#944D5E"),
+            graph_loss_alt: hex_color!("#A75E6F"),
+            graph_empty: hex_color!("#333333"),
+            graph_label: Color32::WHITE,
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -119,6 +287,7 @@ impl Theme {
         Self {
             use_old_art: false,
             daytime: false,
+            palette: Palette::Standard,
             water: hex_color!("#000000"),
             grass: hex_color!("#112b15"),
             text: hex_color!("#FFFFFF"),
@@ -134,6 +303,14 @@ impl Theme {
             word_valid: hex_color!("#00A37D"),
             word_invalid: hex_color!("#89043D"),
             gold_medal: hex_color!("#E0A500"),
+            message_bubble: hex_color!("#444444"),
+            message_shadow: hex_color!("#111111"),
+            emoji_colors: EmojiColors::default(),
+            graph_win: hex_color!("#6DAF6B"),
+            graph_loss: hex_color!("#944D5E"),
+            graph_loss_alt: hex_color!("#A75E6F"),
+            graph_empty: hex_color!("#333333"),
+            graph_label: Color32::WHITE,
             grid_size: 50.0,
             letter_size: 25.0,
             tile_margin: 4.0,
@@ -144,7 +321,52 @@ impl Theme {
     }
 }
 
+impl Default for Theme {
+    fn default() -> Self {
+        Self::day()
+    }
+}
+
 impl Theme {
+    /// Swaps any colours that rely on telling red from green for colour-blind-safe ones
+    pub fn colour_blind(self) -> Self {
+        Self {
+            palette: Palette::ColourBlind,
+            graph_win: hex_color!("#0072B2"),
+            graph_loss: hex_color!("#D55E00"),
+            graph_loss_alt: hex_color!("#E69F00"),
+            word_valid: hex_color!("#0072B2"),
+            word_invalid: hex_color!("#D55E00"),
+            ..self
+        }
+    }
+
+    pub fn named(name: &str) -> Option<Self> {
+        match name.trim() {
+            "day" => Some(Self::day()),
+            "old_day" => Some(Self::old_day()),
+            "fog" => Some(Self::fog()),
+            "night" => Some(Self::night()),
+            "colour_blind" => Some(Self::day().colour_blind()),
+            "colour_blind_night" => Some(Self::night().colour_blind()),
+            "colour_blind_fog" => Some(Self::fog().colour_blind()),
+            _ => None,
+        }
+    }
+
+    /// Loads either the name of a built-in theme, or a YAML theme file.
+    /// Colours left out of a theme file fall back to those of the day theme.
+    pub fn load(source: &str) -> Result<Self, String> {
+        if let Some(theme) = Self::named(source) {
+            return Ok(theme);
+        }
+        serde_yaml::from_str(source).map_err(|e| format!("Couldn't read theme: {e}"))
+    }
+
+    pub fn player_color(&self, color: (u8, u8, u8)) -> Color32 {
+        self.palette.player_color(color)
+    }
+
     pub fn calc_rescale(
         &self,
         avail_space: &egui::Rect,
@@ -186,6 +408,79 @@ impl Theme {
     }
 }
 
+/// The built-in themes that players can cycle through from the settings menu
+pub const SELECTABLE_THEMES: [&str; 3] = ["day", "night", "fog"];
+
+/// A built-in theme picked from the settings menu, remembered between visits on the web
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemeChoice {
+    pub base: &'static str,
+    pub colour_blind: bool,
+}
+
+impl ThemeChoice {
+    /// Picks up where a theme that wasn't chosen from the menu left off
+    pub fn from_theme(theme: &Theme) -> Self {
+        Self {
+            base: if theme.daytime { "day" } else { "night" },
+            colour_blind: theme.palette == Palette::ColourBlind,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        let (base, colour_blind) = match name.strip_prefix("colour_blind") {
+            Some("") => ("day", true),
+            Some(base) => (base.strip_prefix('_')?, true),
+            None => (name, false),
+        };
+        let base = SELECTABLE_THEMES.into_iter().find(|t| *t == base)?;
+        Some(Self { base, colour_blind })
+    }
+
+    /// The name of this theme as understood by `Theme::named`
+    pub fn name(&self) -> String {
+        match (self.base, self.colour_blind) {
+            (base, false) => base.to_string(),
+            ("day", true) => "colour_blind".to_string(),
+            (base, true) => format!("colour_blind_{base}"),
+        }
+    }
+
+    pub fn next_theme(self) -> Self {
+        let current = SELECTABLE_THEMES
+            .iter()
+            .position(|t| *t == self.base)
+            .unwrap_or_default();
+        Self {
+            base: SELECTABLE_THEMES[(current + 1) % SELECTABLE_THEMES.len()],
+            ..self
+        }
+    }
+
+    pub fn toggle_colour_blind(self) -> Self {
+        Self {
+            colour_blind: !self.colour_blind,
+            ..self
+        }
+    }
+
+    pub fn theme(&self) -> Theme {
+        Theme::named(&self.name()).unwrap_or_default()
+    }
+
+    /// Stores the choice where the web client looks for a theme when it loads
+    pub fn save(&self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let local_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+            local_storage
+                .set_item("truncate_theme", &self.name())
+                .unwrap();
+        }
+    }
+}
+
 pub trait Diaphanize {
     fn diaphanize(&self) -> Self;
 }
@@ -154,7 +154,7 @@ pub async fn connect(
                             }
                         }
                     }
-                    GameMessage::JoinedLobby(_, _, _, _, token) => {
+                    GameMessage::JoinedLobby(_, _, _, _, _, token) => {
                         // Store a token that we're interacting with, in case we need to
                         // recreate the connection.
                         *most_recent_game_token.lock().unwrap() = Some(token.to_string());
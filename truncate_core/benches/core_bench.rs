@@ -1,5 +1,4 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use std::collections::HashMap;
 use truncate_core::{
     bag::TileBag,
     board::{Board, Coordinate},
@@ -14,7 +13,7 @@ pub static TESTING_DICT: &str = include_str!("../../dict_builder/final_wordlist.
 
 /// Build an (expensive) word dictionary using the real game data.
 fn dict() -> WordDict {
-    let mut valid_words = HashMap::new();
+    let mut valid_words = WordDict::new();
     let lines = TESTING_DICT.lines();
 
     for line in lines {
@@ -232,28 +231,12 @@ pub fn judge_benches(c: &mut Criterion) {
     };
 
     c.bench_function("judge_with_double_alias", |b| {
-        b.iter(|| {
-            judge.valid(
-                &aliased_judge_word,
-                &win_condition,
-                Some(&dict),
-                None,
-                &mut None,
-            )
-        })
+        b.iter(|| judge.valid(&aliased_judge_word, &win_condition, Some(&dict), &mut None))
     });
 
     let wildcard_judge_word = format!("PAR*ITION");
     c.bench_function("judge_with_wildcard", |b| {
-        b.iter(|| {
-            judge.valid(
-                &wildcard_judge_word,
-                &win_condition,
-                Some(&dict),
-                None,
-                &mut None,
-            )
-        })
+        b.iter(|| judge.valid(&wildcard_judge_word, &win_condition, Some(&dict), &mut None))
     });
 }
 
@@ -1,4 +1,5 @@
 use oorandom::Rand32;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
 use crate::rules;
@@ -95,13 +96,23 @@ const TILE_GENERATIONS: [[usize; 26]; 2] = [
     ],
 ];
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileBag {
     bag: Vec<char>,
+    #[serde(serialize_with = "serialize_rng", deserialize_with = "deserialize_rng")]
     rng: Rand32,
     letter_distribution: Option<[usize; 26]>,
 }
 
+/// Stores the generator's internal state so that a restored bag continues the same sequence
+fn serialize_rng<S: Serializer>(rng: &Rand32, serializer: S) -> Result<S::Ok, S::Error> {
+    rng.state().serialize(serializer)
+}
+
+fn deserialize_rng<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rand32, D::Error> {
+    <(u64, u64)>::deserialize(deserializer).map(Rand32::from_state)
+}
+
 impl TileBag {
     pub fn generation(gen: u32, seed: Option<u64>) -> Self {
         TileBag::custom(
@@ -155,6 +166,16 @@ impl TileBag {
         self.bag.swap_remove(index as usize)
     }
 
+    /// Takes a single copy of a tile out of the bag, if the bag has one,
+    /// for when that tile is known to be somewhere else
+    pub fn remove_tile(&mut self, c: char) -> bool {
+        let Some(index) = self.bag.iter().position(|t| *t == c) else {
+            return false;
+        };
+        self.bag.swap_remove(index);
+        true
+    }
+
     // TODO: this doesn't stop us from returning tiles that weren't originally in the bag
     pub fn return_tile(&mut self, c: char) {
         self.bag.push(c);
@@ -196,6 +217,14 @@ pub mod tests {
         assert_eq!(drawn.filter(|&x| x == 'A').count(), 5);
     }
 
+    #[test]
+    fn removes_known_tiles() {
+        let mut bag = a_b_bag();
+        assert!(bag.remove_tile('A'));
+        assert!(!bag.remove_tile('A'));
+        assert_eq!(bag.to_string(), "Letters in the bag:\n['B']");
+    }
+
     // Util functions
     pub fn a_b_bag() -> TileBag {
         let mut dist = [0; 26];
@@ -288,8 +288,10 @@ impl Board {
 
     /// Trims edges containing only empty squares
     pub fn trim(&mut self) {
-        let trim = self.redundant_edges();
+        self.trim_edges(self.redundant_edges());
+    }
 
+    fn trim_edges(&mut self, trim: RedundantEdges) {
         for _ in 0..trim.top {
             self.squares.remove(0);
         }
@@ -317,6 +319,7 @@ impl Board {
 
         self.artifacts.clear();
         self.towns.clear();
+        self.obelisks.clear();
 
         for coord in coords {
             match self.get(coord) {
@@ -1590,6 +1593,44 @@ impl Board {
         self.cache_special_squares();
     }
 
+    /// Turns the board a quarter turn clockwise, swapping its width and height
+    pub fn rotate_clockwise(&mut self) {
+        let height = self.height();
+        self.squares = (0..self.width())
+            .map(|x| (0..height).rev().map(|y| self.squares[y][x]).collect())
+            .collect();
+        self.cache_special_squares();
+    }
+
+    /// Mirrors the board left to right
+    pub fn flip_horizontal(&mut self) {
+        self.squares.iter_mut().for_each(|s| s.reverse());
+        self.cache_special_squares();
+    }
+
+    /// Mirrors the board top to bottom
+    pub fn flip_vertical(&mut self) {
+        self.squares.reverse();
+        self.cache_special_squares();
+    }
+
+    /// The inverse of `grow`, removing the outermost ring of squares.
+    /// Boards are never shrunk below 3x3.
+    pub fn shrink(&mut self) {
+        if self.width() <= 3 || self.height() <= 3 {
+            return;
+        }
+
+        self.squares.remove(0);
+        self.squares.pop();
+        for row in &mut self.squares {
+            row.remove(0);
+            row.pop();
+        }
+
+        self.cache_special_squares();
+    }
+
     pub(crate) fn filter_to_player(
         &self,
         player_index: usize,
@@ -1631,6 +1672,62 @@ impl Board {
 
         new_board
     }
+
+    /// Builds the board shown to spectators, laid out as the followed player sees it
+    /// but only revealing squares that every player can see, so that watching a game
+    /// can't be used to peek through either player's fog.
+    pub(crate) fn filter_to_spectator(
+        &self,
+        following: usize,
+        visibility: &rules::Visibility,
+        board_orientation: &BoardOrientation,
+        winner: &Option<usize>,
+        seen_tiles: &[&HashSet<Coordinate>],
+    ) -> Self {
+        if winner.is_some() || matches!(visibility, rules::Visibility::Standard) {
+            return self.filter_to_player(
+                following,
+                visibility,
+                board_orientation,
+                winner,
+                seen_tiles[following],
+                true,
+            );
+        }
+
+        let followed = self.fog_of_war(following, visibility, seen_tiles[following]);
+        let mut shared = followed.clone();
+
+        for (player_index, seen) in seen_tiles.iter().enumerate() {
+            if player_index == following {
+                continue;
+            }
+            let other = self.fog_of_war(player_index, visibility, seen);
+
+            for (y, row) in other.squares.iter().enumerate() {
+                for (x, other_square) in row.iter().enumerate() {
+                    let clear_to_followed = followed.squares[y][x] == self.squares[y][x];
+                    if matches!(other_square, Square::Fog {}) || clear_to_followed {
+                        shared.squares[y][x] = *other_square;
+                    }
+                }
+            }
+        }
+
+        // Trim to the followed player's view so coordinates line up with their reported changes
+        shared.trim_edges(followed.redundant_edges());
+
+        match following {
+            0 => match board_orientation {
+                BoardOrientation::Standard => {}
+                BoardOrientation::FacingPlayer => shared.rotate_in_place(),
+            },
+            1 => {}
+            _ => unimplemented!("Handle orientation for >2 players"),
+        }
+
+        shared
+    }
 }
 
 impl Default for Board {
@@ -1641,6 +1738,13 @@ impl Default for Board {
 
 impl Board {
     pub fn from_string<S: AsRef<str>>(s: S) -> Board {
+        Self::try_from_string(s).expect("Couldn't build board from string")
+    }
+
+    /// Parses a board in the format produced by its `Display` implementation,
+    /// failing on unknown squares or jagged rows
+    #[allow(clippy::result_unit_err)]
+    pub fn try_from_string<S: AsRef<str>>(s: S) -> Result<Board, ()> {
         // Transform string into a board
         let mut squares: Vec<Vec<Square>> = vec![];
         for line in s.as_ref().split('\n') {
@@ -1648,51 +1752,46 @@ impl Board {
                 continue;
             };
             squares.push(
-                line.trim()
-                    .split(' ')
+                line.split_whitespace()
                     .map(|tile| {
                         let mut chars = tile.chars();
-                        match chars.next() {
-                            Some('~') => Square::water(),
-                            Some('_') => Square::land(),
-                            Some('|') => Square::artifact(
-                                chars
-                                    .next()
-                                    .expect("Square needs player")
-                                    .to_digit(10)
-                                    .unwrap() as usize,
-                            ),
-                            Some('#') => Square::town(
-                                chars
-                                    .next()
-                                    .expect("Square needs player")
-                                    .to_digit(10)
-                                    .unwrap() as usize,
-                            ),
-                            Some(tile) => Square::Occupied {
-                                player: chars
-                                    .next()
-                                    .expect("Square needs player")
-                                    .to_digit(10)
-                                    .unwrap() as usize,
-                                tile,
-                                validity: SquareValidity::Unknown,
+                        let kind = chars.next();
+                        let player = chars.next().and_then(|c| c.to_digit(10));
+                        match (kind, player.map(|p| p as usize)) {
+                            (Some('~'), _) => Ok(Square::water()),
+                            (Some('_'), _) => Ok(Square::land()),
+                            (Some('^'), _) => Ok(Square::obelisk()),
+                            (Some('░'), _) => Ok(Square::fog()),
+                            (Some('|'), Some(player)) => Ok(Square::artifact(player)),
+                            (Some('#'), Some(player)) => Ok(Square::town(player)),
+                            (Some('⊭'), Some(player)) => Ok(Square::Town {
+                                player,
+                                defeated: true,
                                 foggy: false,
-                            },
-                            _ => panic!("Couldn't build board from string"),
+                            }),
+                            (Some(tile), Some(player)) if tile.is_alphabetic() => {
+                                Ok(Square::Occupied {
+                                    player,
+                                    tile,
+                                    validity: SquareValidity::Unknown,
+                                    foggy: false,
+                                })
+                            }
+                            _ => Err(()),
                         }
                     })
-                    .collect(),
+                    .collect::<Result<_, _>>()?,
             );
         }
 
         // Make sure the board is an valid non-jagged grid
-        if squares
-            .iter()
-            .skip(1)
-            .any(|line| line.len() != squares[0].len())
+        if squares.is_empty()
+            || squares
+                .iter()
+                .skip(1)
+                .any(|line| line.len() != squares[0].len())
         {
-            panic!("Tried to make a jagged board");
+            return Err(());
         }
 
         let mut board = Board {
@@ -1704,7 +1803,7 @@ impl Board {
         };
         board.cache_special_squares();
 
-        board
+        Ok(board)
     }
 }
 
@@ -3118,6 +3217,79 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn boards_round_trip_through_strings() {
+        let text = "~~ ~~ |0 ~~ ~~\n\
+                    ~~ #0 __ ^^ ~~\n\
+                    ~~ __ A0 B1 ~~\n\
+                    ~~ ⊭1 __ #1 ~~\n\
+                    ~~ ~~ |1 ~~ ~~";
+        let board = Board::try_from_string(text).unwrap();
+
+        assert_eq!(board.to_string(), text);
+        assert_eq!(board.obelisks, vec![Coordinate::new(3, 1)]);
+        assert_eq!(board.towns.len(), 3);
+
+        assert!(Board::try_from_string("~~ ~~\n~~").is_err());
+        assert!(Board::try_from_string("~~ %% ~~").is_err());
+        assert!(Board::try_from_string("~~ |x ~~").is_err());
+        assert!(Board::try_from_string("").is_err());
+    }
+
+    #[test]
+    fn transform_boards() {
+        let mut board = Board::from_string(
+            "~~ |0 ~~\n\
+             #0 __ __\n\
+             __ __ ^^\n\
+             ~~ |1 ~~",
+        );
+
+        board.rotate_clockwise();
+        assert_eq!(
+            board.to_string(),
+            "~~ __ #0 ~~\n\
+             |1 __ __ |0\n\
+             ~~ ^^ __ ~~"
+        );
+        assert_eq!(board.obelisks, vec![Coordinate::new(1, 2)]);
+
+        board.flip_horizontal();
+        assert_eq!(
+            board.to_string(),
+            "~~ #0 __ ~~\n\
+             |0 __ __ |1\n\
+             ~~ __ ^^ ~~"
+        );
+
+        board.flip_vertical();
+        assert_eq!(
+            board.to_string(),
+            "~~ __ ^^ ~~\n\
+             |0 __ __ |1\n\
+             ~~ #0 __ ~~"
+        );
+        assert_eq!(board.artifacts.len(), 2);
+    }
+
+    #[test]
+    fn shrink_board() {
+        let mut board = Board::new(3, 3);
+        board.grow();
+        assert_eq!((board.width(), board.height()), (7, 7));
+
+        board.shrink();
+        assert_eq!(board, {
+            let mut b = Board::new(3, 3);
+            b.cache_special_squares();
+            b
+        });
+
+        let mut tiny = Board::from_string("~~ ~~ ~~\n~~ __ ~~\n~~ ~~ ~~");
+        tiny.shrink();
+        assert_eq!((tiny.width(), tiny.height()), (3, 3));
+    }
+
     #[test]
     fn apply_rotation() {
         let board = Board::from_string(
@@ -3195,6 +3367,48 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn spectators_only_see_tiles_visible_to_both_players() {
+        let board = Board::from_string(
+            "~~ ~~ A0 ~~ ~~\n\
+             A0 A0 A0 __ A0\n\
+             A0 __ __ A0 __\n\
+             A0 __ __ __ __\n\
+             __ B1 __ B1 __\n\
+             __ B1 B1 B1 __\n\
+             ~~ ~~ B1 ~~ ~~",
+        );
+        let unseen = HashSet::new();
+
+        let spectated = board.filter_to_spectator(
+            0,
+            &rules::Visibility::TileFog,
+            &BoardOrientation::Standard,
+            &None,
+            &[&unseen, &unseen],
+        );
+        assert_eq!(
+            spectated.to_string(),
+            "~~ ~~ __ ~~ ~~\n\
+             A0 __ __ __ __\n\
+             A0 __ __ A0 __\n\
+             A0 __ __ __ __\n\
+             __ B1 __ B1 __\n\
+             __ B1 __ B1 __\n\
+             ~~ ~~ __ ~~ ~~",
+        );
+
+        // Once the game is over everything is revealed
+        let spectated = board.filter_to_spectator(
+            0,
+            &rules::Visibility::TileFog,
+            &BoardOrientation::Standard,
+            &Some(0),
+            &[&unseen, &unseen],
+        );
+        assert_eq!(spectated, board);
+    }
+
     #[test]
     fn apply_disjoint_fog_of_war() {
         let board = Board::from_string(
@@ -0,0 +1,277 @@
+#[derive(Debug, Clone)]
+pub struct WordData {
+    pub extensions: u32,
+    pub rel_freq: f32,
+    pub objectionable: bool,
+}
+
+/// A single position in a word pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    Letter(char),
+    /// Matches any letter
+    Wildcard,
+    /// Matches a letter from one of the sets passed alongside the pattern.
+    /// Each letter of a set can only be used once across the whole word,
+    /// as the set represents a group of tiles.
+    Set(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// Sorted by letter, so that traversals find words alphabetically
+    children: Vec<(char, u32)>,
+    entry: Option<u32>,
+}
+
+/// Word list stored as a trie, so that patterns containing wildcards
+/// and sets of letters can be resolved in a single traversal.
+#[derive(Debug, Clone)]
+pub struct WordDict {
+    nodes: Vec<Node>,
+    entries: Vec<(String, WordData)>,
+}
+
+impl Default for WordDict {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+            entries: vec![],
+        }
+    }
+}
+
+impl WordDict {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a word to the dictionary, returning the data previously stored against it
+    pub fn insert(&mut self, word: String, data: WordData) -> Option<WordData> {
+        let mut node = 0;
+        for letter in word.chars() {
+            node = match self.nodes[node]
+                .children
+                .binary_search_by_key(&letter, |(c, _)| *c)
+            {
+                Ok(index) => self.nodes[node].children[index].1 as usize,
+                Err(index) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[node]
+                        .children
+                        .insert(index, (letter, child as u32));
+                    child
+                }
+            };
+        }
+
+        match self.nodes[node].entry {
+            Some(entry) => Some(std::mem::replace(&mut self.entries[entry as usize].1, data)),
+            None => {
+                self.nodes[node].entry = Some(self.entries.len() as u32);
+                self.entries.push((word, data));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, word: &str) -> Option<&WordData> {
+        let mut node = 0;
+        for letter in word.chars() {
+            node = self.child(node, letter)?;
+        }
+        self.nodes[node]
+            .entry
+            .map(|entry| &self.entries[entry as usize].1)
+    }
+
+    pub fn contains_key(&self, word: &str) -> bool {
+        self.get(word).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &WordData)> {
+        self.entries.iter().map(|(word, data)| (word, data))
+    }
+
+    /// Finds the alphabetically first word matching the pattern, if any.
+    /// `sets` holds the letters available to each `Slot::Set` in the pattern.
+    pub fn resolve(&self, pattern: &[Slot], sets: &[Vec<char>]) -> Option<&str> {
+        let mut used = vec![0_u64; sets.len()];
+        self.resolve_from(0, pattern, sets, &mut used)
+            .map(|entry| self.entries[entry].0.as_str())
+    }
+
+    fn child(&self, node: usize, letter: char) -> Option<usize> {
+        let children = &self.nodes[node].children;
+        children
+            .binary_search_by_key(&letter, |(c, _)| *c)
+            .ok()
+            .map(|index| children[index].1 as usize)
+    }
+
+    fn resolve_from(
+        &self,
+        node: usize,
+        pattern: &[Slot],
+        sets: &[Vec<char>],
+        used: &mut [u64],
+    ) -> Option<usize> {
+        let Some((slot, rest)) = pattern.split_first() else {
+            return self.nodes[node].entry.map(|entry| entry as usize);
+        };
+
+        match slot {
+            Slot::Letter(letter) => {
+                let child = self.child(node, *letter)?;
+                self.resolve_from(child, rest, sets, used)
+            }
+            Slot::Wildcard => self.nodes[node]
+                .children
+                .iter()
+                .find_map(|(_, child)| self.resolve_from(*child as usize, rest, sets, used)),
+            Slot::Set(set) => {
+                let letters = &sets[*set];
+                debug_assert!(letters.len() <= 64, "Sets are limited to 64 letters");
+
+                for (i, letter) in letters.iter().enumerate() {
+                    let is_used = |i: usize, used: &[u64]| used[*set] & (1 << i) != 0;
+                    if is_used(i, used) {
+                        continue;
+                    }
+                    // An identical unused letter earlier in the set has already been tried
+                    if letters[..i]
+                        .iter()
+                        .enumerate()
+                        .any(|(j, other)| other == letter && !is_used(j, used))
+                    {
+                        continue;
+                    }
+                    let Some(child) = self.child(node, *letter) else {
+                        continue;
+                    };
+
+                    used[*set] |= 1 << i;
+                    if let Some(found) = self.resolve_from(child, rest, sets, used) {
+                        return Some(found);
+                    }
+                    used[*set] &= !(1 << i);
+                }
+
+                None
+            }
+        }
+    }
+}
+
+impl FromIterator<(String, WordData)> for WordDict {
+    fn from_iter<T: IntoIterator<Item = (String, WordData)>>(iter: T) -> Self {
+        let mut dict = Self::new();
+        for (word, data) in iter {
+            dict.insert(word, data);
+        }
+        dict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> WordData {
+        WordData {
+            extensions: 0,
+            rel_freq: 0.0,
+            objectionable: false,
+        }
+    }
+
+    fn test_dict() -> WordDict {
+        ["big", "bag", "bags", "jolly", "fat", "a"]
+            .into_iter()
+            .map(|w| (w.to_string(), data()))
+            .collect()
+    }
+
+    fn letters(word: &str) -> Vec<Slot> {
+        word.chars().map(Slot::Letter).collect()
+    }
+
+    #[test]
+    fn lookups() {
+        let dict = test_dict();
+        assert_eq!(dict.len(), 6);
+        assert!(dict.contains_key("bag"));
+        assert!(dict.contains_key("bags"));
+        assert!(dict.contains_key("a"));
+        assert!(!dict.contains_key("ba"));
+        assert!(!dict.contains_key("bagsy"));
+        assert!(!dict.contains_key(""));
+    }
+
+    #[test]
+    fn reinserting() {
+        let mut dict = test_dict();
+        let previous = dict.insert(
+            "bag".to_string(),
+            WordData {
+                extensions: 5,
+                ..data()
+            },
+        );
+        assert!(previous.is_some());
+        assert_eq!(dict.len(), 6);
+        assert_eq!(dict.get("bag").unwrap().extensions, 5);
+    }
+
+    #[test]
+    fn wildcards() {
+        let dict = test_dict();
+        let pattern = [Slot::Letter('b'), Slot::Wildcard, Slot::Letter('g')];
+        assert_eq!(dict.resolve(&pattern, &[]), Some("bag"));
+
+        let pattern = [Slot::Wildcard, Slot::Wildcard, Slot::Wildcard];
+        assert_eq!(dict.resolve(&pattern, &[]), Some("bag"));
+
+        let pattern = [Slot::Letter('r'), Slot::Wildcard, Slot::Letter('g')];
+        assert_eq!(dict.resolve(&pattern, &[]), None);
+
+        assert_eq!(dict.resolve(&letters("fat"), &[]), Some("fat"));
+    }
+
+    #[test]
+    fn sets() {
+        let dict = test_dict();
+        let sets = vec![vec!['o', 'l']];
+        // Each letter in a set can only be used once
+        let pattern = [
+            Slot::Letter('j'),
+            Slot::Letter('o'),
+            Slot::Set(0),
+            Slot::Set(0),
+            Slot::Letter('y'),
+        ];
+        assert_eq!(dict.resolve(&pattern, &sets), None);
+
+        let sets = vec![vec!['l', 'l']];
+        assert_eq!(dict.resolve(&pattern, &sets), Some("jolly"));
+
+        let sets = vec![vec!['o', 'l'], vec!['l']];
+        let pattern = [
+            Slot::Letter('j'),
+            Slot::Set(0),
+            Slot::Set(1),
+            Slot::Set(0),
+            Slot::Letter('y'),
+        ];
+        assert_eq!(dict.resolve(&pattern, &sets), Some("jolly"));
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::board::{Board, Square};
 
 pub const SQ_BLUE: &str = "🟦";
@@ -13,12 +15,42 @@ pub const SQ_BLACK_IN_WHITE: &str = "🔳";
 pub const SQ_WHITE_IN_BLACK: &str = "🔲";
 pub const SQ_ERR: &str = "🆘";
 
+/// Which set of emoji squares to share boards with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmojiPalette {
+    #[default]
+    Standard,
+    /// Avoids telling wins and losses apart by red against green alone
+    ColourBlind,
+}
+
+impl EmojiPalette {
+    fn land(&self, player_won: bool) -> &'static str {
+        match (self, player_won) {
+            (EmojiPalette::Standard, true) => SQ_GREEN,
+            (EmojiPalette::Standard, false) => SQ_BROWN,
+            (EmojiPalette::ColourBlind, true) => SQ_WHITE,
+            (EmojiPalette::ColourBlind, false) => SQ_BLACK,
+        }
+    }
+
+    fn tile(&self, player_won: bool) -> &'static str {
+        match (self, player_won) {
+            (EmojiPalette::Standard, true) => SQ_YELLOW,
+            (EmojiPalette::Standard, false) => SQ_PURPLE,
+            (EmojiPalette::ColourBlind, true) => SQ_ORANGE,
+            (EmojiPalette::ColourBlind, false) => SQ_PURPLE,
+        }
+    }
+}
+
 impl Board {
-    pub fn emojify(&self, player: usize, won: Option<usize>) -> String {
+    pub fn emojify(&self, player: usize, won: Option<usize>, palette: EmojiPalette) -> String {
         let player_won = won == Some(player);
         let water = SQ_BLUE;
-        let land = if player_won { SQ_GREEN } else { SQ_BROWN };
-        let tile = if player_won { SQ_YELLOW } else { SQ_PURPLE };
+        let land = palette.land(player_won);
+        let tile = palette.tile(player_won);
 
         let emoji_for_square = |sq: &Square| match sq {
             crate::board::Square::Water { .. } => water,
@@ -99,3 +131,36 @@ impl Board {
             .join("\n")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emoji_palettes() {
+        let board = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             ~~ #0 __ __ ~~\n\
+             ~~ __ A0 B1 ~~\n\
+             ~~ __ __ #1 ~~\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+
+        assert_eq!(
+            board.emojify(1, Some(0), EmojiPalette::Standard),
+            "🟫🟫🟫\n🟫🟪🟫\n🟫🟫🟫"
+        );
+        assert_eq!(
+            board.emojify(0, Some(0), EmojiPalette::Standard),
+            "🟩🟩🟩\n🟩🟨🟩\n🟩🟩🟩"
+        );
+        assert_eq!(
+            board.emojify(0, Some(0), EmojiPalette::ColourBlind),
+            "⬜⬜⬜\n⬜🟧⬜\n⬜⬜⬜"
+        );
+        assert_eq!(
+            board.emojify(1, Some(0), EmojiPalette::ColourBlind),
+            "⬛⬛⬛\n⬛🟪⬛\n⬛⬛⬛"
+        );
+    }
+}
@@ -1,8 +1,17 @@
-use super::board::Coordinate;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Clone, Error, Debug, PartialEq)]
+use super::board::Coordinate;
+
+#[derive(Clone, Error, Debug, PartialEq, Serialize, Deserialize)]
 pub enum GamePlayError {
+    #[error("Game is already over")]
+    GameOver,
+    #[error("Only the next player can play")]
+    NotYourTurn,
+    #[error("Player's turn has not yet started")]
+    TurnNotStarted,
+
     #[error("Invalid position ({:?}, {:?})", position.x, position.y)]
     InvalidPosition { position: Coordinate },
     #[error("Coordinate is not within board dimensions ({:?}, {:?})", position.x, position.y)]
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ops::Sub;
 
@@ -9,7 +10,7 @@ use crate::board::{Coordinate, Square};
 use crate::error::GamePlayError;
 use crate::judge::{Outcome, WordDict};
 use crate::reporting::{self, BoardChange, BoardChangeAction, BoardChangeDetail, TimeChange};
-use crate::rules::{self, GameRules, OvertimeRule};
+use crate::rules::{self, GameRules, OvertimeRule, RuleSettings};
 
 use super::board::Board;
 use super::judge::Judge;
@@ -31,12 +32,15 @@ pub const GAME_COLORS: [(u8, u8, u8); 5] = [
     GAME_COLOR_YELLOW,
 ];
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub rules: GameRules,
     pub players: Vec<Player>,
     pub board: Board,
     pub bag: TileBag,
+    /// Aliases are only set up for NPC evaluation, and dictionaries are supplied per turn,
+    /// so the judge is not part of a game's persisted state.
+    #[serde(skip)]
     pub judge: Judge,
     pub battle_count: u32,
     pub turn_count: u32,
@@ -117,8 +121,8 @@ impl Game {
         }
     }
 
-    pub fn add_player(&mut self, name: String) {
-        let time_allowance = match self.rules.timing {
+    fn time_allowance(&self) -> Option<Duration> {
+        match self.rules.timing {
             rules::Timing::PerPlayer {
                 time_allowance,
                 overtime_rule: _,
@@ -126,7 +130,11 @@ impl Game {
             rules::Timing::None => None,
             rules::Timing::Periodic { .. } => None,
             _ => unimplemented!(),
-        };
+        }
+    }
+
+    pub fn add_player(&mut self, name: String) {
+        let time_allowance = self.time_allowance();
         self.players.push(Player::new(
             name,
             self.players.len(),
@@ -138,6 +146,17 @@ impl Game {
         self.player_turn_count.push(0);
     }
 
+    /// Customises the rules of a game that hasn't started, resetting player clocks to match
+    pub fn customise_rules(&mut self, settings: &RuleSettings) {
+        settings.apply(&mut self.rules);
+
+        let time_allowance = self.time_allowance();
+        for player in &mut self.players {
+            player.allotted_time = time_allowance;
+            player.time_remaining = time_allowance;
+        }
+    }
+
     pub fn get_player(&self, player: usize) -> Option<&Player> {
         // TODO: Lookup player by `index` field rather than vec position
         self.players.get(player)
@@ -379,9 +398,9 @@ impl Game {
         attacker_dictionary: Option<&WordDict>,
         defender_dictionary: Option<&WordDict>,
         cached_word_judgements: Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
-    ) -> Result<Option<usize>, String> {
+    ) -> Result<Option<usize>, GamePlayError> {
         if self.winner.is_some() {
-            return Err("Game is already over".into());
+            return Err(GamePlayError::GameOver);
         }
 
         let player = match next_move {
@@ -398,17 +417,17 @@ impl Game {
             rules::Timing::Periodic { .. } => { /* All players can play */ }
             _ => {
                 if player != self.next_player.unwrap() {
-                    return Err("Only the next player can play".into());
+                    return Err(GamePlayError::NotYourTurn);
                 }
             }
         }
 
         if let Some(turn_start) = self.players[player].turn_starts_no_sooner_than {
             if turn_start > now() {
-                return Err("Player's turn has not yet started".into());
+                return Err(GamePlayError::TurnNotStarted);
             }
         } else {
-            return Err("Player's turn has not yet started".into());
+            return Err(GamePlayError::TurnNotStarted);
         }
 
         self.recent_changes = match self.make_move(
@@ -418,9 +437,9 @@ impl Game {
             cached_word_judgements,
         ) {
             Ok(changes) => changes,
-            Err(msg) => {
-                println!("Error in game: {}", msg);
-                return Err(format!("{msg}"));
+            Err(e) => {
+                println!("Error in game: {}", e);
+                return Err(e);
             }
         };
 
@@ -916,4 +935,36 @@ impl Game {
         );
         (filtered_board, filtered_changes)
     }
+
+    /// Shows an observer the game from the followed player's side, without revealing
+    /// their hand or anything that is hidden by fog from either player
+    pub fn filter_game_to_spectator(&self, following: usize) -> (Board, Vec<Change>) {
+        let seen = self
+            .players
+            .iter()
+            .map(|player| &player.seen_tiles)
+            .collect::<Vec<_>>();
+
+        let filtered_board = self.board.filter_to_spectator(
+            following,
+            &self.rules.visibility,
+            &self.rules.board_orientation,
+            &self.winner,
+            &seen,
+        );
+
+        let mut filtered_changes = reporting::filter_to_player(
+            &self.recent_changes,
+            &self.board,
+            &filtered_board,
+            following,
+            &self.rules.visibility,
+            &self.rules.board_orientation,
+            &self.winner,
+            seen[following],
+        );
+        filtered_changes.retain(|change| !matches!(change, Change::Hand(_)));
+
+        (filtered_board, filtered_changes)
+    }
 }
@@ -19,7 +19,7 @@ pub enum ArtifactType {
     Continental,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Symmetry {
     SmoothTwoFoldRotational,
     TwoFoldRotational,
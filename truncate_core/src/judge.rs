@@ -6,20 +6,14 @@ use crate::{
     rules,
 };
 
+pub use crate::dictionary::{Slot, WordData, WordDict};
+
 use super::board::{Board, Square};
 use std::{
     collections::HashMap,
     fmt::{self, Display},
 };
 
-#[derive(Debug, Clone)]
-pub struct WordData {
-    pub extensions: u32,
-    pub rel_freq: f32,
-    pub objectionable: bool,
-}
-pub type WordDict = HashMap<String, WordData>;
-
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Outcome {
     AttackerWins(Vec<usize>), // A list of specific defenders who are defeated
@@ -46,7 +40,7 @@ pub struct Judge {
 impl Default for Judge {
     fn default() -> Self {
         Self {
-            builtin_dictionary: HashMap::new(),
+            builtin_dictionary: WordDict::new(),
             aliases: HashMap::new(),
         }
     }
@@ -54,7 +48,7 @@ impl Default for Judge {
 
 impl Judge {
     pub fn new(words: Vec<String>) -> Self {
-        let mut dictionary = HashMap::new();
+        let mut dictionary = WordDict::new();
         for word in words {
             dictionary.insert(
                 word.to_lowercase(),
@@ -145,7 +139,6 @@ impl Judge {
                         w,
                         win_rules,
                         attacker_dictionary,
-                        None,
                         &mut cached_word_judgements,
                     );
                     BattleWord {
@@ -183,7 +176,6 @@ impl Judge {
                 &*defense.resolved_word,
                 win_rules,
                 defender_dictionary,
-                None,
                 &mut cached_word_judgements,
             );
             if let Some(valid) = valid {
@@ -303,27 +295,24 @@ impl Judge {
         word: S,
         win_rules: &rules::WinCondition,
         external_dictionary: Option<&WordDict>,
-        used_aliases: Option<HashMap<char, Vec<usize>>>,
         cached_word_judgements: &mut Option<&mut HashMap<String, bool, xxh3::Xxh3Builder>>,
     ) -> Option<String> {
-        /// Recursive function for resolving word validity through aliases
-        fn valid_inner<S: AsRef<str>>(
+        fn valid_inner(
             judge: &Judge,
-            word: S,
+            word: &str,
             win_rules: &rules::WinCondition,
             external_dictionary: Option<&WordDict>,
-            used_aliases: Option<HashMap<char, Vec<usize>>>,
         ) -> Option<String> {
             // If the word is entirely wildcards, skip the lookup and just say it is valid.
-            if word.as_ref().len() > 1 && word.as_ref().chars().all(|c| c == '*') {
-                return Some(word.as_ref().to_string());
+            if word.len() > 1 && word.chars().all(|c| c == '*') {
+                return Some(word.to_string());
             }
 
-            if word.as_ref().contains('¤') {
-                return Some(word.as_ref().to_string().to_uppercase());
+            if word.contains('¤') {
+                return Some(word.to_string().to_uppercase());
             }
 
-            if word.as_ref().contains('#') {
+            if word.contains('#') {
                 return match win_rules {
                     rules::WinCondition::Destination { town_defense, .. } => match town_defense {
                         rules::TownDefense::BeatenByContact => None,
@@ -339,7 +328,7 @@ impl Judge {
                 };
             }
 
-            if word.as_ref().contains('|') {
+            if word.contains('|') {
                 return match win_rules {
                     rules::WinCondition::Destination {
                         artifact_defense, ..
@@ -356,56 +345,31 @@ impl Judge {
                 };
             }
 
-            // Handle the first matching alias we find (others will be handled in the next recursion)
-            for (alias, resolved) in &judge.aliases {
-                if word.as_ref().contains(*alias) {
-                    let valid = resolved.iter().enumerate().find_map(|(i, c)| {
-                        let mut used = used_aliases.clone().unwrap_or_default();
-                        if used.get(c).map(|tiles| tiles.contains(&i)) == Some(true) {
-                            return None;
-                        }
-
-                        if let Some(used_alias) = used.get_mut(c) {
-                            used_alias.push(i);
-                        } else {
-                            used.insert(*c, vec![i]);
-                        }
-
-                        valid_inner(
-                            judge,
-                            word.as_ref().replacen(*alias, &c.to_string(), 1),
-                            win_rules,
-                            external_dictionary,
-                            Some(used),
-                        )
+            // Aliases become sets of letters, where each tile in the set can be used once
+            let mut sets: Vec<Vec<char>> = vec![];
+            let mut set_indices: HashMap<char, usize> = HashMap::new();
+            let pattern: Vec<_> = word
+                .to_lowercase()
+                .chars()
+                .map(|c| {
+                    if c == '*' {
+                        return Slot::Wildcard;
+                    }
+                    let Some(resolved) = judge.aliases.get(&c) else {
+                        return Slot::Letter(c);
+                    };
+                    let set = *set_indices.entry(c).or_insert_with(|| {
+                        sets.push(resolved.iter().flat_map(|c| c.to_lowercase()).collect());
+                        sets.len() - 1
                     });
-                    return valid;
-                }
-            }
-
-            if word.as_ref().contains('*') {
-                // Try all letters in the first wildcard spot
-                // TODO: find a fun way to optimize this to not be 26^wildcard_count (regex?)
-                let valid = (97..=122_u8).find_map(|c| {
-                    valid_inner(
-                        judge,
-                        word.as_ref().replacen('*', &(c as char).to_string(), 1),
-                        win_rules,
-                        external_dictionary,
-                        used_aliases.clone(),
-                    )
-                });
-                return valid;
-            }
+                    Slot::Set(set)
+                })
+                .collect();
 
-            if external_dictionary
+            external_dictionary
                 .unwrap_or(&judge.builtin_dictionary)
-                .contains_key(&word.as_ref().to_lowercase())
-            {
-                Some(word.as_ref().to_string().to_uppercase())
-            } else {
-                None
-            }
+                .resolve(&pattern, &sets)
+                .map(|resolved| resolved.to_uppercase())
         }
 
         if let Some(cached_word_judgements) = cached_word_judgements {
@@ -417,7 +381,7 @@ impl Judge {
         }
 
         let word_str = word.as_ref().to_string();
-        let valid = valid_inner(self, word, win_rules, external_dictionary, used_aliases);
+        let valid = valid_inner(self, word.as_ref(), win_rules, external_dictionary);
 
         // Never cache the result of evaluating a town
         if !word_str.contains('#') && !word_str.contains('|') {
@@ -1,13 +1,16 @@
 pub mod bag;
 pub mod board;
+pub mod dictionary;
 pub mod emojification;
 pub mod error;
 pub mod game;
 pub mod generation;
 pub mod judge;
+pub mod locale;
 pub mod messages;
 pub mod moves;
 pub mod npc;
 pub mod player;
+pub mod puzzle;
 pub mod reporting;
 pub mod rules;
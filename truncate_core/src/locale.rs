@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// A language the game can be presented and played in,
+/// which decides both the text shown to the player and the dictionary words are judged against
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 1] = [Locale::English];
+
+    /// The language subtag of this locale, as used in BCP 47 tags
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+        }
+    }
+
+    /// Finds the locale for a language tag such as `en-GB` or `en_US.UTF-8`,
+    /// ignoring any region or encoding we don't distinguish between.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.tag() == language)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locales_from_tags() {
+        assert_eq!(Locale::from_tag("en"), Some(Locale::English));
+        assert_eq!(Locale::from_tag("en-GB"), Some(Locale::English));
+        assert_eq!(Locale::from_tag("EN_us.UTF-8"), Some(Locale::English));
+        assert_eq!(Locale::from_tag("xx-YY"), None);
+        assert_eq!(Locale::from_tag(""), None);
+    }
+}
@@ -5,13 +5,17 @@ use std::{
 use time::Duration;
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::{
     board::{Board, Coordinate},
+    error::GamePlayError,
     game::Game,
+    locale::Locale,
     moves::Move,
     player::{Hand, Player},
     reporting::{Change, WordMeaning},
+    rules::RuleSettings,
 };
 
 pub type RoomCode = String;
@@ -39,8 +43,20 @@ pub enum PlayerMessage {
     },
     JoinGame(RoomCode, String, Option<TruncateToken>),
     RejoinGame(TruncateToken),
+    SpectateGame(RoomCode),
+    StopSpectating,
+    FindMatch {
+        player_token: TruncateToken,
+        player_name: String,
+        effective_day: u32,
+        /// Only match against players wanting this rule generation, or anything if `None`
+        rules_generation: Option<u32>,
+    },
+    CancelMatch,
     EditBoard(Board),
     EditName(String),
+    /// Lets the host of a lobby customise the rules its game will start with
+    SetRules(RuleSettings),
     StartGame,
     Resign,
     Place(Coordinate, char),
@@ -55,6 +71,9 @@ pub enum PlayerMessage {
         user_agent: String,
         referrer: String,
         unread_changelogs: Vec<String>,
+        /// The language the player is playing in, which picks their dictionary
+        #[serde(default)]
+        locale: Locale,
     },
     Login {
         player_token: TruncateToken,
@@ -62,16 +81,42 @@ pub enum PlayerMessage {
         screen_height: u32,
         user_agent: String,
         referrer: String,
+        /// The language the player is playing in, which picks their dictionary
+        #[serde(default)]
+        locale: Locale,
+    },
+    /// Claims a unique display name for the player's account
+    ClaimName {
+        player_token: TruncateToken,
+        player_name: String,
     },
+    /// Emails a link that logs into the account with that email,
+    /// linking the email to this player if no account has it yet
+    RequestLoginLink {
+        player_token: TruncateToken,
+        email: String,
+    },
+    RedeemLoginLink(String),
     LoadDailyPuzzle(TruncateToken, u32),
     PersistPuzzleMoves {
         player_token: TruncateToken,
         day: u32,
+        /// Which attempt at the day's puzzle these moves belong to,
+        /// or the latest attempt if not given
+        #[serde(default)]
+        attempt: Option<u32>,
         human_player: u32,
         moves: Vec<Move>,
         won: bool,
+        /// How many hints the player has asked for during this attempt
+        #[serde(default)]
+        hints: u32,
+        /// How many of their moves the player has taken back during this attempt
+        #[serde(default)]
+        takebacks: u32,
     },
     RequestStats(TruncateToken),
+    RequestMatchHistory(TruncateToken),
     LoadReplay(String),
     MarkChangelogRead(String),
     GenericEvent {
@@ -99,8 +144,20 @@ impl fmt::Display for PlayerMessage {
             PlayerMessage::RejoinGame(token) => {
                 write!(f, "Player wants to rejoin a game using the token {}", token)
             }
+            PlayerMessage::SpectateGame(room) => write!(f, "Spectate game {room}"),
+            PlayerMessage::StopSpectating => write!(f, "Stop spectating"),
+            PlayerMessage::FindMatch {
+                player_name,
+                rules_generation,
+                ..
+            } => write!(
+                f,
+                "Find a match for player {player_name} with rules {rules_generation:?}"
+            ),
+            PlayerMessage::CancelMatch => write!(f, "Stop looking for a match"),
             PlayerMessage::EditBoard(board) => write!(f, "Set board to {board}"),
             PlayerMessage::EditName(name) => write!(f, "Set name to {name}"),
+            PlayerMessage::SetRules(settings) => write!(f, "Set rules to {settings:?}"),
             PlayerMessage::StartGame => write!(f, "Start the game"),
             PlayerMessage::Resign => write!(f, "Resign"),
             PlayerMessage::Place(coord, tile) => write!(f, "Place {} at {}", tile, coord),
@@ -115,6 +172,11 @@ impl fmt::Display for PlayerMessage {
             PlayerMessage::Login { .. } => {
                 write!(f, "Login as an existing player")
             }
+            PlayerMessage::ClaimName { player_name, .. } => {
+                write!(f, "Claim the name {player_name}")
+            }
+            PlayerMessage::RequestLoginLink { .. } => write!(f, "Email a login link"),
+            PlayerMessage::RedeemLoginLink(_) => write!(f, "Log in with an emailed link"),
             PlayerMessage::LoadDailyPuzzle(_token, day) => {
                 write!(f, "Load any partial puzzle for day {day:?}")
             }
@@ -122,12 +184,16 @@ impl fmt::Display for PlayerMessage {
                 player_token: _,
                 human_player: _,
                 day,
+                attempt: _,
                 moves,
                 won: _,
+                hints: _,
+                takebacks: _,
             } => {
                 write!(f, "Persist {} move(s) for day {day:?}", moves.len())
             }
             PlayerMessage::RequestStats(_token) => write!(f, "Requesting daily puzzle stats!"),
+            PlayerMessage::RequestMatchHistory(_token) => write!(f, "Requesting match history!"),
             PlayerMessage::LoadReplay(id) => write!(f, "Requesting the replay for {id}!"),
             PlayerMessage::MarkChangelogRead(id) => write!(f, "Marked changelog {id} as read"),
             PlayerMessage::GenericEvent { name } => write!(f, "Tracking a {name} event"),
@@ -203,6 +269,10 @@ pub struct DailyStateMessage {
     pub puzzle_day: u32,
     pub attempt: u32,
     pub current_moves: Vec<Move>,
+    #[serde(default)]
+    pub hints: u32,
+    #[serde(default)]
+    pub takebacks: u32,
 }
 
 impl fmt::Display for DailyStateMessage {
@@ -225,6 +295,17 @@ pub struct DailyAttempt {
     pub id: String,
     pub moves: u32,
     pub won: bool,
+    #[serde(default)]
+    pub hints: u32,
+    #[serde(default)]
+    pub takebacks: u32,
+}
+
+impl DailyAttempt {
+    /// Whether the player asked for any help during this attempt
+    pub fn assisted(&self) -> bool {
+        self.hints > 0 || self.takebacks > 0
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -253,6 +334,128 @@ impl DailyStats {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchHistoryEntry {
+    /// Can be passed to `LoadReplay` to watch the game back
+    pub game_id: String,
+    pub finished_at: u64,
+    pub duration_secs: u64,
+    pub player_number: PlayerNumber,
+    pub opponents: Vec<String>,
+    pub won: bool,
+    /// How far the game moved the player's rating, if it was rated
+    pub rating_change: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchHistory {
+    pub rating: i32,
+    pub games: Vec<MatchHistoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiplayerReplayMessage {
+    pub game_id: String,
+    /// The game as it was just before starting, which holds the board, tile bag and players
+    pub starting_game: Game,
+    pub moves: Vec<Move>,
+    /// The player to watch the replay as
+    pub player_number: PlayerNumber,
+}
+
+impl fmt::Display for MultiplayerReplayMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Game {} with {} move(s), as player {}",
+            self.game_id,
+            self.moves.len(),
+            self.player_number
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Presence {
+    Connected,
+    /// The player has lost their connection, and forfeits at the given time unless they return
+    Disconnected {
+        forfeits_at: Option<u64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MatchmakingStatus {
+    /// Waiting for an opponent, until the given time
+    Searching {
+        gives_up_at: u64,
+    },
+    Cancelled,
+    /// Nobody was found in time, so the player should be offered a computer opponent
+    TimedOut,
+}
+
+/// Why the server couldn't do what a player asked, so that clients can describe it in their own words
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerError {
+    #[error("Room {} does not exist", .0.to_ascii_uppercase())]
+    RoomNotFound(RoomCode),
+    #[error("Room {} already has two players, cannot join", .0.to_ascii_uppercase())]
+    RoomFull(RoomCode),
+    #[error("Unable to join room {}", .0.to_ascii_uppercase())]
+    CannotJoinRoom(RoomCode),
+    #[error("Error rejoining existing game")]
+    CannotRejoin,
+    #[error("Not in a lobby")]
+    NotInLobby,
+    #[error("Not in a game")]
+    NotInGame,
+    #[error("Not a player in this game")]
+    PlayerNotFound,
+    #[error("Only the host can change the rules")]
+    NotHost,
+    #[error("The game has already started")]
+    GameStarted,
+    #[error("Cannot rematch unfinished game")]
+    GameUnfinished,
+    #[error("Spectators can't make changes to the game")]
+    SpectatorsCannotPlay,
+    #[error("Cannot spectate while playing in a game")]
+    AlreadyPlaying,
+    #[error("Obelisk proximity needs a board with exactly one obelisk")]
+    ObeliskRequired,
+    #[error("Those rules don't exist")]
+    UnknownRules,
+    #[error("Invalid Token")]
+    InvalidToken,
+    #[error("Invalid game token")]
+    InvalidGameToken,
+    #[error("Please log in to find a match")]
+    LoginRequired,
+    #[error("Invalid Replay ID")]
+    InvalidReplayId,
+    #[error("Replay does not exist")]
+    ReplayNotFound,
+    #[error("This daily puzzle has already been won")]
+    PuzzleComplete,
+    #[error("Names must be 3 to 20 letters, numbers, dashes or underscores")]
+    InvalidName,
+    #[error("That name is already taken")]
+    NameTaken,
+    #[error("That email address doesn't look right")]
+    InvalidEmail,
+    #[error("That login link has expired or was already used")]
+    InvalidLoginLink,
+    #[error("Too many login links have been requested, try again later")]
+    TooManyLoginLinks,
+    #[error("The server can't reach its database right now")]
+    DatabaseOffline,
+    #[error("Something about this request was malformed")]
+    BadRequest,
+    #[error("Something went wrong on the server")]
+    Internal,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GameMessage {
     Ping,
@@ -263,23 +466,42 @@ pub enum GameMessage {
         RoomCode,
         Vec<LobbyPlayerMessage>,
         Board,
+        RuleSettings,
         TruncateToken,
     ),
-    LobbyUpdate(PlayerNumber, RoomCode, Vec<LobbyPlayerMessage>, Board),
+    LobbyUpdate(
+        PlayerNumber,
+        RoomCode,
+        Vec<LobbyPlayerMessage>,
+        Board,
+        RuleSettings,
+    ),
     StartedGame(GameStateMessage),
+    SpectatingGame(GameStateMessage),
+    SpectatorsUpdate(RoomCode, u64),
     GameTimingUpdate(GameStateMessage),
     GameUpdate(GameStateMessage),
     GameEnd(GameStateMessage, PlayerNumber),
-    GameError(RoomCode, PlayerNumber, String),
-    GenericError(String),
+    GameError(RoomCode, PlayerNumber, GamePlayError),
+    PlayerPresence(RoomCode, PlayerNumber, Presence),
+    Matchmaking(MatchmakingStatus),
+    Error(ServerError),
     SupplyDefinitions(Vec<(String, Option<Vec<WordMeaning>>)>),
     LoggedInAs {
         token: TruncateToken,
+        player_name: Option<String>,
         unread_changelogs: Vec<String>,
     },
+    NameClaimed(String),
+    /// A login link was emailed to the given address
+    LoginLinkSent(String),
     ResumeDailyPuzzle(DailyStateMessage, Option<DailyStateMessage>), // (latest, best)
     DailyStats(DailyStats),
+    /// The moves for a day's puzzle were saved, to the given attempt if one was asked for
+    DailyMovesSaved(u32, Option<u32>),
     LoadDailyReplay(DailyStateMessage),
+    MatchHistory(MatchHistory),
+    LoadMultiplayerReplay(Box<MultiplayerReplayMessage>),
 }
 
 impl fmt::Display for GameMessage {
@@ -288,7 +510,7 @@ impl fmt::Display for GameMessage {
             GameMessage::Ping => write!(f, "Game ping"),
             GameMessage::Ack(_) => write!(f, "ACK"),
             GameMessage::PleaseLogin => write!(f, "Server is requesting player to login"),
-            GameMessage::JoinedLobby(player, room, players, board, _token) => write!(
+            GameMessage::JoinedLobby(player, room, players, board, _settings, _token) => write!(
                 f,
                 "Joined lobby {} as player {} with players {}. Board is:\n{}",
                 player,
@@ -300,9 +522,9 @@ impl fmt::Display for GameMessage {
                     .join(", "),
                 board
             ),
-            GameMessage::LobbyUpdate(player, room, players, board) => write!(
+            GameMessage::LobbyUpdate(player, room, players, board, settings) => write!(
                 f,
-                "Update to lobby {} as player {}. Players are {}. Board is:\n{}",
+                "Update to lobby {} as player {}. Players are {}. Rules are {:?}. Board is:\n{}",
                 player,
                 room,
                 players
@@ -310,27 +532,45 @@ impl fmt::Display for GameMessage {
                     .map(|p| p.name.clone())
                     .collect::<Vec<_>>()
                     .join(", "),
+                settings,
                 board
             ),
             GameMessage::StartedGame(game) => write!(f, "Started game:\n{}", game),
+            GameMessage::SpectatingGame(game) => write!(f, "Spectating game:\n{}", game),
+            GameMessage::SpectatorsUpdate(room, count) => {
+                write!(f, "Room {room} has {count} spectator(s)")
+            }
             GameMessage::GameTimingUpdate(game) => write!(f, "Update to timing:\n{}", game),
             GameMessage::GameUpdate(game) => write!(f, "Update to game:\n{}", game),
             GameMessage::GameEnd(game, winner) => {
                 write!(f, "Conclusion of game, winner was {}:\n{}", winner, game)
             }
             GameMessage::GameError(_, _, msg) => write!(f, "Error in game: {}", msg),
-            GameMessage::GenericError(msg) => write!(f, "Generic error: {}", msg),
+            GameMessage::Matchmaking(status) => write!(f, "Matchmaking is {:?}", status),
+            GameMessage::PlayerPresence(room, player, presence) => {
+                write!(f, "Player {} in {} is now {:?}", player, room, presence)
+            }
+            GameMessage::Error(error) => write!(f, "Error: {}", error),
             GameMessage::SupplyDefinitions(_) => {
                 write!(f, "Supplying definitions for words")
             }
             GameMessage::LoggedInAs { .. } => {
                 write!(f, "Logged in as a player")
             }
+            GameMessage::NameClaimed(name) => write!(f, "Claimed the name {name}"),
+            GameMessage::LoginLinkSent(email) => write!(f, "Emailed a login link to {email}"),
             GameMessage::ResumeDailyPuzzle(puzzle, _best) => {
                 write!(f, "Starting puzzle:\n{}", puzzle)
             }
             GameMessage::DailyStats(stats) => write!(f, "Stats for {} days", stats.days.len()),
+            GameMessage::DailyMovesSaved(day, _) => write!(f, "Saved moves for day {day}"),
             GameMessage::LoadDailyReplay(puzzle) => write!(f, "Loading puzzle replay:\n{}", puzzle),
+            GameMessage::MatchHistory(history) => {
+                write!(f, "Match history of {} game(s)", history.games.len())
+            }
+            GameMessage::LoadMultiplayerReplay(replay) => {
+                write!(f, "Loading multiplayer replay:\n{}", replay)
+            }
         }
     }
 }
@@ -19,7 +19,7 @@ fn unpack_coord(packed_coord: &String) -> Result<Coordinate, ()> {
     })
 }
 
-pub fn pack_moves(moves: &Vec<Move>, player_count: usize) -> String {
+pub fn pack_moves(moves: &[Move], player_count: usize) -> String {
     let mut packed = String::with_capacity(moves.len() * 3);
 
     let mut next_player: usize = 0;
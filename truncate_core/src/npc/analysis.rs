@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{game::Game, judge::WordDict, messages::PlayerMessage, moves::Move};
+
+use super::{
+    scoring::{BoardScore, NPCParams, ScoreComponent},
+    Arborist, Caches,
+};
+
+/// How far a move has to fall behind the NPC's suggestion to be considered a blunder
+pub const BLUNDER_THRESHOLD: f32 = 0.1;
+
+/// How many components of the evaluation are given as reasons for a blunder
+const MAX_REASONS: usize = 2;
+
+/// A review of a single move from a finished game.
+/// Evaluations are from the perspective of the player who moved,
+/// from 0 (lost) to 1 (won).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveAnalysis {
+    pub player: usize,
+    pub eval_before: f32,
+    pub eval_played: f32,
+    /// The move the NPC would have played instead, if it differs from the played move
+    pub suggestion: Option<Move>,
+    pub eval_suggested: f32,
+    /// The parts of the evaluation where the played move fell furthest behind the suggestion
+    pub reasons: Vec<ScoreComponent>,
+}
+
+impl MoveAnalysis {
+    /// How much better the position would have been for the player had they taken the suggestion
+    pub fn loss(&self) -> f32 {
+        self.eval_suggested - self.eval_played
+    }
+
+    pub fn is_blunder(&self) -> bool {
+        self.suggestion.is_some() && self.loss() >= BLUNDER_THRESHOLD
+    }
+}
+
+impl Game {
+    /// Compares a move that was played from this position against what the NPC would have played,
+    /// searching as deep and as widely as the given params allow.
+    /// Both moves are scored by searching the replies to them to the same depth.
+    /// Returns None if the move can't be analysed, such as in periodic games or after the game is over.
+    pub fn analyse_move(
+        &self,
+        played: &Move,
+        dictionary: &WordDict,
+        npc_params: &NPCParams,
+    ) -> Option<MoveAnalysis> {
+        let player = match played {
+            Move::Place { player, .. } | Move::Swap { player, .. } => *player,
+        };
+        if self.winner.is_some() || self.next_player != Some(player) {
+            return None;
+        }
+
+        let new_arborist = || {
+            if npc_params.pruning {
+                Arborist::pruning()
+            } else {
+                Arborist::exhaustive()
+            }
+        };
+
+        // The flood fill cache assumes a single perspective, so it can't outlive this move
+        let mut caches = Caches::new();
+        let reply_depth = npc_params.max_depth.saturating_sub(1);
+        let mut eval_after = |next_move: &Move| {
+            let mut game = self.clone();
+            game.play_turn(next_move.clone(), Some(dictionary), Some(dictionary), None)
+                .ok()?;
+            // Scored the same way the search scores this move, so the two evaluations line up
+            let (score, _) = Game::minimax(
+                game,
+                Some(dictionary),
+                Some(dictionary),
+                reply_depth,
+                reply_depth,
+                1,
+                BoardScore::neg_inf(),
+                BoardScore::inf(),
+                player,
+                &mut new_arborist(),
+                &mut caches,
+                npc_params,
+            );
+            Some(score)
+        };
+
+        let played_score = eval_after(played)?;
+
+        let mut arborist = new_arborist();
+        arborist.capped(npc_params.evaluation_cap);
+
+        let (suggested_move, _) = Game::best_move(
+            self,
+            Some(dictionary),
+            Some(dictionary),
+            npc_params.max_depth,
+            Some(&mut arborist),
+            false,
+            npc_params,
+        );
+        let suggested_move = match suggested_move {
+            PlayerMessage::Place(position, tile) => Move::Place {
+                player,
+                tile,
+                position,
+            },
+            PlayerMessage::Swap(from, to) => Move::Swap {
+                player,
+                positions: [from, to],
+            },
+            _ => return None,
+        };
+        let suggestion = Some(suggested_move).filter(|suggestion| !same_move(suggestion, played));
+
+        let suggested_score = match &suggestion {
+            Some(suggestion) => eval_after(suggestion)?,
+            None => played_score.clone(),
+        };
+
+        let mut losses: Vec<_> = suggested_score
+            .components()
+            .into_iter()
+            .zip(played_score.components())
+            .map(|((component, suggested), (_, played))| (component, suggested - played))
+            .filter(|(_, loss)| *loss > 0.0)
+            .collect();
+        losses.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        let eval_before = self
+            .static_eval(Some(dictionary), player, 0, &mut caches, npc_params)
+            .win_likelihood();
+
+        Some(MoveAnalysis {
+            player,
+            eval_before,
+            eval_played: played_score.win_likelihood(),
+            suggestion,
+            eval_suggested: suggested_score.win_likelihood(),
+            reasons: losses
+                .into_iter()
+                .take(MAX_REASONS)
+                .map(|(component, _)| component)
+                .collect(),
+        })
+    }
+}
+
+/// Swaps are the same move whichever way around their squares are given
+fn same_move(a: &Move, b: &Move) -> bool {
+    match (a, b) {
+        (
+            Move::Swap {
+                player: a_player,
+                positions: [a_from, a_to],
+            },
+            Move::Swap {
+                player: b_player,
+                positions: [b_from, b_to],
+            },
+        ) => {
+            a_player == b_player
+                && ([a_from, a_to] == [b_from, b_to] || [a_from, a_to] == [b_to, b_from])
+        }
+        _ => a == b,
+    }
+}
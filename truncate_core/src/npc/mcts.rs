@@ -0,0 +1,275 @@
+use oorandom::Rand32;
+
+use crate::{
+    bag::TileBag,
+    board::{Coordinate, Square},
+    game::Game,
+    judge::WordDict,
+    moves::Move,
+    player::Hand,
+};
+
+use super::{
+    scoring::{BoardScore, NPCParams},
+    Arborist, Caches,
+};
+
+/// How strongly the search favours exploring rarely visited moves
+const EXPLORATION: f32 = 0.7;
+
+struct Node {
+    mv: Option<(Coordinate, char)>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    depth: usize,
+    visits: f32,
+    /// How many times this move was legal when its parent was visited.
+    /// Opponent hands differ between determinizations, so not every move is always available.
+    available: f32,
+    /// Accumulated rewards, from the perspective of the evaluation player
+    reward: f32,
+}
+
+impl Node {
+    fn new(mv: Option<(Coordinate, char)>, parent: Option<usize>, depth: usize) -> Self {
+        Self {
+            mv,
+            parent,
+            children: vec![],
+            depth,
+            visits: 0.0,
+            available: 1.0,
+            reward: 0.0,
+        }
+    }
+
+    fn ucb(&self, maximizing: bool) -> f32 {
+        let mean = self.reward / self.visits;
+        let exploitation = if maximizing { mean } else { 1.0 - mean };
+        exploitation + EXPLORATION * (self.available.ln() / self.visits).sqrt()
+    }
+}
+
+impl Game {
+    /// Information set Monte Carlo tree search.
+    ///
+    /// Each iteration samples the hidden parts of the game (opponent hands and the bag),
+    /// walks the tree, then plays random moves for up to `horizon` turns before
+    /// scoring the board with `static_eval`. Stops when the arborist is exhausted,
+    /// and picks the most visited move.
+    pub(super) fn monte_carlo(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        horizon: usize,
+        arborist: &mut Arborist,
+        caches: &mut Caches,
+        npc_params: &NPCParams,
+    ) -> (BoardScore, Option<(Coordinate, char)>) {
+        let evaluation_player = game
+            .next_player
+            .expect("Monte Carlo search only works in non-periodic playmodes");
+
+        if game.winner.is_some() || game.possible_moves().is_empty() {
+            return (
+                game.static_eval(self_dictionary, evaluation_player, 0, caches, npc_params),
+                None,
+            );
+        }
+
+        let mut rng = Rand32::new(game.turn_count as u64);
+        let mut tree = vec![Node::new(None, None, 0)];
+
+        while !arborist.exhausted() {
+            let mut sim = game.determinize(evaluation_player, &mut rng);
+            let mut node = 0;
+
+            // Walk down the tree until we reach a move we haven't tried yet
+            while sim.winner.is_none() {
+                let legal = sim.possible_moves();
+                if legal.is_empty() {
+                    break;
+                }
+
+                let mut untried = vec![];
+                for mv in &legal {
+                    match tree[node]
+                        .children
+                        .iter()
+                        .copied()
+                        .find(|child| tree[*child].mv == Some(*mv))
+                    {
+                        Some(child) => tree[child].available += 1.0,
+                        None => untried.push(*mv),
+                    }
+                }
+
+                if !untried.is_empty() {
+                    let mv = untried[rng.rand_range(0..untried.len() as u32) as usize];
+                    sim.play_simulated(
+                        mv,
+                        evaluation_player,
+                        self_dictionary,
+                        opponent_dictionary,
+                        caches,
+                    );
+                    arborist.tick();
+
+                    let child = tree.len();
+                    tree.push(Node::new(Some(mv), Some(node), tree[node].depth + 1));
+                    tree[node].children.push(child);
+                    node = child;
+                    break;
+                }
+
+                let maximizing = sim.next_player == Some(evaluation_player);
+                let parent = &tree[node];
+                let Some(selected) = parent
+                    .children
+                    .iter()
+                    .copied()
+                    .filter(|child| legal.contains(&tree[*child].mv.unwrap()))
+                    .max_by(|a, b| {
+                        let (a, b) = (&tree[*a], &tree[*b]);
+                        a.ucb(maximizing).total_cmp(&b.ucb(maximizing))
+                    })
+                else {
+                    break;
+                };
+
+                sim.play_simulated(
+                    tree[selected].mv.unwrap(),
+                    evaluation_player,
+                    self_dictionary,
+                    opponent_dictionary,
+                    caches,
+                );
+                arborist.tick();
+                node = selected;
+            }
+
+            // Play out the rest of the game randomly, up to our horizon
+            for _ in 0..horizon {
+                if sim.winner.is_some() {
+                    break;
+                }
+                let legal = sim.possible_moves();
+                if legal.is_empty() {
+                    break;
+                }
+                let mv = legal[rng.rand_range(0..legal.len() as u32) as usize];
+                sim.play_simulated(
+                    mv,
+                    evaluation_player,
+                    self_dictionary,
+                    opponent_dictionary,
+                    caches,
+                );
+                arborist.tick();
+            }
+
+            let reward = sim
+                .static_eval(self_dictionary, evaluation_player, 0, caches, npc_params)
+                .win_likelihood();
+
+            arborist.depth_reached = arborist.depth_reached.max(tree[node].depth);
+
+            let mut backprop = Some(node);
+            while let Some(index) = backprop {
+                tree[index].visits += 1.0;
+                tree[index].reward += reward;
+                backprop = tree[index].parent;
+            }
+        }
+
+        let Some(best_move) = tree[0]
+            .children
+            .iter()
+            .max_by(|a, b| tree[**a].visits.total_cmp(&tree[**b].visits))
+            .and_then(|best| tree[*best].mv)
+        else {
+            return (
+                game.static_eval(self_dictionary, evaluation_player, 0, caches, npc_params),
+                None,
+            );
+        };
+
+        let mut resulting_game = game.clone();
+        resulting_game.rules.battle_delay = 0;
+        resulting_game.play_simulated(
+            best_move,
+            evaluation_player,
+            self_dictionary,
+            opponent_dictionary,
+            caches,
+        );
+        let score =
+            resulting_game.static_eval(self_dictionary, evaluation_player, 0, caches, npc_params);
+
+        (score, Some(best_move))
+    }
+
+    /// A copy of the game with everything the evaluation player can't know
+    /// (opponent hands and the order of the bag) randomly resampled.
+    pub(super) fn determinize(&self, evaluation_player: usize, rng: &mut Rand32) -> Game {
+        let mut sim = self.clone();
+
+        // Remove timing concerns from the simulated turns
+        sim.rules.battle_delay = 0;
+
+        sim.bag = TileBag::generation(sim.rules.tile_generation, Some(rng.rand_u32() as u64));
+
+        // Tiles the evaluation player can already see can't be drawn by anyone else
+        let board_tiles = sim
+            .board
+            .squares
+            .iter()
+            .flatten()
+            .filter_map(|square| match square {
+                Square::Occupied { tile, .. } => Some(*tile),
+                _ => None,
+            });
+        let known_tiles = board_tiles.chain(sim.players[evaluation_player].hand.iter().copied());
+        for tile in known_tiles {
+            sim.bag.remove_tile(tile);
+        }
+
+        for (index, player) in sim.players.iter_mut().enumerate() {
+            if index == evaluation_player {
+                continue;
+            }
+            let hand_size = player.hand.len();
+            player.hand = Hand((0..hand_size).map(|_| sim.bag.draw_tile()).collect());
+        }
+
+        sim
+    }
+
+    fn play_simulated(
+        &mut self,
+        (position, tile): (Coordinate, char),
+        evaluation_player: usize,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        caches: &mut Caches,
+    ) {
+        let next_player = self.next_player.unwrap();
+        let (attacker_dict, defender_dict) = if next_player == evaluation_player {
+            (self_dictionary, opponent_dictionary)
+        } else {
+            (opponent_dictionary, self_dictionary)
+        };
+
+        self.play_turn(
+            Move::Place {
+                player: next_player,
+                tile,
+                position,
+            },
+            attacker_dict,
+            defender_dict,
+            Some(&mut caches.cached_words),
+        )
+        .expect("Should be exploring valid turns");
+    }
+}
@@ -1,8 +1,15 @@
 use std::{
     collections::{HashMap, HashSet},
     ops::Div,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering},
+        Arc,
+    },
+    time::Duration,
 };
 
+use instant::Instant;
+
 use crate::{
     board::{BoardDistances, Coordinate, Square},
     game::Game,
@@ -12,43 +19,91 @@ use crate::{
     player::Hand,
 };
 
+pub mod analysis;
+mod mcts;
+#[cfg(feature = "parallel")]
+mod parallel;
 pub mod scoring;
 
 use scoring::BoardScore;
 use xxhash_rust::xxh3;
 
-use self::scoring::NPCParams;
+use self::scoring::{NPCEngine, NPCParams};
 
 #[derive(Debug)]
 pub struct Arborist {
-    assessed: usize,
+    // Shared so that parallel branches of a search count against the same cap
+    assessed: Arc<AtomicUsize>,
     prune: bool,
+    #[cfg(feature = "parallel")]
+    parallel: bool,
     cap: usize,
+    budget: Option<Duration>,
+    deadline: Option<Instant>,
+    cancel: Option<CancelToken>,
+    started: Instant,
+    depth_reached: usize,
+    interrupted: bool,
+    // Set when any branch skips part of the tree, which leaves the current depth incomplete
+    stopped_early: Arc<AtomicBool>,
 }
 impl Arborist {
     pub fn pruning() -> Self {
         Self {
-            assessed: 0,
             prune: true,
-            cap: std::usize::MAX,
+            ..Self::exhaustive()
         }
     }
 
     pub fn assessed(&self) -> usize {
-        self.assessed
+        self.assessed.load(AtomicOrdering::Relaxed)
     }
 
     pub fn capped(&mut self, cap: usize) {
         self.cap = cap;
     }
 
+    /// Bounds the search by wall-clock time rather than (or as well as) the evaluation cap.
+    /// The budget starts counting when the search begins.
+    pub fn timed(&mut self, budget: Duration) {
+        self.budget = Some(budget);
+    }
+
+    pub fn cancellable(&mut self, token: CancelToken) {
+        self.cancel = Some(token);
+    }
+
+    /// Splits the root of the search across threads.
+    #[cfg(feature = "parallel")]
+    pub fn parallel(&mut self) {
+        self.parallel = true;
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        SearchStats {
+            assessed: self.assessed(),
+            depth: self.depth_reached,
+            elapsed: self.started.elapsed(),
+            interrupted: self.interrupted,
+        }
+    }
+
     /// Useful for testing, dead in production code
     #[allow(dead_code)]
     fn exhaustive() -> Self {
         Self {
-            assessed: 0,
+            assessed: Arc::new(AtomicUsize::new(0)),
             prune: false,
+            #[cfg(feature = "parallel")]
+            parallel: false,
             cap: std::usize::MAX,
+            budget: None,
+            deadline: None,
+            cancel: None,
+            started: Instant::now(),
+            depth_reached: 0,
+            interrupted: false,
+            stopped_early: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -57,10 +112,95 @@ impl Arborist {
     }
 
     fn tick(&mut self) {
-        self.assessed += 1
+        self.assessed.fetch_add(1, AtomicOrdering::Relaxed);
+    }
+
+    /// A handle for searching one branch of the tree on another thread,
+    /// sharing this arborist's limits and evaluation count.
+    #[cfg(feature = "parallel")]
+    fn branch(&self) -> Self {
+        Self {
+            assessed: Arc::clone(&self.assessed),
+            prune: self.prune,
+            parallel: false,
+            cap: self.cap,
+            budget: self.budget,
+            deadline: self.deadline,
+            cancel: self.cancel.clone(),
+            started: self.started,
+            depth_reached: self.depth_reached,
+            interrupted: false,
+            stopped_early: Arc::clone(&self.stopped_early),
+        }
+    }
+
+    fn begin(&mut self) {
+        self.started = Instant::now();
+        self.deadline = self.budget.map(|budget| self.started + budget);
+        self.depth_reached = 0;
+        self.interrupted = false;
+    }
+
+    /// Whether the search should stop expanding the tree.
+    /// Time and cancellation can only interrupt once the first depth has completed,
+    /// so that there is always a move to play.
+    fn exhausted(&self) -> bool {
+        if self.assessed() > self.cap {
+            return true;
+        }
+        if self.depth_reached == 0 {
+            return false;
+        }
+
+        let out_of_time = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        let cancelled = self.cancel.as_ref().is_some_and(|c| c.is_cancelled());
+
+        out_of_time || cancelled
+    }
+
+    /// Checks whether to stop expanding the tree, remembering if we did
+    /// so that a partly searched depth is never mistaken for a finished one.
+    fn stop_here(&self) -> bool {
+        let exhausted = self.exhausted();
+        if exhausted {
+            self.stopped_early.store(true, AtomicOrdering::Relaxed);
+        }
+        exhausted
+    }
+}
+
+/// Allows a search running elsewhere to be stopped early,
+/// in which case it returns the best move from its deepest completed depth.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchStats {
+    /// Total boards checked across all depths
+    pub assessed: usize,
+    /// Deepest depth that was fully searched
+    pub depth: usize,
+    pub elapsed: Duration,
+    /// Whether the search was cut short by the cap, time budget, or cancellation
+    pub interrupted: bool,
+}
+
 pub struct Caches {
     cached_floods: HashMap<Vec<u64>, (BoardDistances, BoardDistances), xxh3::Xxh3Builder>,
     cached_scores: HashMap<(Coordinate, char, usize), usize, xxh3::Xxh3Builder>,
@@ -119,21 +259,37 @@ impl Game {
         let mut looked = 0;
 
         let arborist = counter.unwrap_or_else(|| &mut internal_arborist);
-        for d in 1..depth {
-            let maybelatest = Some(run_mini(d, arborist));
-
-            if arborist.assessed > arborist.cap {
-                break;
+        arborist.begin();
+
+        match npc_params.engine {
+            NPCEngine::Minimax => {
+                for d in 1..=depth {
+                    // A depth only counts if the search never had to skip any of it,
+                    // however close to the deadline it finished.
+                    arborist.stopped_early.store(false, AtomicOrdering::Relaxed);
+                    let maybelatest = Some(run_mini(d, arborist));
+
+                    if arborist.stopped_early.load(AtomicOrdering::Relaxed) {
+                        arborist.interrupted = true;
+                        break;
+                    }
+                    latest = maybelatest;
+                    looked = d;
+                    arborist.depth_reached = d;
+                }
             }
-            latest = maybelatest;
-            looked = d;
-        }
-
-        if arborist.assessed < arborist.cap {
-            let maybelatest = Some(run_mini(depth, arborist));
-            if arborist.assessed < arborist.cap {
-                latest = maybelatest;
-                looked = depth;
+            NPCEngine::MonteCarlo => {
+                // For Monte Carlo, depth is the length of each random playout
+                latest = Some(Game::monte_carlo(
+                    game,
+                    self_dictionary,
+                    opponent_dictionary,
+                    depth,
+                    arborist,
+                    &mut caches,
+                    npc_params,
+                ));
+                looked = arborist.depth_reached;
             }
         }
 
@@ -157,6 +313,40 @@ impl Game {
         (PlayerMessage::Place(position, tile), best_score)
     }
 
+    /// Searches deeper until the time budget runs out (or the search is cancelled),
+    /// returning the best move from the deepest depth that was fully searched.
+    /// `npc_params.max_depth` still bounds how deep the search can go.
+    pub fn best_move_within(
+        game: &Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        budget: Duration,
+        cancel: Option<CancelToken>,
+        npc_params: &NPCParams,
+    ) -> (PlayerMessage, BoardScore, SearchStats) {
+        let mut arborist = if npc_params.pruning {
+            Arborist::pruning()
+        } else {
+            Arborist::exhaustive()
+        };
+        arborist.timed(budget);
+        if let Some(cancel) = cancel {
+            arborist.cancellable(cancel);
+        }
+
+        let (best_move, score) = Game::best_move(
+            game,
+            self_dictionary,
+            opponent_dictionary,
+            npc_params.max_depth,
+            Some(&mut arborist),
+            false,
+            npc_params,
+        );
+
+        (best_move, score, arborist.stats())
+    }
+
     fn minimax(
         mut game: Game,
         self_dictionary: Option<&WordDict>,
@@ -181,19 +371,27 @@ impl Game {
             );
         }
 
-        let mut possible_moves = game.possible_moves();
-        possible_moves.sort_by_cached_key(|(position, tile)| {
-            std::usize::MAX
-                - caches
-                    .cached_scores
-                    .get(&(*position, *tile, layer))
-                    .unwrap_or(&std::usize::MAX)
-        });
+        #[cfg(feature = "parallel")]
+        if arborist.parallel && layer == 0 && depth > 1 {
+            return Game::minimax_parallel_root(
+                game,
+                self_dictionary,
+                opponent_dictionary,
+                total_depth,
+                depth,
+                for_player,
+                arborist,
+                caches,
+                npc_params,
+            );
+        }
+
+        let possible_moves = game.ordered_moves(caches, layer);
 
         let mut turn_score =
             |game: &Game, tile: char, position: Coordinate, alpha: BoardScore, beta: BoardScore| {
                 arborist.tick();
-                if arborist.assessed > arborist.cap {
+                if arborist.stop_here() {
                     return None;
                 }
                 let mut next_turn = game.clone();
@@ -305,6 +503,19 @@ impl Game {
         }
     }
 
+    /// Possible moves, with those that scored best in shallower searches first
+    fn ordered_moves(&self, caches: &Caches, layer: usize) -> Vec<(Coordinate, char)> {
+        let mut possible_moves = self.possible_moves();
+        possible_moves.sort_by_cached_key(|(position, tile)| {
+            std::usize::MAX
+                - caches
+                    .cached_scores
+                    .get(&(*position, *tile, layer))
+                    .unwrap_or(&std::usize::MAX)
+        });
+        possible_moves
+    }
+
     fn possible_moves(&self) -> Vec<(Coordinate, char)> {
         let mut playable_tiles: Vec<_> = self
             .players
@@ -566,7 +777,6 @@ impl Game {
                             word,
                             &crate::rules::WinCondition::Elimination,
                             Some(external_dictionary),
-                            None,
                             &mut Some(&mut caches.cached_words),
                         );
                         if let Some(resolved_word) = resolved {
@@ -599,13 +809,16 @@ impl Game {
 mod tests {
     use super::*;
 
-    use crate::{bag::TileBag, board::Board, judge::WordData, player::Player, rules::GameRules};
+    use crate::{
+        bag::TileBag, board::Board, judge::WordData, npc::scoring::NPCPersonality, player::Player,
+        rules::GameRules,
+    };
 
     pub static TESTING_DICT: &str = include_str!("../../../dict_builder/final_wordlist.txt");
 
     /// Build an (expensive) word dictionary using the real game data.
     fn dict() -> WordDict {
-        let mut valid_words = HashMap::new();
+        let mut valid_words = WordDict::new();
         let lines = TESTING_DICT.lines();
 
         for line in lines {
@@ -709,8 +922,8 @@ mod tests {
 
         (
             pruned_best_move,
-            pruned_arbor.assessed,
-            exhaustive_arbor.assessed,
+            pruned_arbor.assessed(),
+            exhaustive_arbor.assessed(),
         )
     }
 
@@ -963,6 +1176,214 @@ mod tests {
         }
     }
 
+    #[test]
+    fn time_bounded_search() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~ ~~
+            __ S0 O0 __ __
+            __ T0 __ __ __
+            __ R0 __ __ __
+            __ __ T1 __ __
+            __ __ A1 __ __
+            __ __ R1 __ __
+            ~~ ~~ |1 ~~ ~~
+            "###,
+            "SEAT",
+        );
+        let npc_params = NPCParams {
+            max_depth: 3,
+            ..NPCParams::default()
+        };
+
+        // With no time to think, the first depth still completes
+        let (_, _, stats) = Game::best_move_within(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            Duration::ZERO,
+            None,
+            &npc_params,
+        );
+        assert_eq!(stats.depth, 1);
+        assert!(stats.interrupted);
+
+        // With plenty of time, we should match the unbounded search
+        let (timed_move, _, stats) = Game::best_move_within(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            Duration::from_secs(600),
+            None,
+            &npc_params,
+        );
+        let (unbounded_move, _) =
+            Game::best_move(&game, Some(&dict), Some(&dict), 3, None, false, &npc_params);
+        assert_eq!(stats.depth, 3);
+        assert!(!stats.interrupted);
+        assert_eq!(timed_move, unbounded_move);
+    }
+
+    #[test]
+    fn cancelled_search() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~ ~~
+            __ S0 O0 __ __
+            __ T0 __ __ __
+            __ R0 __ __ __
+            __ __ T1 __ __
+            __ __ A1 __ __
+            __ __ R1 __ __
+            ~~ ~~ |1 ~~ ~~
+            "###,
+            "SEAT",
+        );
+        let npc_params = NPCParams {
+            max_depth: 3,
+            ..NPCParams::default()
+        };
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let (_, _, stats) = Game::best_move_within(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            Duration::from_secs(600),
+            Some(cancel),
+            &npc_params,
+        );
+        assert_eq!(stats.depth, 1);
+        assert!(stats.interrupted);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_search_matches_sequential() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~ ~~ ~~ ~~
+            __ __ R0 __ __ __ __
+            __ __ A0 __ __ __ __
+            __ __ O0 S0 __ __ __
+            __ __ C0 T0 __ __ __
+            __ __ __ A0 __ __ __
+            __ __ __ B0 __ __ __
+            __ __ I1 __ __ __ __
+            __ __ D1 A1 T1 E1 S1
+            __ __ E1 __ __ __ __
+            ~~ ~~ |1 ~~ ~~ ~~ ~~
+            "###,
+            "MATERSK",
+        );
+
+        let (sequential_move, sequential_score) = Game::best_move(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            3,
+            Some(&mut Arborist::pruning()),
+            false,
+            &NPCParams::default(),
+        );
+
+        let mut parallel_arbor = Arborist::pruning();
+        parallel_arbor.parallel();
+        let (parallel_move, parallel_score) = Game::best_move(
+            &game,
+            Some(&dict),
+            Some(&dict),
+            3,
+            Some(&mut parallel_arbor),
+            false,
+            &NPCParams::default(),
+        );
+
+        assert_eq!(
+            sequential_score.partial_cmp(&parallel_score),
+            Some(std::cmp::Ordering::Equal)
+        );
+        assert_eq!(sequential_move, parallel_move);
+    }
+
+    #[test]
+    fn monte_carlo_tests() {
+        let dict = dict();
+        let board = r###"
+            ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~
+            ~~ ~~ ~~ ~~ __ ~~ __ ~~ ~~ ~~ ~~
+            ~~ ~~ |0 __ __ I1 __ __ ~~ ~~ ~~
+            ~~ #0 __ S1 __ O1 __ __ __ ~~ ~~
+            ~~ ~~ __ U1 T1 S1 __ ~~ __ ~~ ~~
+            ~~ __ G1 N1 U1 __ __ __ __ __ ~~
+            ~~ Y1 U1 __ S1 I1 B1 __ ~~ ~~ ~~
+            ~~ E1 ~~ __ H1 O1 L1 D1 #1 __ ~~
+            ~~ ~~ E1 L1 __ __ A1 A1 ~~ ~~ ~~
+            ~~ ~~ S1 E1 R1 E1 |1 #1 ~~ ~~ ~~
+            ~~ ~~ T1 A1 ~~ ~~ ~~ ~~ ~~ ~~ ~~
+            ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~
+            "###;
+        let npc_params = NPCPersonality::quartz().params;
+
+        let eval = || {
+            let game = test_game(board, "A");
+            let mut arbor = Arborist::pruning();
+            arbor.capped(500);
+            Game::best_move(
+                &game,
+                Some(&dict),
+                Some(&dict),
+                npc_params.max_depth,
+                Some(&mut arbor),
+                false,
+                &npc_params,
+            )
+            .0
+        };
+
+        let best = eval();
+        assert_eq!(
+            best,
+            PlayerMessage::Place(Coordinate { x: 2, y: 3 }, 'A'),
+            "Monte Carlo search should find the immediate win"
+        );
+        for _ in 0..5 {
+            assert_eq!(eval(), best);
+        }
+    }
+
+    #[test]
+    fn determinized_hands_skip_known_tiles() {
+        // Both of the Js, Qs, Xs and Zs are either on the board or in player 1's hand
+        let game = test_game(
+            r###"
+            ~~ ~~ |0 ~~ ~~
+            __ __ X0 __ __
+            __ __ __ __ __
+            __ __ X1 __ __
+            ~~ ~~ |1 ~~ ~~
+            "###,
+            "JJQQZZA",
+        );
+        let known = ['J', 'Q', 'X', 'Z'];
+
+        let mut rng = oorandom::Rand32::new(1);
+        for _ in 0..200 {
+            let sim = game.determinize(1, &mut rng);
+            assert_eq!(sim.players[1].hand, game.players[1].hand);
+            assert!(
+                !sim.players[0].hand.iter().any(|tile| known.contains(tile)),
+                "Sampled the impossible hand {}",
+                sim.players[0].hand
+            );
+        }
+    }
+
     #[test]
     fn generic_npc_tests() {
         let dict = dict();
@@ -1324,4 +1745,62 @@ mod tests {
             });
         }
     }
+
+    #[test]
+    fn analysis_flags_missed_wins() {
+        let dict = dict();
+        let game = test_game(
+            r###"
+            ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~
+            ~~ ~~ ~~ ~~ __ ~~ __ ~~ ~~ ~~ ~~
+            ~~ ~~ |0 __ __ I1 __ __ ~~ ~~ ~~
+            ~~ #0 __ S1 __ O1 __ __ __ ~~ ~~
+            ~~ ~~ __ U1 T1 S1 __ ~~ __ ~~ ~~
+            ~~ __ G1 N1 U1 __ __ __ __ __ ~~
+            ~~ Y1 U1 __ S1 I1 B1 __ ~~ ~~ ~~
+            ~~ E1 ~~ __ H1 O1 L1 D1 #1 __ ~~
+            ~~ ~~ E1 L1 __ __ A1 A1 ~~ ~~ ~~
+            ~~ ~~ S1 E1 R1 E1 |1 #1 ~~ ~~ ~~
+            ~~ ~~ T1 A1 ~~ ~~ ~~ ~~ ~~ ~~ ~~
+            ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~ ~~
+            "###,
+            "A",
+        );
+
+        let winning_move = Move::Place {
+            player: 1,
+            tile: 'A',
+            position: Coordinate { x: 2, y: 3 },
+        };
+        let params = NPCParams {
+            max_depth: 2,
+            ..NPCParams::default()
+        };
+
+        let winning = game
+            .analyse_move(&winning_move, &dict, &params)
+            .expect("Move should be analysable");
+        assert_eq!(winning.suggestion, None);
+        assert!(!winning.is_blunder());
+        assert_eq!(winning.eval_played, 1.0);
+
+        let idle_move = Move::Place {
+            player: 1,
+            tile: 'A',
+            position: Coordinate { x: 3, y: 7 },
+        };
+        let idle = game
+            .analyse_move(&idle_move, &dict, &params)
+            .expect("Move should be analysable");
+        assert_eq!(idle.suggestion, Some(winning_move));
+        assert!(idle.is_blunder());
+        assert!(!idle.reasons.is_empty());
+
+        let out_of_turn = Move::Place {
+            player: 0,
+            tile: 'A',
+            position: Coordinate { x: 3, y: 2 },
+        };
+        assert_eq!(game.analyse_move(&out_of_turn, &dict, &params), None);
+    }
 }
@@ -0,0 +1,167 @@
+use rayon::prelude::*;
+
+use crate::{board::Coordinate, game::Game, judge::WordDict, moves::Move};
+
+use super::{
+    scoring::{BoardScore, NPCParams},
+    Arborist, Caches,
+};
+
+struct Branch {
+    position: Coordinate,
+    tile: char,
+    score: BoardScore,
+    caches: Caches,
+}
+
+impl Game {
+    /// Root-split search: each move available at the root is explored on its own thread.
+    ///
+    /// The first (best ordered) move is searched on its own to establish a bound,
+    /// which the remaining moves are then searched against in parallel so that
+    /// they can still be pruned. Moves are considered in their original order,
+    /// so this selects the same move as the sequential search.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn minimax_parallel_root(
+        game: Game,
+        self_dictionary: Option<&WordDict>,
+        opponent_dictionary: Option<&WordDict>,
+        total_depth: usize,
+        depth: usize,
+        for_player: usize,
+        arborist: &mut Arborist,
+        caches: &mut Caches,
+        npc_params: &NPCParams,
+    ) -> (BoardScore, Option<(Coordinate, char)>) {
+        let possible_moves = game.ordered_moves(caches, 0);
+
+        let next_player = game.next_player.unwrap();
+        let is_players_turn = next_player == for_player;
+        let (attacker_dict, defender_dict) = if is_players_turn {
+            (self_dictionary, opponent_dictionary)
+        } else {
+            (opponent_dictionary, self_dictionary)
+        };
+
+        let shared_arborist = &*arborist;
+        let seeded_scores = &caches.cached_scores;
+
+        let search_branch =
+            |(position, tile): (Coordinate, char), alpha: BoardScore, beta: BoardScore| {
+                let mut branch_arborist = shared_arborist.branch();
+                branch_arborist.tick();
+                if branch_arborist.stop_here() {
+                    return None;
+                }
+
+                let mut branch_caches = Caches::new();
+                branch_caches.cached_scores = seeded_scores.clone();
+
+                let mut next_turn = game.clone();
+                next_turn
+                    .play_turn(
+                        Move::Place {
+                            player: next_player,
+                            tile,
+                            position,
+                        },
+                        attacker_dict,
+                        defender_dict,
+                        Some(&mut branch_caches.cached_words),
+                    )
+                    .expect("Should be exploring valid turns");
+
+                let (score, _) = Game::minimax(
+                    next_turn,
+                    self_dictionary,
+                    opponent_dictionary,
+                    total_depth,
+                    depth - 1,
+                    1,
+                    alpha,
+                    beta,
+                    for_player,
+                    &mut branch_arborist,
+                    &mut branch_caches,
+                    npc_params,
+                );
+
+                Some(Branch {
+                    position,
+                    tile,
+                    score,
+                    caches: branch_caches,
+                })
+            };
+
+        let mut best_score = if is_players_turn {
+            BoardScore::neg_inf()
+        } else {
+            BoardScore::inf()
+        };
+
+        let mut possible_moves = possible_moves.into_iter();
+        let Some(first_move) = possible_moves.next() else {
+            return (best_score, None);
+        };
+        let first_branch = search_branch(first_move, BoardScore::neg_inf(), BoardScore::inf());
+
+        // Every other move only needs to show whether it beats the first one
+        let (alpha, beta) = match &first_branch {
+            Some(first) if is_players_turn => (first.score.clone(), BoardScore::inf()),
+            Some(first) => (BoardScore::neg_inf(), first.score.clone()),
+            None => (BoardScore::neg_inf(), BoardScore::inf()),
+        };
+
+        // Collected in move order so that both tie-breaking and the
+        // merging of caches below stay deterministic.
+        let other_branches: Vec<_> = if first_branch.is_some() {
+            possible_moves
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .map(|next_move| search_branch(next_move, alpha.clone(), beta.clone()))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let mut relevant_move = None;
+
+        // As in the sequential search, once a move couldn't be searched
+        // the moves after it aren't considered either.
+        for branch in std::iter::once(first_branch).chain(other_branches) {
+            let Some(Branch {
+                position,
+                tile,
+                score,
+                caches: branch_caches,
+            }) = branch
+            else {
+                break;
+            };
+
+            caches.cached_scores.extend(branch_caches.cached_scores);
+            caches.cached_words.extend(branch_caches.cached_words);
+
+            if is_players_turn {
+                caches
+                    .cached_scores
+                    .insert((position, tile, 0), score.usize_rank());
+                if score > best_score {
+                    best_score = score;
+                    relevant_move = Some((position, tile));
+                }
+            } else {
+                caches
+                    .cached_scores
+                    .insert((position, tile, 0), usize::MAX - score.usize_rank());
+                if score < best_score {
+                    best_score = score;
+                    relevant_move = Some((position, tile));
+                }
+            }
+        }
+
+        (best_score, relevant_move)
+    }
+}
@@ -13,8 +13,20 @@ pub enum NPCVocab {
     Small,
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum NPCEngine {
+    /// Alpha-beta search over the possible moves, scored by `static_eval`
+    #[default]
+    Minimax,
+    /// Tree search guided by random playouts, better suited to hidden information.
+    /// `evaluation_cap` bounds the simulated turns, and `max_depth` bounds each playout.
+    MonteCarlo,
+}
+
 #[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct NPCParams {
+    #[serde(default)]
+    pub engine: NPCEngine,
     pub pruning: bool,
     pub evaluation_cap: usize,
     pub vocab: NPCVocab,
@@ -43,6 +55,7 @@ pub struct NPCPersonality {
 impl Default for NPCParams {
     fn default() -> Self {
         Self {
+            engine: NPCEngine::Minimax,
             pruning: true,
             evaluation_cap: 1000,
             max_depth: 1,
@@ -95,11 +108,24 @@ impl NPCPersonality {
         }
     }
 
+    pub fn quartz() -> Self {
+        Self {
+            name: "quartz".to_string(),
+            params: NPCParams {
+                engine: NPCEngine::MonteCarlo,
+                evaluation_cap: 6000,
+                max_depth: 4,
+                ..NPCParams::default()
+            },
+        }
+    }
+
     pub fn from_id(id: impl AsRef<str>) -> Option<Self> {
         match id.as_ref() {
             "opal" => Some(Self::opal()),
             "jet" => Some(Self::jet()),
             "mellite" => Some(Self::mellite()),
+            "quartz" => Some(Self::quartz()),
             _ => None,
         }
     }
@@ -236,6 +262,92 @@ impl BoardScore {
     pub fn usize_rank(&self) -> usize {
         (self.rank() * 100000.0) as usize
     }
+
+    /// The rank scaled between 0 and 1, with wins and losses at the extremes
+    pub fn win_likelihood(&self) -> f32 {
+        if self.self_win {
+            return 1.0;
+        }
+        if self.opponent_win {
+            return 0.0;
+        }
+
+        let params = &self.npc_params;
+        let total_weight = params.raced_defense
+            + params.raced_attack
+            + params.self_defense
+            + params.self_attack
+            + params.direct_defence
+            + params.direct_attack
+            + params.word_validity
+            + params.word_length
+            + params.word_extensibility;
+
+        if total_weight <= 0.0 {
+            return 0.5;
+        }
+
+        (self.rank() / total_weight).clamp(0.0, 1.0)
+    }
+}
+
+/// One of the weighted parts that make up a `BoardScore`'s rank
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreComponent {
+    RacedDefense,
+    RacedAttack,
+    SelfDefense,
+    SelfAttack,
+    DirectDefence,
+    DirectAttack,
+    WordValidity,
+    WordLength,
+    WordExtensibility,
+}
+
+impl BoardScore {
+    /// The weighted contribution of each component to this score's rank
+    pub fn components(&self) -> [(ScoreComponent, f32); 9] {
+        let params = &self.npc_params;
+        [
+            (
+                ScoreComponent::RacedDefense,
+                self.raced_defense * params.raced_defense,
+            ),
+            (
+                ScoreComponent::RacedAttack,
+                self.raced_attack * params.raced_attack,
+            ),
+            (
+                ScoreComponent::SelfDefense,
+                self.self_defense * params.self_defense,
+            ),
+            (
+                ScoreComponent::SelfAttack,
+                self.self_attack * params.self_attack,
+            ),
+            (
+                ScoreComponent::DirectDefence,
+                self.direct_defence * params.direct_defence,
+            ),
+            (
+                ScoreComponent::DirectAttack,
+                self.direct_attack * params.direct_attack,
+            ),
+            (
+                ScoreComponent::WordValidity,
+                self.word_quality.word_validity * params.word_validity,
+            ),
+            (
+                ScoreComponent::WordLength,
+                self.word_quality.word_length * params.word_length,
+            ),
+            (
+                ScoreComponent::WordExtensibility,
+                self.word_quality.word_extensibility * params.word_extensibility,
+            ),
+        ]
+    }
 }
 
 impl PartialOrd for BoardScore {
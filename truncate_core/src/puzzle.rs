@@ -0,0 +1,358 @@
+use crate::{
+    board::{Board, Direction, Square, SquareValidity},
+    game::Game,
+    npc::scoring::NPCPersonality,
+    player::Hand,
+    rules::GameRules,
+};
+
+const CODE_VERSION: &str = "1";
+pub const MAX_HAND_SIZE: usize = 12;
+const MAX_BOARD_SQUARES: usize = 40 * 40;
+
+/// A hand-built puzzle that can be shared as a compact code.
+///
+/// Codes take the form `1.{npc}.{human}.{moves}.{rules}.{seed}.{width}.{board}.{hand}.{hand}`,
+/// where the board is run-length encoded row by row using
+/// `W` water, `L` land, `O` obelisk, `T{player}` town, `A{player}` artifact,
+/// and a lowercase letter followed by its player for a placed tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomPuzzle {
+    pub board: Board,
+    /// The starting hand of each player, indexed by player number
+    pub hands: Vec<Vec<char>>,
+    pub npc: String,
+    pub human_player: usize,
+    /// The number of moves the human has to win within, if any
+    pub move_limit: Option<u32>,
+    pub rules_generation: u32,
+    /// Seeds the bag that hands are refilled from once tiles are played
+    pub tile_seed: u32,
+}
+
+fn pack_square(square: &Square) -> String {
+    match square {
+        Square::Water { .. } | Square::Fog {} => "W".to_string(),
+        Square::Land { .. } => "L".to_string(),
+        Square::Obelisk { .. } => "O".to_string(),
+        Square::Town { player, .. } => format!("T{player}"),
+        Square::Artifact { player, .. } => format!("A{player}"),
+        Square::Occupied { player, tile, .. } => {
+            format!("{}{player}", tile.to_ascii_lowercase())
+        }
+    }
+}
+
+fn pack_board(board: &Board) -> String {
+    let mut packed = String::new();
+    let mut squares = board.squares.iter().flatten().map(pack_square).peekable();
+
+    while let Some(token) = squares.next() {
+        let mut count = 1;
+        while squares.peek() == Some(&token) {
+            squares.next();
+            count += 1;
+        }
+        if count > 1 {
+            packed.push_str(&count.to_string());
+        }
+        packed.push_str(&token);
+    }
+
+    packed
+}
+
+fn unpack_board(width: usize, packed: &str) -> Result<Board, ()> {
+    let mut squares = vec![];
+    let mut chars = packed.chars().peekable();
+
+    while chars.peek().is_some() {
+        let mut count = String::new();
+        while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+            count.push(digit);
+        }
+        let count: usize = if count.is_empty() {
+            1
+        } else {
+            count.parse().map_err(|_| ())?
+        };
+
+        let token = chars.next().ok_or(())?;
+        // Puzzles are always between two players
+        let mut player = || -> Result<usize, ()> {
+            chars
+                .next()
+                .and_then(|c| c.to_digit(10))
+                .map(|p| p as usize)
+                .filter(|p| *p < 2)
+                .ok_or(())
+        };
+
+        let square = match token {
+            'W' => Square::water(),
+            'L' => Square::land(),
+            'O' => Square::obelisk(),
+            'T' => Square::town(player()?),
+            'A' => Square::artifact(player()?),
+            tile if tile.is_ascii_lowercase() => Square::Occupied {
+                player: player()?,
+                tile: tile.to_ascii_uppercase(),
+                validity: SquareValidity::Unknown,
+                foggy: false,
+            },
+            _ => return Err(()),
+        };
+
+        // Counts come from shared links, so are checked without risking an overflow
+        if count == 0 || count > MAX_BOARD_SQUARES - squares.len() {
+            return Err(());
+        }
+        squares.extend(vec![square; count]);
+    }
+
+    if width == 0 || squares.is_empty() || squares.len() % width != 0 {
+        return Err(());
+    }
+
+    let mut board = Board {
+        squares: squares.chunks(width).map(|row| row.to_vec()).collect(),
+        artifacts: vec![],
+        towns: vec![],
+        obelisks: vec![],
+        orientations: vec![Direction::North, Direction::South],
+    };
+    board.cache_special_squares();
+
+    Ok(board)
+}
+
+fn unpack_hand(packed: &str) -> Result<Vec<char>, ()> {
+    if packed.is_empty()
+        || packed.len() > MAX_HAND_SIZE
+        || !packed.chars().all(|c| c.is_ascii_uppercase())
+    {
+        return Err(());
+    }
+    Ok(packed.chars().collect())
+}
+
+impl CustomPuzzle {
+    pub fn to_code(&self) -> String {
+        let hands: Vec<String> = self.hands.iter().map(|h| h.iter().collect()).collect();
+
+        [
+            CODE_VERSION.to_string(),
+            self.npc.clone(),
+            self.human_player.to_string(),
+            self.move_limit.unwrap_or(0).to_string(),
+            self.rules_generation.to_string(),
+            self.tile_seed.to_string(),
+            self.board.width().to_string(),
+            pack_board(&self.board),
+        ]
+        .into_iter()
+        .chain(hands)
+        .collect::<Vec<_>>()
+        .join(".")
+    }
+
+    #[allow(clippy::result_unit_err)]
+    pub fn from_code(code: &str) -> Result<Self, ()> {
+        let parts: Vec<_> = code.trim().split('.').collect();
+        let [version, npc, human_player, move_limit, rules_generation, tile_seed, width, board, hand_a, hand_b] =
+            parts.as_slice()
+        else {
+            return Err(());
+        };
+
+        if *version != CODE_VERSION {
+            return Err(());
+        }
+
+        let npc = npc.to_ascii_lowercase();
+        if NPCPersonality::from_id(&npc).is_none() {
+            return Err(());
+        }
+
+        let human_player: usize = human_player.parse().map_err(|_| ())?;
+        if human_player > 1 {
+            return Err(());
+        }
+
+        let move_limit = match move_limit.parse::<u32>().map_err(|_| ())? {
+            0 => None,
+            limit => Some(limit),
+        };
+
+        let rules_generation: u32 = rules_generation.parse().map_err(|_| ())?;
+        if !GameRules::has_generation(rules_generation) {
+            return Err(());
+        }
+
+        let puzzle = Self {
+            board: unpack_board(width.parse().map_err(|_| ())?, board)?,
+            hands: vec![unpack_hand(hand_a)?, unpack_hand(hand_b)?],
+            npc,
+            human_player,
+            move_limit,
+            rules_generation,
+            tile_seed: tile_seed.parse().map_err(|_| ())?,
+        };
+
+        if puzzle.is_playable() {
+            Ok(puzzle)
+        } else {
+            Err(())
+        }
+    }
+
+    /// Whether both players have somewhere to play from and something to win against
+    pub fn is_playable(&self) -> bool {
+        let has = |player: usize, is_artifact: bool| {
+            self.board
+                .squares
+                .iter()
+                .flatten()
+                .any(|square| match square {
+                    Square::Artifact { player: p, .. } => is_artifact && *p == player,
+                    Square::Town { player: p, .. } => !is_artifact && *p == player,
+                    _ => false,
+                })
+        };
+
+        let owned_by_players = self
+            .board
+            .squares
+            .iter()
+            .flatten()
+            .all(|square| match square {
+                Square::Artifact { player, .. }
+                | Square::Town { player, .. }
+                | Square::Occupied { player, .. } => *player < 2,
+                _ => true,
+            });
+
+        self.hands.len() == 2
+            && self.hands.iter().all(|hand| !hand.is_empty())
+            && owned_by_players
+            && (0..2).all(|player| has(player, true) && has(player, false))
+    }
+
+    pub fn npc_personality(&self) -> NPCPersonality {
+        NPCPersonality::from_id(&self.npc).unwrap_or_else(NPCPersonality::jet)
+    }
+
+    /// Lays out the puzzle's board and hands on a game that has players but hasn't started
+    pub fn apply_to(&self, game: &mut Game) {
+        let mut board = self.board.clone();
+        board.cache_special_squares();
+        game.board = board;
+
+        for (player, hand) in game.players.iter_mut().zip(&self.hands) {
+            player.hand = Hand(hand.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle() -> CustomPuzzle {
+        let mut board = Board::from_string(
+            "~~ ~~ |0 ~~ ~~\n\
+             ~~ #0 __ __ ~~\n\
+             ~~ __ A0 __ ~~\n\
+             ~~ __ __ #1 ~~\n\
+             ~~ ~~ |1 ~~ ~~",
+        );
+        board.squares[2][3] = Square::obelisk();
+        board.cache_special_squares();
+
+        CustomPuzzle {
+            board,
+            hands: vec![vec!['A', 'B', 'C'], vec!['X', 'Y', 'Z']],
+            npc: "mellite".to_string(),
+            human_player: 1,
+            move_limit: Some(3),
+            rules_generation: 1,
+            tile_seed: 12345,
+        }
+    }
+
+    #[test]
+    fn puzzle_codes_round_trip() {
+        let puzzle = puzzle();
+        let code = puzzle.to_code();
+
+        assert_eq!(
+            code,
+            "1.mellite.1.3.1.12345.5.2WA03WT02L2WLa0O2W2LT13WA12W.ABC.XYZ"
+        );
+        assert_eq!(CustomPuzzle::from_code(&code), Ok(puzzle));
+    }
+
+    #[test]
+    fn puzzle_codes_without_a_limit_round_trip() {
+        let mut puzzle = puzzle();
+        puzzle.move_limit = None;
+        puzzle.npc = "jet".to_string();
+
+        assert_eq!(CustomPuzzle::from_code(&puzzle.to_code()), Ok(puzzle));
+    }
+
+    #[test]
+    fn malformed_puzzle_codes_are_rejected() {
+        let code = puzzle().to_code();
+
+        // Unknown NPC
+        assert!(CustomPuzzle::from_code(&code.replace("mellite", "granite")).is_err());
+        // Board doesn't divide into rows
+        assert!(CustomPuzzle::from_code(&code.replace(".5.", ".4.")).is_err());
+        // Tile without a player
+        assert!(CustomPuzzle::from_code(&code.replace("La0O", "LaO")).is_err());
+        // Tile or town belonging to a third player
+        assert!(CustomPuzzle::from_code(&code.replace("La0O", "La5O")).is_err());
+        assert!(CustomPuzzle::from_code(&code.replace("LT13W", "LT23W")).is_err());
+        // Empty hand
+        assert!(CustomPuzzle::from_code(&code.replace(".XYZ", ".")).is_err());
+        // Player with no artifact to defend
+        assert!(CustomPuzzle::from_code(&code.replace("WA12W", "WL2W")).is_err());
+        // Missing sections
+        assert!(CustomPuzzle::from_code("1.jet.0").is_err());
+        // Runs of squares too long to fit on any board
+        assert!(CustomPuzzle::from_code("1.jet.0.0.1.1.1.L18446744073709551615W.AB.CD").is_err());
+    }
+
+    #[test]
+    fn puzzles_with_extra_players_are_unplayable() {
+        let mut puzzle = puzzle();
+        assert!(puzzle.is_playable());
+
+        puzzle.board.squares[2][2] = Square::Occupied {
+            player: 5,
+            tile: 'A',
+            validity: SquareValidity::Unknown,
+            foggy: false,
+        };
+        assert!(!puzzle.is_playable());
+    }
+
+    #[test]
+    fn puzzles_apply_to_games() {
+        let puzzle = puzzle();
+        let mut game = Game::new(
+            3,
+            3,
+            Some(puzzle.tile_seed as u64),
+            GameRules::generation(1),
+        );
+        game.add_player("A".into());
+        game.add_player("B".into());
+        puzzle.apply_to(&mut game);
+
+        assert_eq!(game.board.artifacts.len(), 2);
+        assert_eq!(game.board.towns.len(), 2);
+        assert_eq!(game.players[1].hand, Hand(vec!['X', 'Y', 'Z']));
+    }
+}
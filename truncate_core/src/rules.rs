@@ -7,6 +7,7 @@ use crate::{
     generation::{
         ArtifactType, BoardElements, BoardNoiseParams, BoardParams, BoardSeed, Symmetry, WaterLayer,
     },
+    messages::ServerError,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -226,6 +227,10 @@ impl GameRules {
         rules
     }
 
+    pub fn has_generation(gen: u32) -> bool {
+        (gen as usize) < RULE_GENERATIONS.len()
+    }
+
     pub fn latest(effective_date: Option<u32>) -> (u32, Self) {
         RULE_GENERATIONS
             .iter()
@@ -296,3 +301,153 @@ impl GameRules {
         }
     }
 }
+
+/// Clocks that a lobby host can choose, each giving players a fixed allowance for the whole game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimingPreset {
+    #[default]
+    Untimed,
+    /// Five minutes each, losing the game when time runs out
+    Blitz,
+    /// Fifteen minutes each, after which the opponent is handed bombs
+    Rapid,
+    /// An hour each, losing the game when time runs out
+    Classical,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VisibilityPreset {
+    #[default]
+    Standard,
+    TileFog,
+    LandFog,
+    OnlyHouseFog,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwappingPreset {
+    #[default]
+    Contiguous,
+    Universal,
+    Disabled,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TruncationPreset {
+    #[default]
+    Root,
+    Disabled,
+}
+
+/// How a winner is chosen if nobody has won by the turn limit.
+/// Picking any metric other than `Unlimited` also introduces a turn limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WinMetricPreset {
+    #[default]
+    Unlimited,
+    TownProximity,
+    /// Only valid on boards with exactly one obelisk
+    ObeliskProximity,
+}
+
+/// Variations on the standard rules that a lobby host can pick for their game.
+/// Each option is a preset, so any combination of them forms a playable rule set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleSettings {
+    pub timing: TimingPreset,
+    pub visibility: VisibilityPreset,
+    pub swapping: SwappingPreset,
+    pub truncation: TruncationPreset,
+    pub win_metric: WinMetricPreset,
+}
+
+const SETTINGS_TURN_LIMIT: u64 = 100;
+
+impl RuleSettings {
+    /// Checks that these settings can be played on the given board
+    pub fn validate(&self, board: &Board) -> Result<(), ServerError> {
+        if self.win_metric == WinMetricPreset::ObeliskProximity {
+            let mut board = board.clone();
+            board.cache_special_squares();
+            if board.obelisks.len() != 1 {
+                return Err(ServerError::ObeliskRequired);
+            }
+        }
+        Ok(())
+    }
+
+    /// Layers these settings over a base rule set, leaving anything they don't cover untouched
+    pub fn apply(&self, rules: &mut GameRules) {
+        rules.timing = match self.timing {
+            TimingPreset::Untimed => Timing::None,
+            TimingPreset::Blitz => Timing::PerPlayer {
+                time_allowance: 5 * 60,
+                overtime_rule: OvertimeRule::Elimination,
+            },
+            TimingPreset::Rapid => Timing::PerPlayer {
+                time_allowance: 15 * 60,
+                overtime_rule: OvertimeRule::Bomb { period: 30 },
+            },
+            TimingPreset::Classical => Timing::PerPlayer {
+                time_allowance: 60 * 60,
+                overtime_rule: OvertimeRule::Elimination,
+            },
+        };
+
+        rules.visibility = match self.visibility {
+            VisibilityPreset::Standard => Visibility::Standard,
+            VisibilityPreset::TileFog => Visibility::TileFog,
+            VisibilityPreset::LandFog => Visibility::LandFog,
+            VisibilityPreset::OnlyHouseFog => Visibility::OnlyHouseFog,
+        };
+
+        // Customised swapping keeps whatever penalty the base rules use
+        let penalty = match &rules.swapping {
+            Swapping::Contiguous(penalty) | Swapping::Universal(penalty) => penalty.clone(),
+            Swapping::None => SwapPenalty::Disallowed { allowed_swaps: 1 },
+        };
+        rules.swapping = match self.swapping {
+            SwappingPreset::Contiguous => Swapping::Contiguous(penalty),
+            SwappingPreset::Universal => Swapping::Universal(penalty),
+            SwappingPreset::Disabled => Swapping::None,
+        };
+
+        rules.truncation = match self.truncation {
+            TruncationPreset::Root => Truncation::Root,
+            TruncationPreset::Disabled => Truncation::None,
+        };
+
+        (rules.win_metric, rules.max_turns) = match self.win_metric {
+            WinMetricPreset::Unlimited => (WinMetric::TownProximity, None),
+            WinMetricPreset::TownProximity => (WinMetric::TownProximity, Some(SETTINGS_TURN_LIMIT)),
+            WinMetricPreset::ObeliskProximity => {
+                (WinMetric::ObeliskProximity, Some(SETTINGS_TURN_LIMIT))
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_settings_match_latest_rules() {
+        let (_, latest) = GameRules::latest(None);
+        let mut customised = latest.clone();
+        RuleSettings::default().apply(&mut customised);
+
+        assert_eq!(format!("{latest:?}"), format!("{customised:?}"));
+    }
+
+    #[test]
+    fn obelisk_metric_needs_an_obelisk() {
+        let settings = RuleSettings {
+            win_metric: WinMetricPreset::ObeliskProximity,
+            ..Default::default()
+        };
+
+        assert!(settings.validate(&Board::new(9, 9)).is_err());
+        assert!(RuleSettings::default().validate(&Board::new(9, 9)).is_ok());
+    }
+}
@@ -10,9 +10,9 @@ pub struct Dicts {
 }
 
 impl Dicts {
-    pub fn remember(&mut self, word: &String) {
+    pub fn remember(&mut self, word: &str) {
         if let Some(word_data) = self.total.get(word).cloned() {
-            self.restricted.insert(word.clone(), word_data.clone());
+            self.restricted.insert(word.to_string(), word_data.clone());
         }
     }
 }
@@ -38,8 +38,8 @@ pub fn ensure_dicts() {
     let mut restricted_dict = RESTRICTED_DICT.lock().unwrap();
 
     if total_dict.is_none() {
-        let mut valid_words = std::collections::HashMap::new();
-        let mut restricted_words = std::collections::HashMap::new();
+        let mut valid_words = WordDict::new();
+        let mut restricted_words = WordDict::new();
         let lines = TRUNCATE_DICT.lines();
 
         for line in lines {
@@ -0,0 +1,126 @@
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use truncate_core::{
+    generation::BoardSeed,
+    messages::PlayerMessage,
+    moves::Move,
+    npc::scoring::NPCPersonality,
+    rules::{GameRules, Visibility},
+};
+
+use crate::{best_move, dicts::get_dicts, get_game_for_seed};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuelResult {
+    Won(usize),
+    Drawn,
+}
+
+/// Plays two NPCs against one another on the given seed.
+/// Each NPC only sees the board as filtered to them, so fog rules apply.
+pub fn play_duel(
+    seed: BoardSeed,
+    npcs: [&NPCPersonality; 2],
+    rules_generation: u32,
+    visibility: Option<Visibility>,
+) -> DuelResult {
+    let maximum_turns = 200;
+
+    let mut game = get_game_for_seed(seed, rules_generation);
+    if let Some(visibility) = visibility {
+        game.rules.visibility = visibility;
+    }
+    let mut dicts = get_dicts();
+
+    while game.turn_count < maximum_turns {
+        let next_player = game.next_player.unwrap();
+
+        let (filtered_board, _) = game.filter_game_to_player(next_player);
+        let mut evaluation_game = game.clone();
+        evaluation_game.board = filtered_board;
+
+        let next_move = match best_move(&evaluation_game, &npcs[next_player].params, &dicts) {
+            PlayerMessage::Place(position, tile) => Move::Place {
+                player: next_player,
+                tile,
+                position,
+            },
+            PlayerMessage::Swap(from, to) => Move::Swap {
+                player: next_player,
+                positions: [from, to],
+            },
+            _ => unreachable!(),
+        };
+
+        match game.play_turn(next_move, Some(&dicts.total), Some(&dicts.total), None) {
+            Ok(Some(winner)) => return DuelResult::Won(winner),
+            Ok(None) => {
+                for battle in game
+                    .recent_changes
+                    .iter()
+                    .filter_map(|change| match change {
+                        truncate_core::reporting::Change::Battle(battle) => Some(battle),
+                        _ => None,
+                    })
+                {
+                    for word in battle.attackers.iter().chain(battle.defenders.iter()) {
+                        if word.valid == Some(true) {
+                            dicts.remember(&word.original_word.to_lowercase());
+                        }
+                    }
+                }
+            }
+            Err(e) => panic!("Errored in duel on {}:\n{e}", game.board),
+        }
+    }
+
+    DuelResult::Drawn
+}
+
+/// Plays `games` duels between two NPCs, alternating who goes first,
+/// and reports how often each won.
+pub fn duel(npc_a: NPCPersonality, npc_b: NPCPersonality, games: u32, fog: bool) {
+    let rules_generation = GameRules::latest(None).0;
+    let visibility = fog.then_some(Visibility::LandFog);
+
+    println!(
+        "Duelling {} against {} over {games} games{}",
+        npc_a.name,
+        npc_b.name,
+        if fog { " with fog" } else { "" }
+    );
+
+    let results: Vec<_> = (0..games)
+        .into_par_iter()
+        .map(|game_number| {
+            let seed = BoardSeed::new(game_number);
+            // Swap sides every other game so neither NPC always moves first
+            let a_player = (game_number % 2) as usize;
+            let npcs = if a_player == 0 {
+                [&npc_a, &npc_b]
+            } else {
+                [&npc_b, &npc_a]
+            };
+
+            let result = play_duel(seed, npcs, rules_generation, visibility.clone());
+            println!(
+                "Game {game_number}: {result:?} ({} was player {a_player})",
+                npc_a.name
+            );
+
+            match result {
+                DuelResult::Won(winner) if winner == a_player => Some(true),
+                DuelResult::Won(_) => Some(false),
+                DuelResult::Drawn => None,
+            }
+        })
+        .collect();
+
+    let a_wins = results.iter().filter(|r| **r == Some(true)).count();
+    let b_wins = results.iter().filter(|r| **r == Some(false)).count();
+    let draws = results.iter().filter(|r| r.is_none()).count();
+
+    println!(
+        "{}: {a_wins} wins, {}: {b_wins} wins, {draws} draws",
+        npc_a.name, npc_b.name
+    );
+}
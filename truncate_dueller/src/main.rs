@@ -13,13 +13,16 @@ use truncate_core::{
 use crate::dicts::ensure_dicts;
 
 mod dicts;
+mod duel;
 mod storage;
+mod tuning;
 
 fn best_move(game: &Game, npc_params: &NPCParams, dicts: &Dicts) -> PlayerMessage {
     ensure_dicts();
 
     let mut arb = truncate_core::npc::Arborist::pruning();
     arb.capped(npc_params.evaluation_cap);
+    arb.parallel();
     let search_depth = npc_params.max_depth;
 
     let (best_move, _score) = truncate_core::game::Game::best_move(
@@ -199,6 +202,47 @@ fn verify_note(seed: &u32, note: &SeedNote) -> bool {
 fn main() {
     let quantity = 30;
 
+    let args = std::env::args().collect::<Vec<_>>();
+
+    // e.g. `truncate_dueller duel quartz jet 50 fog`
+    if args.get(1).map(String::as_str) == Some("duel") {
+        let npc = |arg: Option<&String>| {
+            let id = arg.expect("Duel requires two NPC names");
+            NPCPersonality::from_id(id).unwrap_or_else(|| panic!("No NPC named {id}"))
+        };
+        let npc_a = npc(args.get(2));
+        let npc_b = npc(args.get(3));
+        let games = args
+            .get(4)
+            .map(|g| g.parse().expect("Game count should be a number"))
+            .unwrap_or(20);
+        let fog = args.get(5).map(String::as_str) == Some("fog");
+
+        ensure_dicts();
+        duel::duel(npc_a, npc_b, games, fog);
+        return;
+    }
+
+    // e.g. `truncate_dueller tune jet 30 10`
+    if args.get(1).map(String::as_str) == Some("tune") {
+        let baseline = args
+            .get(2)
+            .map(|id| NPCPersonality::from_id(id).unwrap_or_else(|| panic!("No NPC named {id}")))
+            .unwrap_or_else(NPCPersonality::jet);
+        let iterations = args
+            .get(3)
+            .map(|i| i.parse().expect("Iterations should be a number"))
+            .unwrap_or(30);
+        let seeds = args
+            .get(4)
+            .map(|s| s.parse().expect("Seed count should be a number"))
+            .unwrap_or(10);
+
+        ensure_dicts();
+        tuning::tune(baseline, iterations, seeds);
+        return;
+    }
+
     let mut current_notes = load_file();
     ensure_dicts();
 
@@ -212,8 +256,6 @@ fn main() {
         panic!("One or more seeds failed to verify");
     }
 
-    let args = std::env::args().collect::<Vec<_>>();
-
     if let Some(seed) = args.get(1) {
         let day = seed.parse().expect("Seed should be a number");
         let seed = BoardSeed::new(day);
@@ -0,0 +1,298 @@
+use std::path::PathBuf;
+
+use oorandom::Rand32;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use truncate_core::{
+    generation::BoardSeed,
+    npc::scoring::{NPCParams, NPCPersonality},
+    rules::GameRules,
+};
+
+use crate::duel::{play_duel, DuelResult};
+
+fn tuning_file() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tuned_params.yml")
+}
+
+/// The number of weights in `NPCParams` that are tuned
+const WEIGHT_COUNT: usize = 9;
+
+fn weights(params: &NPCParams) -> [f32; WEIGHT_COUNT] {
+    [
+        params.raced_defense,
+        params.raced_attack,
+        params.self_defense,
+        params.self_attack,
+        params.direct_defence,
+        params.direct_attack,
+        params.word_validity,
+        params.word_length,
+        params.word_extensibility,
+    ]
+}
+
+fn with_weights(params: &NPCParams, weights: [f32; WEIGHT_COUNT]) -> NPCParams {
+    // Negative weights would reward the NPC for doing worse on a metric
+    let [raced_defense, raced_attack, self_defense, self_attack, direct_defence, direct_attack, word_validity, word_length, word_extensibility] =
+        weights.map(|w| w.max(0.0));
+
+    NPCParams {
+        raced_defense,
+        raced_attack,
+        self_defense,
+        self_attack,
+        direct_defence,
+        direct_attack,
+        word_validity,
+        word_length,
+        word_extensibility,
+        ..*params
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MatchReport {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+impl MatchReport {
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    /// Draws count as half a win
+    pub fn win_rate(&self) -> f32 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f32 + self.draws as f32 / 2.0) / self.games() as f32
+    }
+
+    /// 95% Wilson score interval around the win rate
+    pub fn confidence_interval(&self) -> (f32, f32) {
+        let n = self.games() as f32;
+        if n == 0.0 {
+            return (0.0, 1.0);
+        }
+        let z = 1.96_f32;
+        let p = self.win_rate();
+
+        let denominator = 1.0 + z * z / n;
+        let centre = (p + z * z / (2.0 * n)) / denominator;
+        let margin = z * ((p * (1.0 - p) / n) + (z * z / (4.0 * n * n))).sqrt() / denominator;
+
+        ((centre - margin).max(0.0), (centre + margin).min(1.0))
+    }
+}
+
+impl std::fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (low, high) = self.confidence_interval();
+        write!(
+            f,
+            "{}W {}L {}D, win rate {:.1}% (95% CI {:.1}% to {:.1}%)",
+            self.wins,
+            self.losses,
+            self.draws,
+            self.win_rate() * 100.0,
+            low * 100.0,
+            high * 100.0
+        )
+    }
+}
+
+/// Plays the candidate against the baseline once per seed from each side of the board.
+pub fn evaluate_params(
+    candidate: &NPCParams,
+    baseline: &NPCParams,
+    seeds: std::ops::Range<u32>,
+    rules_generation: u32,
+) -> MatchReport {
+    let candidate = NPCPersonality {
+        name: "candidate".to_string(),
+        params: *candidate,
+    };
+    let baseline = NPCPersonality {
+        name: "baseline".to_string(),
+        params: *baseline,
+    };
+
+    let results: Vec<_> = seeds
+        .flat_map(|seed| [(seed, 0), (seed, 1)])
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(seed, candidate_player)| {
+            let npcs = if candidate_player == 0 {
+                [&candidate, &baseline]
+            } else {
+                [&baseline, &candidate]
+            };
+            (
+                candidate_player,
+                play_duel(BoardSeed::new(seed), npcs, rules_generation, None),
+            )
+        })
+        .collect();
+
+    let mut report = MatchReport::default();
+    for (candidate_player, result) in results {
+        match result {
+            DuelResult::Won(winner) if winner == candidate_player => report.wins += 1,
+            DuelResult::Won(_) => report.losses += 1,
+            DuelResult::Drawn => report.draws += 1,
+        }
+    }
+    report
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TuningResult {
+    pub iterations: usize,
+    pub games_per_evaluation: usize,
+    pub baseline: NPCParams,
+    pub tuned: NPCParams,
+    pub report: MatchReport,
+}
+
+pub fn write_tuning_file(result: &TuningResult) {
+    let output_content = serde_yaml::to_string(result).unwrap();
+    std::fs::write(tuning_file(), output_content).expect("Writing tuned params should succeed");
+}
+
+/// Searches for weights that beat the baseline using SPSA
+/// (simultaneous perturbation stochastic approximation).
+///
+/// Each iteration nudges every weight up or down at random, plays both
+/// perturbed sets against the baseline, and steps towards whichever did better.
+/// Every evaluation uses fresh seeds so the weights don't overfit to specific boards.
+pub fn tune(baseline: NPCPersonality, iterations: usize, seeds_per_evaluation: u32) {
+    let rules_generation = GameRules::latest(None).0;
+    let mut rng = Rand32::new(0);
+
+    let baseline = baseline.params;
+    let mut theta = weights(&baseline);
+    let mut next_seed = 0;
+    let mut seed_range = |count: u32| {
+        let range = next_seed..next_seed + count;
+        next_seed += count;
+        range
+    };
+
+    // Standard SPSA gain sequences
+    let (a, c, big_a, alpha, gamma) = (2.0_f32, 0.5_f32, iterations as f32 / 10.0, 0.602, 0.101);
+
+    for k in 0..iterations {
+        let a_k = a / (k as f32 + 1.0 + big_a).powf(alpha);
+        let c_k = c / (k as f32 + 1.0).powf(gamma);
+
+        let delta: [f32; WEIGHT_COUNT] =
+            std::array::from_fn(|_| if rng.rand_range(0..2) == 0 { 1.0 } else { -1.0 });
+
+        let plus = with_weights(
+            &baseline,
+            std::array::from_fn(|i| theta[i] + c_k * delta[i]),
+        );
+        let minus = with_weights(
+            &baseline,
+            std::array::from_fn(|i| theta[i] - c_k * delta[i]),
+        );
+
+        let seeds = seed_range(seeds_per_evaluation);
+        let plus_report = evaluate_params(&plus, &baseline, seeds.clone(), rules_generation);
+        let minus_report = evaluate_params(&minus, &baseline, seeds, rules_generation);
+
+        let difference = plus_report.win_rate() - minus_report.win_rate();
+        for i in 0..WEIGHT_COUNT {
+            theta[i] = (theta[i] + a_k * difference / (2.0 * c_k * delta[i])).max(0.0);
+        }
+
+        println!("Iteration {k}:\n  + {plus_report}\n  - {minus_report}\n  weights: {theta:?}");
+    }
+
+    let tuned = with_weights(&baseline, theta);
+    let report = evaluate_params(
+        &tuned,
+        &baseline,
+        seed_range(seeds_per_evaluation),
+        rules_generation,
+    );
+    println!("Tuned weights against the baseline: {report}");
+
+    write_tuning_file(&TuningResult {
+        iterations,
+        games_per_evaluation: seeds_per_evaluation as usize * 2,
+        baseline,
+        tuned,
+        report,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(wins: usize, losses: usize, draws: usize) -> MatchReport {
+        MatchReport {
+            wins,
+            losses,
+            draws,
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 0.0005,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn win_rates() {
+        assert_eq!(report(0, 0, 0).games(), 0);
+        assert_close(report(0, 0, 0).win_rate(), 0.5);
+        assert_close(report(3, 1, 0).win_rate(), 0.75);
+        // Draws are half a win
+        assert_close(report(1, 1, 2).win_rate(), 0.5);
+        assert_close(report(0, 3, 1).win_rate(), 0.125);
+    }
+
+    #[test]
+    fn wilson_intervals() {
+        assert_eq!(report(0, 0, 0).confidence_interval(), (0.0, 1.0));
+
+        let (low, high) = report(50, 50, 0).confidence_interval();
+        assert_close(low, 0.4038);
+        assert_close(high, 0.5962);
+
+        // Unlike a normal approximation, a perfect record still has a lower bound below 100%
+        let (low, high) = report(10, 0, 0).confidence_interval();
+        assert_close(low, 0.7225);
+        assert_close(high, 1.0);
+
+        let (low, high) = report(0, 10, 0).confidence_interval();
+        assert_close(low, 0.0);
+        assert_close(high, 0.2775);
+
+        // More games narrow the interval
+        let (few_low, few_high) = report(6, 4, 0).confidence_interval();
+        let (many_low, many_high) = report(600, 400, 0).confidence_interval();
+        assert!(many_high - many_low < few_high - few_low);
+    }
+
+    #[test]
+    fn weights_round_trip() {
+        let params = NPCParams::default();
+        let mut tuned = weights(&params);
+        tuned[0] = -1.0;
+        tuned[1] = 2.5;
+
+        let adjusted = with_weights(&params, tuned);
+        assert_eq!(adjusted.raced_defense, 0.0);
+        assert_eq!(adjusted.raced_attack, 2.5);
+        assert_eq!(weights(&adjusted)[2..], weights(&params)[2..]);
+        assert_eq!(adjusted.max_depth, params.max_depth);
+    }
+}
@@ -0,0 +1,13 @@
+-- Live Games Table
+CREATE TABLE live_games (
+    room_code VARCHAR(255) PRIMARY KEY,
+    -- Room codes are recycled, so each game gets its own ID to keep old writes from clobbering new games
+    instance_id UUID NOT NULL,
+    snapshot_version BIGINT NOT NULL,
+    effective_day INT NOT NULL,
+    player_count INT NOT NULL,
+    game_state JSONB NOT NULL,
+    sequence_of_moves TEXT NOT NULL DEFAULT '',
+    finished BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
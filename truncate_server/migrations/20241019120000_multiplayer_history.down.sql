@@ -0,0 +1,10 @@
+DROP TABLE IF EXISTS multiplayer_game_players;
+DROP TABLE IF EXISTS multiplayer_games;
+
+ALTER TABLE live_games
+    DROP COLUMN player_ids,
+    DROP COLUMN starting_game;
+
+ALTER TABLE players
+    DROP COLUMN rating,
+    DROP COLUMN rated_games;
@@ -0,0 +1,39 @@
+-- Ratings, which only move in games between two logged-in players
+ALTER TABLE players
+    ADD COLUMN rating INT NOT NULL DEFAULT 1500,
+    ADD COLUMN rated_games INT NOT NULL DEFAULT 0;
+
+-- Live games need to remember who is playing and how they started, to record them once finished
+ALTER TABLE live_games
+    ADD COLUMN player_ids JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN starting_game JSONB;
+
+-- Multiplayer Games Table
+CREATE TABLE multiplayer_games (
+    -- Matches the instance ID of the live game, so each game is only recorded once
+    game_id UUID PRIMARY KEY,
+    room_code VARCHAR(255) NOT NULL,
+    rules_generation INT,
+    player_count INT NOT NULL,
+    -- The game just before it started, holding the board, tile bag and players
+    starting_game JSONB NOT NULL,
+    sequence_of_moves TEXT NOT NULL DEFAULT '',
+    winner INT NOT NULL,
+    rated BOOLEAN NOT NULL DEFAULT false,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    finished_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Multiplayer Game Players Table
+CREATE TABLE multiplayer_game_players (
+    game_id UUID REFERENCES multiplayer_games(game_id),
+    player_number INT NOT NULL,
+    -- Anonymous connections that never logged in have no player
+    player_id UUID REFERENCES players(player_id),
+    player_name VARCHAR(255) NOT NULL,
+    rating_before INT,
+    rating_after INT,
+    PRIMARY KEY (game_id, player_number)
+);
+
+CREATE INDEX multiplayer_game_players_by_player ON multiplayer_game_players(player_id);
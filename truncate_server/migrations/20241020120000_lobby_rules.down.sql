@@ -0,0 +1,2 @@
+ALTER TABLE live_games
+    DROP COLUMN rule_settings;
@@ -0,0 +1,3 @@
+-- Rule customisations picked in the lobby, applied once the game starts
+ALTER TABLE live_games
+    ADD COLUMN rule_settings JSONB;
@@ -0,0 +1,4 @@
+DROP TABLE login_links;
+
+DROP INDEX players_player_email_lower_idx;
+DROP INDEX players_player_name_lower_idx;
@@ -0,0 +1,12 @@
+-- Names and emails are unique regardless of case
+CREATE UNIQUE INDEX players_player_name_lower_idx ON players (LOWER(player_name));
+CREATE UNIQUE INDEX players_player_email_lower_idx ON players (LOWER(player_email));
+
+-- Magic links emailed to players, each of which can only be redeemed once
+CREATE TABLE login_links (
+    link_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    requested_by UUID NOT NULL REFERENCES players(player_id),
+    player_email VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    redeemed_at TIMESTAMP WITH TIME ZONE
+);
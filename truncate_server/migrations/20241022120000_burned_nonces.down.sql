@@ -0,0 +1 @@
+DROP TABLE burned_nonces;
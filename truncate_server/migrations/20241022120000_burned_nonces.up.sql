@@ -0,0 +1,9 @@
+-- Nonces of messages that have already been handled, shared between server instances
+CREATE TABLE burned_nonces (
+    player_id UUID NOT NULL REFERENCES players(player_id),
+    generated_at BIGINT NOT NULL,
+    nonce_id BIGINT NOT NULL,
+    PRIMARY KEY (player_id, generated_at, nonce_id)
+);
+
+CREATE INDEX burned_nonces_generated_at_idx ON burned_nonces (generated_at);
@@ -0,0 +1,3 @@
+ALTER TABLE daily_puzzle_attempts
+    DROP COLUMN hints_used,
+    DROP COLUMN takebacks_used;
@@ -0,0 +1,4 @@
+-- Help the player asked for during a daily puzzle attempt
+ALTER TABLE daily_puzzle_attempts
+    ADD COLUMN hints_used INT NOT NULL DEFAULT 0,
+    ADD COLUMN takebacks_used INT NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- The language of each game, which decides the dictionary its words are judged against
+ALTER TABLE live_games
+    ADD COLUMN locale VARCHAR(16) NOT NULL DEFAULT 'en';
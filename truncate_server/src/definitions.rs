@@ -1,14 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use rand::seq::SliceRandom;
 use rusqlite::Connection;
 use truncate_core::{
     judge::{WordData, WordDict},
+    locale::Locale,
     reporting::WordMeaning,
 };
 
 pub static TRUNCATE_DICT: &str = include_str!("../../dict_builder/final_wordlist.txt");
 
+/// The list of valid words for games played in a given language
+fn word_list(locale: Locale) -> &'static str {
+    match locale {
+        Locale::English => TRUNCATE_DICT,
+    }
+}
+
 pub struct WordDB {
     pub conn: Option<Connection>,
     pub valid_words: WordDict,
@@ -43,7 +51,6 @@ impl WordDB {
             .expect("No words in dataset")
     }
 
-    // TODO: Reclaim codes after use
     pub fn get_free_code(&mut self) -> String {
         let mut word = self.rand_code();
         while self.allocated_room_codes.get(&word).is_some() {
@@ -52,15 +59,20 @@ impl WordDB {
         self.allocated_room_codes.insert(word.clone());
         word
     }
+
+    /// Makes a room code available for new games once its game has been expired
+    pub fn release_code(&mut self, code: &str) {
+        self.allocated_room_codes.remove(code);
+    }
 }
 
-pub fn read_defs() -> WordDB {
-    println!("Loading word definitions...");
+pub fn read_defs(locale: Locale) -> WordDB {
+    println!("Loading word definitions for {}...", locale.tag());
 
     let defs_file = option_env!("TR_DEFS_FILE").unwrap_or_else(|| "/truncate/defs.db");
 
-    let mut valid_words = HashMap::new();
-    let lines = TRUNCATE_DICT.lines();
+    let mut valid_words = WordDict::new();
+    let lines = word_list(locale).lines();
 
     for line in lines {
         let mut chunks = line.split(' ');
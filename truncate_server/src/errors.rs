@@ -1,10 +1,15 @@
 use thiserror::Error;
+use truncate_core::messages::ServerError;
 use uuid::Uuid;
 
 #[derive(Error, Debug)]
 pub enum TruncateServerError {
     #[error(transparent)]
     DatabaseError(#[from] sqlx::Error),
+    #[error(transparent)]
+    SerializationError(#[from] serde_json::Error),
+    #[error(transparent)]
+    MailError(#[from] std::io::Error),
     #[error("no sqlx connection exists")]
     DatabaseOffline,
     #[error("no user exists for {0}")]
@@ -15,4 +20,53 @@ pub enum TruncateServerError {
     PuzzleComplete,
     #[error("something about this request was malformed")]
     BadRequest,
+    #[error("names must be 3 to 20 letters, numbers, dashes or underscores")]
+    InvalidName,
+    #[error("that name is already taken")]
+    NameTaken,
+    #[error("that email address doesn't look right")]
+    InvalidEmail,
+    #[error("that login link has expired or was already used")]
+    InvalidLoginLink,
+    #[error("too many login links have been requested, try again later")]
+    TooManyLoginLinks,
+    #[error("no connection exists for {0}")]
+    PlayerDisconnected(std::net::SocketAddr),
+    #[error(transparent)]
+    PlayerError(#[from] ServerError),
+}
+
+/// Why a player's message was rejected as already handled
+#[derive(Error, Debug)]
+pub enum NonceError {
+    #[error("nonce is more than an hour old")]
+    Expired,
+    #[error("nonce has already been used")]
+    Replayed,
+}
+
+impl From<TruncateServerError> for ServerError {
+    fn from(error: TruncateServerError) -> Self {
+        match error {
+            TruncateServerError::DatabaseOffline => ServerError::DatabaseOffline,
+            TruncateServerError::InvalidUser(_) | TruncateServerError::InvalidToken => {
+                ServerError::InvalidToken
+            }
+            TruncateServerError::PuzzleComplete => ServerError::PuzzleComplete,
+            TruncateServerError::BadRequest => ServerError::BadRequest,
+            TruncateServerError::InvalidName => ServerError::InvalidName,
+            TruncateServerError::NameTaken => ServerError::NameTaken,
+            TruncateServerError::InvalidEmail => ServerError::InvalidEmail,
+            TruncateServerError::InvalidLoginLink => ServerError::InvalidLoginLink,
+            TruncateServerError::TooManyLoginLinks => ServerError::TooManyLoginLinks,
+            TruncateServerError::PlayerError(e) => e,
+            TruncateServerError::DatabaseError(_)
+            | TruncateServerError::SerializationError(_)
+            | TruncateServerError::MailError(_)
+            | TruncateServerError::PlayerDisconnected(_) => {
+                eprintln!("Internal error: {error}");
+                ServerError::Internal
+            }
+        }
+    }
 }
@@ -4,44 +4,184 @@ use serde::{Deserialize, Serialize};
 use std::{net::SocketAddr, sync::Arc};
 use truncate_core::{
     board::{Board, Coordinate},
-    game::Game,
-    generation::{ArtifactType, BoardParams},
-    messages::{GameMessage, GamePlayerMessage, GameStateMessage, LobbyPlayerMessage},
+    game::{now, Game},
+    locale::Locale,
+    messages::{
+        GameMessage, GamePlayerMessage, GameStateMessage, LobbyPlayerMessage, Presence, ServerError,
+    },
     moves::Move,
+    player::Hand,
     reporting::Change,
-    rules::GameRules,
+    rules::{GameRules, RuleSettings},
 };
 
+use uuid::Uuid;
+
 use crate::definitions::WordDB;
 
 #[derive(Debug, Clone)]
 pub struct Player {
     pub socket: Option<SocketAddr>,
+    /// The logged-in player behind this connection, whose rating and history the game counts towards
+    pub account: Option<Uuid>,
+    /// When this player's connection dropped, if they haven't returned since
+    pub disconnected_at: Option<u64>,
+    /// When this player will lose the game by abandonment, if they don't return
+    pub forfeits_at: Option<u64>,
+}
+
+impl Player {
+    pub fn new(socket: SocketAddr, account: Option<Uuid>) -> Self {
+        Self {
+            socket: Some(socket),
+            account,
+            disconnected_at: None,
+            forfeits_at: None,
+        }
+    }
 }
 
+/// How long a finished game or an empty lobby stays around without any activity
+const STALE_GAME_SECS: u64 = 60 * 60;
+/// How long an in-progress game that everyone has left stays around, matching our token lifetime
+const IDLE_GAME_SECS: u64 = 60 * 60 * 24 * 7;
+/// Spectators follow the game from the perspective of the first player
+const SPECTATED_PLAYER: usize = 0;
+
+/// Messages to send out, for handlers that can't borrow the players they're addressed to
+pub type PlayerMessages = Vec<(Player, GameMessage)>;
+
 #[derive(Serialize, Deserialize)]
 pub struct PlayerClaims {
     pub player_index: usize,
     pub room_code: String,
+    /// Room codes are recycled, so tokens only rejoin the game they were issued for
+    pub instance_id: Uuid,
 }
 
 pub struct GameManager {
     pub game_id: String,
     pub players: Vec<Player>,
+    /// Read-only observers, who are sent a spectator's view of the game
+    pub spectators: Vec<Player>,
     pub core_game: Game,
     pub effective_day: u32,
+    /// The language of the game, which decides the dictionary words are judged against
+    pub locale: Locale,
+    /// Customisations picked by the host, which are applied to the rules once the game starts
+    pub rule_settings: RuleSettings,
+    pub moves: Vec<Move>,
+    /// The game just before it started, which replays its moves from the same board and tiles
+    pub starting_game: Option<Game>,
+    /// Distinguishes this game from others that have used the same room code
+    pub instance_id: Uuid,
+    snapshot_version: i64,
+    /// Whether the game was paused by a disconnect, rather than by a player
+    auto_paused: bool,
+    /// When the game last changed, which is whenever it was snapshotted
+    last_activity: u64,
+}
+
+/// Everything needed to rebuild a `GameManager` after a server restart.
+/// Player sockets are not included, as players reconnect using their `PlayerClaims`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub room_code: String,
+    pub instance_id: Uuid,
+    pub snapshot_version: i64,
+    pub effective_day: u32,
+    pub player_count: usize,
+    pub core_game: Game,
+    pub rule_settings: RuleSettings,
+    pub moves: Vec<Move>,
+    pub player_ids: Vec<Option<Uuid>>,
+    pub starting_game: Option<Game>,
+    /// Snapshots from before games had a language are in the default locale
+    #[serde(default)]
+    pub locale: Locale,
 }
 
 impl GameManager {
     pub fn new(game_id: String, effective_day: u32) -> Self {
-        let game = Game::new(9, 9, None, GameRules::latest(Some(effective_day)).1);
+        let (generation, _) = GameRules::latest(Some(effective_day));
+        Self::with_rules(game_id, effective_day, GameRules::generation(generation))
+    }
+
+    pub fn with_rules(game_id: String, effective_day: u32, rules: GameRules) -> Self {
+        let game = Game::new(9, 9, None, rules);
         // let game = Game::new(9, 9, None, GameRules::tuesday());
 
         Self {
             game_id,
             players: vec![],
+            spectators: vec![],
             core_game: game,
             effective_day,
+            locale: Locale::default(),
+            rule_settings: RuleSettings::default(),
+            moves: vec![],
+            starting_game: None,
+            instance_id: Uuid::new_v4(),
+            snapshot_version: 0,
+            auto_paused: false,
+            last_activity: now(),
+        }
+    }
+
+    pub fn snapshot(&mut self) -> GameSnapshot {
+        self.snapshot_version += 1;
+        self.last_activity = now();
+
+        GameSnapshot {
+            room_code: self.game_id.clone(),
+            instance_id: self.instance_id,
+            snapshot_version: self.snapshot_version,
+            effective_day: self.effective_day,
+            player_count: self.players.len(),
+            core_game: self.core_game.clone(),
+            rule_settings: self.rule_settings,
+            moves: self.moves.clone(),
+            player_ids: self.players.iter().map(|p| p.account).collect(),
+            starting_game: self.starting_game.clone(),
+            locale: self.locale,
+        }
+    }
+
+    /// Rebuilds a game from its snapshot. Everyone starts out disconnected,
+    /// so games in progress are paused until the players return.
+    pub fn restore(snapshot: GameSnapshot, abandonment_timeout: Duration) -> Self {
+        let restored_at = now();
+        let in_progress =
+            snapshot.core_game.started_at.is_some() && snapshot.core_game.winner.is_none();
+        let forfeits_at = in_progress.then(|| restored_at + abandonment_timeout.as_secs());
+
+        let mut core_game = snapshot.core_game;
+        let auto_paused = in_progress && !core_game.paused;
+        if auto_paused {
+            core_game.pause();
+        }
+
+        Self {
+            game_id: snapshot.room_code,
+            players: (0..snapshot.player_count)
+                .map(|index| Player {
+                    socket: None,
+                    account: snapshot.player_ids.get(index).copied().flatten(),
+                    disconnected_at: Some(restored_at),
+                    forfeits_at,
+                })
+                .collect(),
+            spectators: vec![],
+            core_game,
+            effective_day: snapshot.effective_day,
+            locale: snapshot.locale,
+            rule_settings: snapshot.rule_settings,
+            moves: snapshot.moves,
+            starting_game: snapshot.starting_game,
+            instance_id: snapshot.instance_id,
+            snapshot_version: snapshot.snapshot_version,
+            auto_paused,
+            last_activity: restored_at,
         }
     }
 
@@ -58,9 +198,9 @@ impl GameManager {
         }
     }
 
-    pub fn add_player(&mut self, player: Player, name: String) -> Result<usize, ()> {
+    pub fn add_player(&mut self, player: Player, name: String) -> Result<usize, ServerError> {
         if self.core_game.started_at.is_some() {
-            return Err(()); // TODO: Error types
+            return Err(ServerError::GameStarted);
         }
         // TODO: Check player #
         self.core_game.add_player(name);
@@ -68,26 +208,167 @@ impl GameManager {
         Ok(self.players.len() - 1)
     }
 
-    pub fn reconnect_player(&mut self, socket: SocketAddr, index: usize) -> Result<(), ()> {
-        match self.players.get_mut(index) {
-            Some(existing_player) => {
-                existing_player.socket = Some(socket);
-                Ok(())
-            }
-            None => {
-                eprintln!("Couldn't reconnect player. Nothing stored for player {index}");
-                Err(())
+    pub fn reconnect_player(
+        &mut self,
+        socket: SocketAddr,
+        index: usize,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Result<PlayerMessages, ServerError> {
+        let Some(existing_player) = self.players.get_mut(index) else {
+            eprintln!("Couldn't reconnect player. Nothing stored for player {index}");
+            return Err(ServerError::CannotRejoin);
+        };
+        existing_player.socket = Some(socket);
+        existing_player.forfeits_at = None;
+        let was_disconnected = existing_player.disconnected_at.take().is_some();
+
+        let mut messages = vec![];
+
+        // Let the returning player know who is still missing
+        for (other_index, other) in self.players.iter().enumerate() {
+            if other.disconnected_at.is_some() {
+                messages.push((
+                    self.players[index].clone(),
+                    GameMessage::PlayerPresence(
+                        self.game_id.clone(),
+                        other_index as u64,
+                        Presence::Disconnected {
+                            forfeits_at: other.forfeits_at,
+                        },
+                    ),
+                ));
             }
         }
+
+        if !was_disconnected {
+            return Ok(messages);
+        }
+
+        let others = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(other_index, _)| *other_index != index)
+            .map(|(_, other)| other);
+        for other in others.chain(self.spectators.iter()) {
+            messages.push((
+                other.clone(),
+                GameMessage::PlayerPresence(
+                    self.game_id.clone(),
+                    index as u64,
+                    Presence::Connected,
+                ),
+            ));
+        }
+
+        let everyone_present = self.players.iter().all(|p| p.disconnected_at.is_none());
+        if self.auto_paused && everyone_present {
+            self.auto_paused = false;
+            messages.extend(
+                self.unpause(words)
+                    .into_iter()
+                    .map(|(player, message)| (player.clone(), message)),
+            );
+        }
+
+        Ok(messages)
+    }
+
+    /// Marks the player on this socket as absent, pausing the game while they're gone.
+    /// Returns the player's index, if the socket was still playing in this game.
+    pub fn disconnect_player(
+        &mut self,
+        socket: SocketAddr,
+        abandonment_timeout: Duration,
+        words: Arc<Mutex<WordDB>>,
+    ) -> Option<(usize, PlayerMessages)> {
+        let player_index = self.get_player_index(socket)?;
+        let disconnected_at = now();
+        // Games can only be abandoned once they've started
+        let forfeits_at = self
+            .in_progress()
+            .then(|| disconnected_at + abandonment_timeout.as_secs());
+
+        let player = &mut self.players[player_index];
+        player.socket = None;
+        player.disconnected_at = Some(disconnected_at);
+        player.forfeits_at = forfeits_at;
+
+        let mut messages: Vec<_> = self
+            .players
+            .iter()
+            .chain(self.spectators.iter())
+            .filter(|p| p.socket.is_some())
+            .map(|p| {
+                (
+                    p.clone(),
+                    GameMessage::PlayerPresence(
+                        self.game_id.clone(),
+                        player_index as u64,
+                        Presence::Disconnected { forfeits_at },
+                    ),
+                )
+            })
+            .collect();
+
+        if self.in_progress() && !self.core_game.paused {
+            self.auto_paused = true;
+            messages.extend(
+                self.pause(words)
+                    .into_iter()
+                    .map(|(player, message)| (player.clone(), message)),
+            );
+        }
+
+        Some((player_index, messages))
+    }
+
+    /// Forfeits the game for a player who didn't return before their abandonment clock ran out
+    pub fn abandon(&mut self, player_index: usize) -> Vec<(&Player, GameMessage)> {
+        // Players who have since returned (or left again later) won't have run out of time yet
+        let abandoned = self.players.get(player_index).is_some_and(|p| {
+            p.socket.is_none()
+                && p.forfeits_at
+                    .is_some_and(|forfeits_at| forfeits_at <= now())
+        });
+
+        if !abandoned || !self.in_progress() {
+            return vec![];
+        }
+
+        self.resign_index(player_index)
+    }
+
+    /// Whether this game can be dropped from memory and its room code reused.
+    /// Expired games are also removed from the database, so they can no longer be rejoined.
+    pub fn expired(&self, now: u64) -> bool {
+        if self.players.iter().any(|p| p.socket.is_some()) {
+            // Give finished games a while longer, in case anyone wants a rematch
+            return self.core_game.winner.is_some()
+                && now.saturating_sub(self.last_activity) > STALE_GAME_SECS;
+        }
+
+        // Finished games are kept around for a while after everyone leaves,
+        // so their room code isn't handed to a new game straight away
+        let idle_for = now.saturating_sub(self.last_activity);
+        if self.in_progress() {
+            idle_for > IDLE_GAME_SECS
+        } else {
+            idle_for > STALE_GAME_SECS
+        }
+    }
+
+    pub fn in_progress(&self) -> bool {
+        self.core_game.started_at.is_some() && self.core_game.winner.is_none()
     }
 
-    pub fn rename_player(&mut self, socket: SocketAddr, name: String) -> Result<(), ()> {
+    pub fn rename_player(&mut self, socket: SocketAddr, name: String) -> Result<(), ServerError> {
         if let Some(player_index) = self.get_player_index(socket) {
             self.core_game.players[player_index].name = name;
             Ok(())
         } else {
             eprintln!("Couldn't rename player. Nothing stored for player {socket}");
-            Err(())
+            Err(ServerError::NotInLobby)
         }
     }
 
@@ -107,12 +388,47 @@ impl GameManager {
         self.core_game.board = board;
     }
 
+    /// Changes the rules this game will start with, which only the host can do
+    pub fn set_rules(
+        &mut self,
+        socket: SocketAddr,
+        settings: RuleSettings,
+    ) -> Result<(), ServerError> {
+        if self.get_player_index(socket) != Some(0) {
+            return Err(ServerError::NotHost);
+        }
+        if self.core_game.started_at.is_some() {
+            return Err(ServerError::GameStarted);
+        }
+        settings.validate(&self.core_game.board)?;
+
+        self.rule_settings = settings;
+        Ok(())
+    }
+
     pub fn game_msg(
         &self,
         player_index: usize,
         word_map: Option<&MutexGuard<'_, WordDB>>,
     ) -> GameStateMessage {
-        let (board, mut changes) = self.core_game.filter_game_to_player(player_index);
+        self.view_msg(player_index, false, word_map)
+    }
+
+    pub fn spectator_msg(&self, word_map: Option<&MutexGuard<'_, WordDB>>) -> GameStateMessage {
+        self.view_msg(SPECTATED_PLAYER, true, word_map)
+    }
+
+    fn view_msg(
+        &self,
+        player_index: usize,
+        spectating: bool,
+        word_map: Option<&MutexGuard<'_, WordDB>>,
+    ) -> GameStateMessage {
+        let (board, mut changes) = if spectating {
+            self.core_game.filter_game_to_spectator(player_index)
+        } else {
+            self.core_game.filter_game_to_player(player_index)
+        };
 
         if let Some(definitions) = word_map {
             for battle in changes.iter_mut().filter_map(|change| match change {
@@ -143,12 +459,15 @@ impl GameManager {
             }
         }
 
-        let hand = self
-            .core_game
-            .get_player(player_index)
-            .expect("Player should have been dealt a hand")
-            .hand
-            .clone();
+        let hand = if spectating {
+            Hand(vec![])
+        } else {
+            self.core_game
+                .get_player(player_index)
+                .expect("Player should have been dealt a hand")
+                .hand
+                .clone()
+        };
 
         let remaining_turns = self
             .core_game
@@ -206,7 +525,9 @@ impl GameManager {
 
         // Trim off all edges and add one back for our land edges to show in the gui
         self.core_game.board.trim();
+        self.core_game.customise_rules(&self.rule_settings);
 
+        self.starting_game = Some(self.core_game.clone());
         self.core_game.start();
         let mut messages = Vec::with_capacity(self.players.len());
 
@@ -218,30 +539,44 @@ impl GameManager {
                 GameMessage::StartedGame(self.game_msg(player_index, None)),
             ));
         }
+        messages.extend(
+            self.spectator_messages(None, GameMessage::SpectatingGame)
+                .into_iter()
+                .map(|(spectator, message)| (spectator.clone(), message)),
+        );
 
         messages
     }
 
-    pub fn resign(&mut self, player: SocketAddr) -> Vec<(&Player, GameMessage)> {
-        if let Some(player_index) = self.get_player_index(player) {
-            self.core_game.resign_player(player_index);
-            let mut messages = Vec::with_capacity(self.players.len());
-
-            if let Some(winner) = self.core_game.winner {
-                for (player_index, player) in self.players.iter().enumerate() {
-                    let mut end_game_msg = self.game_msg(player_index, None);
-                    end_game_msg.changes = vec![];
-                    messages.push((
-                        player,
-                        GameMessage::GameEnd(self.game_msg(player_index, None), winner as u64),
-                    ));
-                }
-            }
+    pub fn resign(
+        &mut self,
+        player: SocketAddr,
+    ) -> Result<Vec<(&Player, GameMessage)>, ServerError> {
+        let Some(player_index) = self.get_player_index(player) else {
+            return Err(ServerError::PlayerNotFound);
+        };
+        Ok(self.resign_index(player_index))
+    }
 
-            messages
-        } else {
-            todo!("Handle missing player");
+    fn resign_index(&mut self, player_index: usize) -> Vec<(&Player, GameMessage)> {
+        self.core_game.resign_player(player_index);
+        let mut messages = Vec::with_capacity(self.players.len());
+
+        if let Some(winner) = self.core_game.winner {
+            for (player_index, player) in self.players.iter().enumerate() {
+                let mut end_game_msg = self.game_msg(player_index, None);
+                end_game_msg.changes = vec![];
+                messages.push((
+                    player,
+                    GameMessage::GameEnd(self.game_msg(player_index, None), winner as u64),
+                ));
+            }
+            messages.extend(
+                self.spectator_messages(None, |state| GameMessage::GameEnd(state, winner as u64)),
+            );
         }
+
+        messages
     }
 
     pub fn play(
@@ -250,21 +585,27 @@ impl GameManager {
         position: Coordinate,
         tile: char,
         words: Arc<Mutex<WordDB>>,
-    ) -> Vec<(&Player, GameMessage)> {
+    ) -> Result<Vec<(&Player, GameMessage)>, ServerError> {
         let mut messages = Vec::with_capacity(self.players.len());
 
         if let Some(player_index) = self.get_player_index(player) {
             let words_db = words.lock();
-            match self.core_game.play_turn(
-                Move::Place {
-                    player: player_index,
-                    tile,
-                    position,
-                },
+            let next_move = Move::Place {
+                player: player_index,
+                tile,
+                position,
+            };
+            let result = self.core_game.play_turn(
+                next_move.clone(),
                 Some(&words_db.valid_words),
                 Some(&words_db.valid_words),
                 None,
-            ) {
+            );
+            if result.is_ok() {
+                self.moves.push(next_move);
+            }
+
+            match result {
                 Ok(Some(winner)) => {
                     for (player_index, player) in self.players.iter().enumerate() {
                         messages.push((
@@ -275,7 +616,10 @@ impl GameManager {
                             ),
                         ));
                     }
-                    return messages;
+                    messages.extend(self.spectator_messages(Some(&words_db), |state| {
+                        GameMessage::GameEnd(state, winner as u64)
+                    }));
+                    Ok(messages)
                 }
                 Ok(None) => {
                     for (player_index, player) in self.players.iter().enumerate() {
@@ -284,21 +628,17 @@ impl GameManager {
                             GameMessage::GameUpdate(self.game_msg(player_index, Some(&words_db))),
                         ));
                     }
-                    return messages;
-                }
-                Err(msg) => {
-                    return vec![(
-                        &self.players[player_index],
-                        GameMessage::GameError(
-                            self.game_id.clone(),
-                            player_index as u64,
-                            msg.into(),
-                        ),
-                    )]
+                    messages
+                        .extend(self.spectator_messages(Some(&words_db), GameMessage::GameUpdate));
+                    Ok(messages)
                 }
+                Err(msg) => Ok(vec![(
+                    &self.players[player_index],
+                    GameMessage::GameError(self.game_id.clone(), player_index as u64, msg),
+                )]),
             }
         } else {
-            todo!("Handle missing player");
+            Err(ServerError::PlayerNotFound)
         }
     }
 
@@ -310,20 +650,26 @@ impl GameManager {
         from: Coordinate,
         to: Coordinate,
         words: Arc<Mutex<WordDB>>,
-    ) -> Vec<(&Player, GameMessage)> {
+    ) -> Result<Vec<(&Player, GameMessage)>, ServerError> {
         let mut messages = Vec::with_capacity(self.players.len());
 
         if let Some(player_index) = self.get_player_index(player) {
             let words_db = words.lock();
-            match self.core_game.play_turn(
-                Move::Swap {
-                    player: player_index,
-                    positions: [from, to],
-                },
+            let next_move = Move::Swap {
+                player: player_index,
+                positions: [from, to],
+            };
+            let result = self.core_game.play_turn(
+                next_move.clone(),
                 Some(&words_db.valid_words),
                 Some(&words_db.valid_words),
                 None,
-            ) {
+            );
+            if result.is_ok() {
+                self.moves.push(next_move);
+            }
+
+            match result {
                 Ok(Some(_)) => {
                     unreachable!("Cannot win by swapping")
                 }
@@ -334,30 +680,37 @@ impl GameManager {
                             GameMessage::GameUpdate(self.game_msg(player_index, None)),
                         ));
                     }
+                    messages.extend(self.spectator_messages(None, GameMessage::GameUpdate));
 
-                    messages
-                }
-                Err(msg) => {
-                    return vec![(
-                        &self.players[player_index],
-                        GameMessage::GameError(
-                            self.game_id.clone(),
-                            player_index as u64,
-                            msg.into(),
-                        ),
-                    )]
+                    Ok(messages)
                 }
+                Err(msg) => Ok(vec![(
+                    &self.players[player_index],
+                    GameMessage::GameError(self.game_id.clone(), player_index as u64, msg),
+                )]),
             }
         } else {
-            todo!("Handle missing player");
+            Err(ServerError::PlayerNotFound)
         }
     }
 
     pub fn pause(&mut self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
         self.core_game.pause();
 
+        self.timing_messages(words)
+    }
+
+    pub fn unpause(&mut self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
+        self.auto_paused = false;
+        self.core_game.unpause();
+
+        self.timing_messages(words)
+    }
+
+    fn timing_messages(&self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
         let words_db = words.lock();
-        self.players
+        let mut messages: Vec<_> = self
+            .players
             .iter()
             .enumerate()
             .map(|(player_index, player)| {
@@ -366,22 +719,223 @@ impl GameManager {
                     GameMessage::GameTimingUpdate(self.game_msg(player_index, Some(&words_db))),
                 )
             })
+            .collect();
+        messages.extend(self.spectator_messages(Some(&words_db), GameMessage::GameTimingUpdate));
+        messages
+    }
+
+    /// Wraps the spectator's view of the game in a message for each spectator
+    pub fn spectator_messages(
+        &self,
+        word_map: Option<&MutexGuard<'_, WordDB>>,
+        wrap: impl Fn(GameStateMessage) -> GameMessage,
+    ) -> Vec<(&Player, GameMessage)> {
+        if self.spectators.is_empty() {
+            return vec![];
+        }
+
+        let state = self.spectator_msg(word_map);
+        self.spectators
+            .iter()
+            .map(|spectator| (spectator, wrap(state.clone())))
             .collect()
     }
 
-    pub fn unpause(&mut self, words: Arc<Mutex<WordDB>>) -> Vec<(&Player, GameMessage)> {
-        self.core_game.unpause();
+    /// Attaches a read-only observer to this game, letting everyone know the new spectator count
+    pub fn add_spectator(
+        &mut self,
+        socket: SocketAddr,
+        words: Arc<Mutex<WordDB>>,
+    ) -> PlayerMessages {
+        let spectator = Player::new(socket, None);
+        self.spectators.push(spectator.clone());
 
-        let words_db = words.lock();
+        let mut messages = self.spectators_update();
+        if self.core_game.started_at.is_some() {
+            let state = self.spectator_msg(Some(&words.lock()));
+            messages.push((spectator, GameMessage::SpectatingGame(state)));
+        }
+        messages
+    }
+
+    /// Detaches an observer, returning the updated spectator count for everyone if they were here
+    pub fn remove_spectator(&mut self, socket: SocketAddr) -> Option<PlayerMessages> {
+        let index = self
+            .spectators
+            .iter()
+            .position(|s| s.socket == Some(socket))?;
+        self.spectators.remove(index);
+        Some(self.spectators_update())
+    }
+
+    pub fn spectators_update(&self) -> PlayerMessages {
+        let count = self.spectators.len() as u64;
         self.players
             .iter()
-            .enumerate()
-            .map(|(player_index, player)| {
+            .chain(self.spectators.iter())
+            .map(|p| {
                 (
-                    player,
-                    GameMessage::GameTimingUpdate(self.game_msg(player_index, Some(&words_db))),
+                    p.clone(),
+                    GameMessage::SpectatorsUpdate(self.game_id.clone(), count),
                 )
             })
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started_game() -> GameManager {
+        let mut game_manager = GameManager::new("test-room".into(), 300);
+        for (index, name) in ["Alice", "Bob"].into_iter().enumerate() {
+            let addr = format!("127.0.0.1:100{index}").parse().unwrap();
+            game_manager
+                .add_player(Player::new(addr, None), name.into())
+                .unwrap();
+        }
+        game_manager.core_game.start();
+        game_manager
+    }
+
+    fn words() -> Arc<Mutex<WordDB>> {
+        Arc::new(Mutex::new(WordDB {
+            conn: None,
+            valid_words: Default::default(),
+            room_codes: vec![],
+            allocated_room_codes: Default::default(),
+        }))
+    }
+
+    #[test]
+    fn snapshots_round_trip() {
+        let mut game_manager = GameManager::new("test-room".into(), 300);
+        game_manager
+            .add_player(
+                Player::new("127.0.0.1:1000".parse().unwrap(), Some(Uuid::new_v4())),
+                "Alice".into(),
+            )
+            .unwrap();
+        game_manager
+            .add_player(
+                Player::new("127.0.0.1:1001".parse().unwrap(), None),
+                "Bob".into(),
+            )
+            .unwrap();
+        game_manager.core_game.start();
+        game_manager.starting_game = Some(game_manager.core_game.clone());
+
+        let snapshot = game_manager.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: GameSnapshot = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.room_code, snapshot.room_code);
+        assert_eq!(restored.instance_id, snapshot.instance_id);
+        assert_eq!(restored.snapshot_version, snapshot.snapshot_version);
+        assert_eq!(restored.effective_day, snapshot.effective_day);
+        assert_eq!(restored.player_count, 2);
+        assert_eq!(restored.player_ids, snapshot.player_ids);
+        assert_eq!(restored.moves, snapshot.moves);
+        assert_eq!(restored.locale, snapshot.locale);
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+
+        let restored_manager = GameManager::restore(restored, Duration::from_secs(60));
+        assert_eq!(restored_manager.instance_id, snapshot.instance_id);
+        assert_eq!(restored_manager.locale, snapshot.locale);
+        assert!(restored_manager.core_game.paused);
+        assert!(restored_manager
+            .players
+            .iter()
+            .all(|p| p.socket.is_none() && p.forfeits_at.is_some()));
+    }
+
+    #[test]
+    fn disconnects_pause_until_everyone_returns() {
+        let mut game_manager = started_game();
+        let alice: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+        let timeout = Duration::from_secs(60);
+
+        let (index, _) = game_manager
+            .disconnect_player(alice, timeout, words())
+            .unwrap();
+        assert_eq!(index, 0);
+        assert!(game_manager.core_game.paused);
+        assert!(game_manager.players[0].socket.is_none());
+        assert!(game_manager.players[0].forfeits_at.is_some());
+
+        // The same socket can't disconnect twice
+        assert!(game_manager
+            .disconnect_player(alice, timeout, words())
+            .is_none());
+
+        let rejoined_at: SocketAddr = "127.0.0.1:2000".parse().unwrap();
+        game_manager
+            .reconnect_player(rejoined_at, 0, words())
+            .unwrap();
+        assert!(!game_manager.core_game.paused);
+        assert_eq!(game_manager.players[0].socket, Some(rejoined_at));
+        assert!(game_manager.players[0].disconnected_at.is_none());
+        assert!(game_manager.players[0].forfeits_at.is_none());
+    }
+
+    #[test]
+    fn player_pauses_survive_reconnects() {
+        let mut game_manager = started_game();
+        let alice: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+        game_manager.pause(words());
+
+        game_manager
+            .disconnect_player(alice, Duration::from_secs(60), words())
+            .unwrap();
+        game_manager.reconnect_player(alice, 0, words()).unwrap();
+        assert!(game_manager.core_game.paused);
+    }
+
+    #[test]
+    fn lobbies_are_not_abandoned() {
+        let mut game_manager = GameManager::new("test-room".into(), 300);
+        let alice: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+        game_manager
+            .add_player(Player::new(alice, None), "Alice".into())
+            .unwrap();
+
+        game_manager
+            .disconnect_player(alice, Duration::ZERO, words())
+            .unwrap();
+        assert!(!game_manager.core_game.paused);
+        assert!(game_manager.players[0].forfeits_at.is_none());
+        assert!(game_manager.abandon(0).is_empty());
+    }
+
+    #[test]
+    fn absent_players_forfeit_after_the_deadline() {
+        let alice: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+
+        // Still inside the abandonment window
+        let mut game_manager = started_game();
+        game_manager
+            .disconnect_player(alice, Duration::from_secs(60), words())
+            .unwrap();
+        assert!(game_manager.abandon(0).is_empty());
+        assert_eq!(game_manager.core_game.winner, None);
+
+        // Returning before the check runs keeps the player in the game
+        let mut game_manager = started_game();
+        game_manager
+            .disconnect_player(alice, Duration::ZERO, words())
+            .unwrap();
+        game_manager.reconnect_player(alice, 0, words()).unwrap();
+        assert!(game_manager.abandon(0).is_empty());
+        assert_eq!(game_manager.core_game.winner, None);
+
+        // Out of time, so the other player wins
+        let mut game_manager = started_game();
+        game_manager
+            .disconnect_player(alice, Duration::ZERO, words())
+            .unwrap();
+        assert!(!game_manager.abandon(0).is_empty());
+        assert_eq!(game_manager.core_game.winner, Some(1));
+        assert!(!game_manager.in_progress());
+    }
+}
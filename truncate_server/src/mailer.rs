@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use crate::errors::TruncateServerError;
+
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Something that can deliver emails to players, such as their login links.
+/// Sending may block, so it is always called from a blocking task.
+pub trait Mailer: Send + Sync {
+    fn send(&self, email: Email) -> Result<(), TruncateServerError>;
+}
+
+/// Stands in for a real mail provider during local development,
+/// writing each email to its own file in a directory instead of sending it
+pub struct FileMailer {
+    directory: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, email: Email) -> Result<(), TruncateServerError> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        let file_name = format!(
+            "{}-{}.eml",
+            truncate_core::game::now(),
+            uuid::Uuid::new_v4()
+        );
+        let contents = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            email.to, email.subject, email.body
+        );
+        std::fs::write(self.directory.join(&file_name), contents)?;
+
+        println!("Wrote email for {} to {file_name}", email.to);
+        Ok(())
+    }
+}
@@ -1,12 +1,15 @@
 mod definitions;
 mod errors;
 mod game_state;
+mod mailer;
+mod matchmaking;
+mod ratings;
 mod storage;
 
 use parking_lot::Mutex;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 use std::{env, io::Error as IoError, net::SocketAddr, sync::Arc};
 use uuid::Uuid;
 
@@ -20,38 +23,39 @@ use tokio_stream::wrappers::UnboundedReceiverStream;
 use tungstenite::protocol::Message;
 
 use crate::definitions::read_defs;
-use crate::game_state::{Player, PlayerClaims};
+use crate::errors::{NonceError, TruncateServerError};
+use crate::game_state::{GameSnapshot, Player, PlayerClaims};
 use crate::storage::accounts::{mark_changelog_read, LoginResponse};
 use crate::storage::daily;
 use crate::storage::events::create_event;
+use crate::storage::games;
+use crate::storage::history;
+use crate::storage::nonces;
 use game_state::GameManager;
+use mailer::{FileMailer, Mailer};
+use matchmaking::{MatchQueue, MatchTicket};
 use storage::accounts::{self, mark_most_changelogs_read, AuthedTruncateToken};
+use truncate_core::locale::Locale;
 use truncate_core::messages::{
-    DailyStateMessage, GameMessage, GameStateMessage, LobbyPlayerMessage, Nonce,
-    NoncedPlayerMessage, PlayerMessage,
+    DailyStateMessage, GameMessage, GameStateMessage, LobbyPlayerMessage, MatchmakingStatus, Nonce,
+    NoncedPlayerMessage, PlayerMessage, ServerError,
 };
+use truncate_core::rules::GameRules;
 
-// TODO: Also find a way to include this in the database to prevent replay if reconnecting to a different backend
+/// Tracks handled nonces in memory, for when the server is running without a database
 #[derive(Default)]
 pub struct NonceTracker {
     map: HashMap<AuthedTruncateToken, HashSet<Nonce>>,
 }
 
 impl NonceTracker {
-    fn burn_nonce(&mut self, user: AuthedTruncateToken, nonce: Nonce) -> Result<(), ()> {
+    fn burn_nonce(&mut self, user: AuthedTruncateToken, nonce: Nonce) -> Result<(), NonceError> {
         let set = self.map.entry(user).or_default();
 
-        let current_time = truncate_core::game::now();
-
-        // Reject all nonces older than an hour.
-        if nonce.generated_at < current_time.saturating_sub(60 * 60) {
-            return Err(());
-        }
-
         if set.insert(nonce) {
             Ok(())
         } else {
-            Err(())
+            Err(NonceError::Replayed)
         }
     }
 
@@ -64,15 +68,54 @@ impl NonceTracker {
     }
 }
 
+/// Counts of what the server is holding in memory, logged for monitoring
+#[derive(Debug, Default)]
+pub struct ServerStats {
+    lobbies: usize,
+    live_games: usize,
+    finished_games: usize,
+    peers: usize,
+    assignments: usize,
+    queued_for_match: usize,
+    allocated_room_codes: usize,
+}
+
+impl std::fmt::Display for ServerStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} lobbies, {} live games, {} finished games, {} peers, {} assignments, {} queued for a match, {} room codes in use",
+            self.lobbies,
+            self.live_games,
+            self.finished_games,
+            self.peers,
+            self.assignments,
+            self.queued_for_match,
+            self.allocated_room_codes
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct ServerState {
     games: Arc<Mutex<HashMap<String, Arc<Mutex<GameManager>>>>>,
     assignments: Arc<Mutex<HashMap<SocketAddr, String>>>,
     peers: Arc<Mutex<HashMap<SocketAddr, UnboundedSender<GameMessage>>>>,
+    match_queue: Arc<Mutex<MatchQueue>>,
+    /// Which room each spectating peer is watching
+    spectating: Arc<Mutex<HashMap<SocketAddr, String>>>,
+    /// When each peer last sent us a message, used to detect silent disconnects
+    last_seen: Arc<Mutex<HashMap<SocketAddr, u64>>>,
+    /// How long a disconnected player has to return before forfeiting their game
+    abandonment_timeout: std::time::Duration,
     word_db: Arc<Mutex<WordDB>>,
     nonces: Arc<Mutex<NonceTracker>>,
     truncate_db: Option<PgPool>,
     jwt_key: HS256Key,
+    /// Delivers login links to players
+    mailer: Arc<dyn Mailer>,
+    /// Where the web client is hosted, for building links that open it
+    public_url: String,
 }
 
 impl ServerState {
@@ -80,6 +123,13 @@ impl ServerState {
         Arc::clone(&self.word_db)
     }
 
+    /// The dictionary for games played in the given language
+    fn words_for(&self, locale: Locale) -> Arc<Mutex<WordDB>> {
+        match locale {
+            Locale::English => self.words(),
+        }
+    }
+
     fn game_code(&self) -> String {
         self.word_db.lock().get_free_code()
     }
@@ -93,6 +143,163 @@ impl ServerState {
         game
     }
 
+    /// Adds a game loaded from the database, unless it has already been restored.
+    /// Nobody is connected to a restored game, so anyone seated starts their abandonment clock.
+    fn restore_game(&self, snapshot: GameSnapshot) -> Arc<Mutex<GameManager>> {
+        let game_id = snapshot.room_code.to_lowercase();
+        self.word_db
+            .lock()
+            .allocated_room_codes
+            .insert(game_id.clone());
+
+        let (game, abandoning_players) = match self.games.lock().entry(game_id.clone()) {
+            Entry::Occupied(existing) => (Arc::clone(existing.get()), vec![]),
+            Entry::Vacant(vacant) => {
+                let game_manager = GameManager::restore(snapshot, self.abandonment_timeout);
+                let abandoning_players: Vec<_> = game_manager
+                    .players
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, player)| player.forfeits_at.is_some())
+                    .map(|(player_index, _)| player_index)
+                    .collect();
+                let game = vacant.insert(Arc::new(Mutex::new(game_manager)));
+                (Arc::clone(game), abandoning_players)
+            }
+        };
+
+        for player_index in abandoning_players {
+            tokio::spawn(check_abandonment(
+                game_id.clone(),
+                player_index,
+                self.clone(),
+            ));
+        }
+
+        game
+    }
+
+    /// Finds a game in memory, or restores it if it was running before a server restart
+    async fn find_game(&self, code: &String) -> Option<Arc<Mutex<GameManager>>> {
+        if let Some(existing_game) = self.get_game_by_code(code) {
+            return Some(existing_game);
+        }
+
+        match games::load_game(self, code).await {
+            Ok(Some(snapshot)) => {
+                println!("Restoring room {code} from the database");
+                Some(self.restore_game(snapshot))
+            }
+            _ => None,
+        }
+    }
+
+    /// Detaches a peer from any game they're spectating
+    fn stop_spectating(&self, addr: &SocketAddr) {
+        let Some(game_id) = self.spectating.lock().remove(addr) else {
+            return;
+        };
+        let Some(existing_game) = self.get_game_by_code(&game_id) else {
+            return;
+        };
+
+        let mut game_manager = existing_game.lock();
+        for (player, message) in game_manager.remove_spectator(*addr).unwrap_or_default() {
+            let Some(socket) = player.socket else {
+                continue;
+            };
+            _ = self.send_to_player(&socket, message);
+        }
+    }
+
+    async fn persist_game(&self, snapshot: GameSnapshot) {
+        if snapshot.core_game.winner.is_some() {
+            match history::record_finished_game(self, &snapshot).await {
+                Ok(()) | Err(TruncateServerError::DatabaseOffline) => {}
+                Err(e) => eprintln!("Errored recording finished game: {e}\n{e:?}"),
+            }
+        }
+
+        match games::persist_game(self, snapshot).await {
+            Ok(()) | Err(TruncateServerError::DatabaseOffline) => {}
+            Err(e) => eprintln!("Errored persisting game: {e}\n{e:?}"),
+        }
+    }
+
+    /// Drops expired games and any connections that were left pointing at them,
+    /// returning the room codes that were freed up.
+    async fn reap_games(&self) -> Vec<String> {
+        let now = truncate_core::game::now();
+
+        let mut expired_codes = vec![];
+        self.games.lock().retain(|code, game| {
+            // Games can lock the map while held, so skip over any that are busy
+            let expired = game.try_lock().is_some_and(|game| game.expired(now));
+            if expired {
+                expired_codes.push(code.clone());
+            }
+            !expired
+        });
+
+        // Stored games would otherwise be restored when their code is next used,
+        // and would keep new games under that code from being stored.
+        if !expired_codes.is_empty() {
+            match games::delete_games(self, &expired_codes).await {
+                Ok(()) | Err(TruncateServerError::DatabaseOffline) => {}
+                Err(e) => eprintln!("Errored deleting expired games: {e}\n{e:?}"),
+            }
+        }
+
+        let mut word_db = self.word_db.lock();
+        for code in &expired_codes {
+            word_db.release_code(code);
+        }
+        drop(word_db);
+
+        // Only hold one of these locks at a time, as other tasks take them in varying orders
+        let live_peers: HashSet<SocketAddr> = {
+            let mut peers = self.peers.lock();
+            peers.retain(|_, tx| !tx.is_closed());
+            peers.keys().copied().collect()
+        };
+        let live_codes: HashSet<String> = self.games.lock().keys().cloned().collect();
+
+        self.assignments
+            .lock()
+            .retain(|addr, code| live_peers.contains(addr) && live_codes.contains(code));
+        self.spectating
+            .lock()
+            .retain(|addr, code| live_peers.contains(addr) && live_codes.contains(code));
+        self.last_seen
+            .lock()
+            .retain(|addr, _| live_peers.contains(addr));
+
+        expired_codes
+    }
+
+    fn stats(&self) -> ServerStats {
+        let mut stats = ServerStats::default();
+
+        let games: Vec<_> = self.games.lock().values().cloned().collect();
+        for game in games {
+            let game = game.lock();
+            if game.core_game.winner.is_some() {
+                stats.finished_games += 1;
+            } else if game.in_progress() {
+                stats.live_games += 1;
+            } else {
+                stats.lobbies += 1;
+            }
+        }
+
+        stats.peers = self.peers.lock().len();
+        stats.assignments = self.assignments.lock().len();
+        stats.queued_for_match = self.match_queue.lock().len();
+        stats.allocated_room_codes = self.word_db.lock().allocated_room_codes.len();
+
+        stats
+    }
+
     fn attach_player_to_game(&self, addr: &SocketAddr, game_id: &String) {
         let mut assignments = self.assignments.lock();
         let game_id = game_id.to_lowercase();
@@ -114,24 +321,96 @@ impl ServerState {
     fn track_peer(&self, addr: &SocketAddr, tx: UnboundedSender<GameMessage>) {
         let mut peers = self.peers.lock();
         peers.insert(*addr, tx);
+        self.mark_seen(addr);
     }
 
-    fn get_player_tx(&self, addr: &SocketAddr) -> Option<UnboundedSender<GameMessage>> {
-        self.peers.lock().get(addr).cloned()
+    fn mark_seen(&self, addr: &SocketAddr) {
+        self.last_seen
+            .lock()
+            .insert(*addr, truncate_core::game::now());
     }
 
-    fn send_to_player(&self, addr: &SocketAddr, msg: GameMessage) -> Result<(), ()> {
-        // TODO: Use a better error crate and stop using `Result<(), ()>`
+    /// Tells the game a player's connection has closed, and starts their abandonment clock
+    async fn drop_player(&self, addr: &SocketAddr) {
+        self.peers.lock().remove(addr);
+        self.last_seen.lock().remove(addr);
+        self.stop_spectating(addr);
+        self.match_queue.lock().cancel(*addr);
+        let Some(game_id) = self.assignments.lock().remove(addr) else {
+            return;
+        };
+        let Some(existing_game) = self.get_game_by_code(&game_id) else {
+            return;
+        };
 
-        let Some(peer_tx) = self.get_player_tx(addr) else {
-            return Err(());
+        let (snapshot, abandoning_player) = {
+            let mut game_manager = existing_game.lock();
+            let words = self.words_for(game_manager.locale);
+            let Some((player_index, messages)) =
+                game_manager.disconnect_player(*addr, self.abandonment_timeout, words)
+            else {
+                return;
+            };
+            println!("Player {player_index} disconnected from room {game_id}");
+
+            for (player, message) in messages {
+                let Some(socket) = player.socket else {
+                    continue;
+                };
+                _ = self.send_to_player(&socket, message);
+            }
+
+            let abandoning_player = game_manager.players[player_index]
+                .forfeits_at
+                .map(|_| player_index);
+            (game_manager.snapshot(), abandoning_player)
         };
 
-        let Ok(_) = peer_tx.send(msg) else {
-            return Err(());
+        if let Some(player_index) = abandoning_player {
+            tokio::spawn(check_abandonment(game_id, player_index, self.clone()));
+        }
+
+        self.persist_game(snapshot).await;
+    }
+
+    /// Marks a player's message as handled, failing if it already was.
+    /// Nonces are burned in the database when we have one, so that replays are caught
+    /// even if the player reconnects to a different server instance.
+    async fn burn_nonce(&self, user: AuthedTruncateToken, nonce: Nonce) -> Result<(), NonceError> {
+        let current_time = truncate_core::game::now();
+
+        // Reject all nonces older than an hour.
+        if nonce.generated_at < current_time.saturating_sub(60 * 60) {
+            return Err(NonceError::Expired);
+        }
+
+        if self.truncate_db.is_some() {
+            match nonces::burn_nonce(self, &user, &nonce).await {
+                Ok(true) => return Ok(()),
+                Ok(false) => return Err(NonceError::Replayed),
+                Err(e) => eprintln!("Errored burning nonce, falling back to memory: {e}"),
+            }
+        }
+
+        self.nonces.lock().burn_nonce(user, nonce)
+    }
+
+    fn get_player_tx(&self, addr: &SocketAddr) -> Option<UnboundedSender<GameMessage>> {
+        self.peers.lock().get(addr).cloned()
+    }
+
+    fn send_to_player(
+        &self,
+        addr: &SocketAddr,
+        msg: GameMessage,
+    ) -> Result<(), TruncateServerError> {
+        let Some(peer_tx) = self.get_player_tx(addr) else {
+            return Err(TruncateServerError::PlayerDisconnected(*addr));
         };
 
-        Ok(())
+        peer_tx
+            .send(msg)
+            .map_err(|_| TruncateServerError::PlayerDisconnected(*addr))
     }
 }
 
@@ -140,12 +419,15 @@ async fn handle_player_msg(
     player_addr: SocketAddr,
     server_state: ServerState,
     connection_info_mutex: Arc<Mutex<ConnectionInfo>>,
-) -> Result<(), tungstenite::Error> {
+) -> Result<(), Box<tungstenite::Error>> {
     let (nonce, mut parsed_msg) = {
-        if let Ok(nonced_msg) = serde_json::from_str::<NoncedPlayerMessage>(msg.to_text().unwrap())
-        {
+        // Only text messages carry player messages
+        let Ok(text) = msg.to_text() else {
+            return Ok(());
+        };
+        if let Ok(nonced_msg) = serde_json::from_str::<NoncedPlayerMessage>(text) {
             (Some(nonced_msg.nonce), nonced_msg.message)
-        } else if let Ok(bare_msg) = serde_json::from_str::<PlayerMessage>(msg.to_text().unwrap()) {
+        } else if let Ok(bare_msg) = serde_json::from_str::<PlayerMessage>(text) {
             (None, bare_msg)
         } else {
             return Ok(());
@@ -158,29 +440,27 @@ async fn handle_player_msg(
             // The player will have to re-send this message after logging in.
             // PleaseLogin tells the client to login prior to re-sending their messages,
             // otherwise they'll thrash waiting for an ack on this message.
-            server_state
-                .send_to_player(&player_addr, GameMessage::PleaseLogin)
-                .unwrap();
+            _ = server_state.send_to_player(&player_addr, GameMessage::PleaseLogin);
 
             return Ok(());
         };
 
         // Pre-acknowledge this message as "handled".
         // If the server panics, we don't want the client to keep thrashing on this message.
-        server_state
-            .send_to_player(&player_addr, GameMessage::Ack(nonce.clone()))
-            .unwrap();
+        _ = server_state.send_to_player(&player_addr, GameMessage::Ack(nonce.clone()));
 
-        let mut nm = server_state.nonces.lock();
-
-        if nm.burn_nonce(connection_player, nonce).is_err() {
+        if server_state
+            .burn_nonce(connection_player, nonce)
+            .await
+            .is_err()
+        {
             // Allow some information-retrieval messages to be replayed,
             // since duplicate replies can be handled by the client,
             // and if the response from the server was lost in a disconnect
             // they may be stuck waiting for the info (e.g. waiting for DailyStats to show splash screen)
             let replayable = matches!(
                 parsed_msg,
-                RequestDefinitions(_) | RequestStats(_) | LoadReplay(_)
+                RequestDefinitions(_) | RequestStats(_) | RequestMatchHistory(_) | LoadReplay(_)
             );
 
             if !replayable {
@@ -190,39 +470,71 @@ async fn handle_player_msg(
         }
     }
 
+    server_state.mark_seen(&player_addr);
+
     use PlayerMessage::*;
     // If player is joining a room that they have a token for,
-    // rejoin using that token instead.
-    // TODO: Handle corner case when room code is reused and they're very unlucky
+    // rejoin using that token instead, as long as the room hasn't been reused since.
     if let JoinGame(joining_room_code, _, Some(token)) = &parsed_msg {
         if let Ok(JWTClaims {
-            custom: PlayerClaims { room_code, .. },
+            custom:
+                PlayerClaims {
+                    room_code,
+                    instance_id,
+                    ..
+                },
             ..
         }) = server_state
             .jwt_key
             .verify_token::<PlayerClaims>(&token, None)
         {
             if joining_room_code.to_uppercase() == room_code.to_uppercase() {
-                parsed_msg = PlayerMessage::RejoinGame(token.clone());
+                let same_game = match server_state.find_game(&room_code).await {
+                    Some(game) => game.lock().instance_id == instance_id,
+                    None => false,
+                };
+                if same_game {
+                    parsed_msg = PlayerMessage::RejoinGame(token.clone());
+                }
             }
         }
     }
 
-    let player_err = |msg: String| {
-        server_state
-            .send_to_player(&player_addr, GameMessage::GenericError(msg))
-            .unwrap();
+    let player_err = |error: ServerError| {
+        _ = server_state.send_to_player(&player_addr, GameMessage::Error(error));
         Ok(())
     };
 
+    let spectating = server_state.spectating.lock().contains_key(&player_addr);
+    if spectating
+        && matches!(
+            parsed_msg,
+            EditBoard(_)
+                | EditName(_)
+                | SetRules(_)
+                | StartGame
+                | Resign
+                | Place(..)
+                | Swap(..)
+                | Rematch
+                | Pause
+                | Unpause
+        )
+    {
+        return player_err(ServerError::SpectatorsCannotPlay);
+    }
+
     match parsed_msg {
-        Ping => { /* TODO: Track pings and notify the game when players disconnect */ }
+        Ping => { /* Every message counts towards presence, see `ServerState::mark_seen` */ }
         NewGame {
             mut player_name,
             effective_day,
         } => {
+            server_state.stop_spectating(&player_addr);
+
             let new_game_id = server_state.game_code();
             let mut game = GameManager::new(new_game_id.clone(), effective_day);
+            game.locale = connection_info_mutex.lock().locale;
 
             let connection_player = connection_info_mutex.lock().player.clone();
             _ = create_event(&server_state, &"new_game".into(), connection_player).await;
@@ -231,16 +543,17 @@ async fn handle_player_msg(
                 player_name = "Player 1".into();
             }
 
-            game.add_player(
-                Player {
-                    socket: Some(player_addr.clone()),
-                },
-                player_name.clone(),
-            )
-            .expect("Failed to add first player to game");
+            let account = connection_info_mutex
+                .lock()
+                .player
+                .as_ref()
+                .map(|p| p.player());
+            game.add_player(Player::new(player_addr, account), player_name.clone())
+                .expect("Failed to add first player to game");
 
             let color = game.core_game.players[0].color;
             let board = game.core_game.board.clone();
+            let snapshot = game.snapshot();
 
             server_state.add_new_game(&new_game_id, game);
             server_state.attach_player_to_game(&player_addr, &new_game_id);
@@ -249,6 +562,7 @@ async fn handle_player_msg(
                 PlayerClaims {
                     player_index: 0,
                     room_code: new_game_id.clone(),
+                    instance_id: snapshot.instance_id,
                 },
                 Duration::from_days(7), // TODO: Determine game expiration time
             );
@@ -257,103 +571,112 @@ async fn handle_player_msg(
                 .authenticate(claims)
                 .expect("Claims should be serializable");
 
-            server_state
-                .send_to_player(
-                    &player_addr,
-                    GameMessage::JoinedLobby(
-                        0,
-                        new_game_id,
-                        vec![LobbyPlayerMessage {
-                            name: player_name,
-                            color,
-                            index: 0,
-                        }],
-                        board,
-                        token,
-                    ),
-                )
-                .unwrap();
+            _ = server_state.send_to_player(
+                &player_addr,
+                GameMessage::JoinedLobby(
+                    0,
+                    new_game_id,
+                    vec![LobbyPlayerMessage {
+                        name: player_name,
+                        color,
+                        index: 0,
+                    }],
+                    board,
+                    snapshot.rule_settings,
+                    token,
+                ),
+            );
+
+            server_state.persist_game(snapshot).await;
         }
         JoinGame(room_code, mut player_name, _) => {
             let code = room_code.to_ascii_lowercase();
-            if let Some(existing_game) = server_state.get_game_by_code(&code) {
+            if let Some(existing_game) = server_state.find_game(&code).await {
                 let connection_player = connection_info_mutex.lock().player.clone();
                 _ = create_event(&server_state, &"join_game".into(), connection_player).await;
 
-                let mut game_manager = existing_game.lock();
+                // Taking a seat ends any spectating, otherwise the player's moves would be refused
+                server_state.stop_spectating(&player_addr);
 
-                // TODO: This is the easiest place to check for lobby capacity right now,
-                // but we'll need to reevaluate if we ever support >2 players, or spectators.
-                if game_manager.players.len() >= 2 {
-                    return player_err(format!(
-                        "Room {} already has two players, cannot join",
-                        code.to_ascii_uppercase()
-                    ));
-                }
+                let joined = {
+                    let mut game_manager = existing_game.lock();
 
-                server_state.attach_player_to_game(&player_addr, &room_code);
+                    // TODO: This is the easiest place to check for lobby capacity right now,
+                    // but we'll need to reevaluate if we ever support >2 players.
+                    // Anyone else can watch using `SpectateGame`.
+                    if game_manager.players.len() >= 2 {
+                        return player_err(ServerError::RoomFull(code));
+                    }
 
-                if &player_name == "___AUTO___" {
-                    player_name = format!("Player {}", game_manager.players.len() + 1);
-                }
+                    server_state.attach_player_to_game(&player_addr, &room_code);
 
-                if let Ok(player_index) = game_manager.add_player(
-                    Player {
-                        socket: Some(player_addr.clone()),
-                    },
-                    player_name.clone(),
-                ) {
-                    let claims = Claims::with_custom_claims(
-                        PlayerClaims {
-                            player_index,
-                            room_code: room_code.clone(),
-                        },
-                        Duration::from_days(7), // TODO: Determine game expiration time
-                    );
-                    let token = server_state
-                        .jwt_key
-                        .authenticate(claims)
-                        .expect("Claims should be serializable");
+                    if &player_name == "___AUTO___" {
+                        player_name = format!("Player {}", game_manager.players.len() + 1);
+                    }
+
+                    let account = connection_info_mutex
+                        .lock()
+                        .player
+                        .as_ref()
+                        .map(|p| p.player());
+                    if let Ok(player_index) = game_manager
+                        .add_player(Player::new(player_addr, account), player_name.clone())
+                    {
+                        let claims = Claims::with_custom_claims(
+                            PlayerClaims {
+                                player_index,
+                                room_code: room_code.clone(),
+                                instance_id: game_manager.instance_id,
+                            },
+                            Duration::from_days(7), // TODO: Determine game expiration time
+                        );
+                        let token = server_state
+                            .jwt_key
+                            .authenticate(claims)
+                            .expect("Claims should be serializable");
 
-                    server_state
-                        .send_to_player(
+                        _ = server_state.send_to_player(
                             &player_addr,
                             GameMessage::JoinedLobby(
                                 player_index as u64,
                                 code.clone(),
                                 game_manager.player_list(),
                                 game_manager.core_game.board.clone(),
+                                game_manager.rule_settings,
                                 token,
                             ),
-                        )
-                        .unwrap();
+                        );
 
-                    for player in &game_manager.players {
-                        let Some(socket) = player.socket else {
-                            continue;
-                        };
+                        for player in &game_manager.players {
+                            let Some(socket) = player.socket else {
+                                continue;
+                            };
 
-                        server_state
-                            .send_to_player(
+                            _ = server_state.send_to_player(
                                 &socket,
                                 GameMessage::LobbyUpdate(
                                     player_index as u64,
                                     code.clone(),
                                     game_manager.player_list(),
                                     game_manager.core_game.board.clone(),
+                                    game_manager.rule_settings,
                                 ),
-                            )
-                            .unwrap();
+                            );
+                        }
+
+                        Some(game_manager.snapshot())
+                    } else {
+                        None
                     }
-                } else {
+                };
+
+                let Some(snapshot) = joined else {
                     // TODO: Render a better error here
-                    return player_err(format!(
-                        "Unable to join room {}",
-                        code.to_ascii_uppercase()
-                    ));
-                }
+                    return player_err(ServerError::CannotJoinRoom(code));
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                return player_err(format!("Room {} does not exist", code.to_ascii_uppercase()));
+                return player_err(ServerError::RoomNotFound(code));
             }
         }
         RejoinGame(token) => {
@@ -361,101 +684,173 @@ async fn handle_player_msg(
                 .jwt_key
                 .verify_token::<PlayerClaims>(&token, None)
             else {
-                return player_err("Invalid Token".into());
+                return player_err(ServerError::InvalidGameToken);
             };
             let PlayerClaims {
                 player_index,
                 room_code,
+                instance_id,
             } = claims.custom;
 
-            let words_db = server_state.words();
-
             let code = room_code.to_ascii_lowercase();
-            if let Some(existing_game) = server_state.get_game_by_code(&code) {
-                let mut game_manager = existing_game.lock();
-                println!("Trying to reconnect player {player_index} to room {code}");
-                match game_manager.reconnect_player(player_addr.clone(), player_index) {
-                    Ok(_) => {
-                        server_state.attach_player_to_game(&player_addr, &code);
-
-                        if game_manager.core_game.started_at.is_some() {
-                            server_state
-                                .send_to_player(
-                                    &player_addr,
-                                    GameMessage::StartedGame(
-                                        game_manager.game_msg(player_index, Some(&words_db.lock())),
-                                    ),
-                                )
-                                .unwrap();
-                        } else {
-                            server_state
-                                .send_to_player(
-                                    &player_addr,
-                                    GameMessage::JoinedLobby(
-                                        player_index as u64,
-                                        code.clone(),
-                                        game_manager.player_list(),
-                                        game_manager.core_game.board.clone(),
-                                        token,
-                                    ),
-                                )
-                                .unwrap();
-                        }
+            if let Some(existing_game) = server_state.find_game(&code).await {
+                server_state.stop_spectating(&player_addr);
+
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    // The room code may since have been reused by another game
+                    if game_manager.instance_id != instance_id {
+                        return player_err(ServerError::CannotRejoin);
                     }
-                    Err(_) => {
-                        return player_err("Error rejoining existing game".into());
+                    let words_db = server_state.words_for(game_manager.locale);
+                    println!("Trying to reconnect player {player_index} to room {code}");
+                    let Ok(presence_messages) =
+                        game_manager.reconnect_player(player_addr, player_index, words_db.clone())
+                    else {
+                        return player_err(ServerError::CannotRejoin);
+                    };
+                    server_state.attach_player_to_game(&player_addr, &code);
+
+                    if game_manager.core_game.started_at.is_some() {
+                        _ = server_state.send_to_player(
+                            &player_addr,
+                            GameMessage::StartedGame(
+                                game_manager.game_msg(player_index, Some(&words_db.lock())),
+                            ),
+                        );
+                    } else {
+                        _ = server_state.send_to_player(
+                            &player_addr,
+                            GameMessage::JoinedLobby(
+                                player_index as u64,
+                                code.clone(),
+                                game_manager.player_list(),
+                                game_manager.core_game.board.clone(),
+                                game_manager.rule_settings,
+                                token,
+                            ),
+                        );
                     }
-                }
+
+                    for (player, message) in presence_messages {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
+                        _ = server_state.send_to_player(&socket, message);
+                    }
+
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                return player_err(format!(
-                    "Room {} no longer exists",
-                    code.to_ascii_uppercase()
-                ));
+                return player_err(ServerError::RoomNotFound(code));
             }
         }
-        EditBoard(board) => {
-            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let mut game_manager = existing_game.lock();
-                game_manager.edit_board(board.clone());
-                let player_list: Vec<_> = game_manager
-                    .core_game
+        SpectateGame(room_code) => {
+            let code = room_code.to_ascii_lowercase();
+            if server_state.assignments.lock().contains_key(&player_addr) {
+                return player_err(ServerError::AlreadyPlaying);
+            }
+            let Some(existing_game) = server_state.find_game(&code).await else {
+                return player_err(ServerError::RoomNotFound(code));
+            };
+
+            // Seated players can't watch their own game from another connection
+            let account = connection_info_mutex
+                .lock()
+                .player
+                .as_ref()
+                .map(|p| p.player());
+            let seated = account.is_some()
+                && existing_game
+                    .lock()
                     .players
                     .iter()
-                    .map(|p| LobbyPlayerMessage {
-                        name: p.name.clone(),
-                        index: p.index,
-                        color: p.color,
-                    })
-                    .collect();
+                    .any(|p| p.account == account);
+            if seated {
+                return player_err(ServerError::AlreadyPlaying);
+            }
+
+            // Spectators only watch one game at a time
+            server_state.stop_spectating(&player_addr);
 
-                let Some(player_index) = game_manager.get_player_index(player_addr) else {
-                    todo!("Handle player editing the board without having a turn index");
+            let mut game_manager = existing_game.lock();
+            println!("Attaching spectator {player_addr} to room {code}");
+            server_state
+                .spectating
+                .lock()
+                .insert(player_addr, code.clone());
+            let words = server_state.words_for(game_manager.locale);
+            for (player, message) in game_manager.add_spectator(player_addr, words) {
+                let Some(socket) = player.socket else {
+                    continue;
                 };
+                _ = server_state.send_to_player(&socket, message);
+            }
+        }
+        StopSpectating => {
+            server_state.stop_spectating(&player_addr);
+        }
+        FindMatch {
+            player_token,
+            player_name,
+            effective_day,
+            rules_generation,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token) else {
+                return player_err(ServerError::LoginRequired);
+            };
+            if rules_generation.is_some_and(|gen| gen > GameRules::latest(None).0) {
+                return player_err(ServerError::UnknownRules);
+            }
+            let rating = match history::get_rating(&server_state, &authed).await {
+                Ok(rating) => rating,
+                Err(TruncateServerError::DatabaseOffline) => ratings::DEFAULT_RATING,
+                Err(e) => {
+                    eprintln!("Errored loading rating for matchmaking: {e}\n{e:?}");
+                    ratings::DEFAULT_RATING
+                }
+            };
 
-                for player in &game_manager.players {
-                    let Some(socket) = player.socket else {
-                        continue;
-                    };
-                    server_state
-                        .send_to_player(
-                            &socket,
-                            GameMessage::LobbyUpdate(
-                                player_index as u64,
-                                game_manager.game_id.clone(),
-                                player_list.clone(),
-                                board.clone(),
-                            ),
-                        )
-                        .unwrap();
+            let ticket = MatchTicket {
+                player_id: authed.player(),
+                socket: player_addr,
+                player_name,
+                effective_day,
+                rating,
+                rules_generation,
+                locale: connection_info_mutex.lock().locale,
+                queued_at: truncate_core::game::now(),
+            };
+            let gives_up_at = ticket.gives_up_at();
+
+            let paired = server_state
+                .match_queue
+                .lock()
+                .enqueue(ticket, truncate_core::game::now());
+            match paired {
+                Some((first, second)) => start_match(&server_state, first, second).await,
+                None => {
+                    _ = server_state.send_to_player(
+                        &player_addr,
+                        GameMessage::Matchmaking(MatchmakingStatus::Searching { gives_up_at }),
+                    );
                 }
-            } else {
-                todo!("Handle player not being enrolled in a game");
             }
         }
-        EditName(name) => {
+        CancelMatch => {
+            if server_state.match_queue.lock().cancel(player_addr) {
+                _ = server_state.send_to_player(
+                    &player_addr,
+                    GameMessage::Matchmaking(MatchmakingStatus::Cancelled),
+                );
+            }
+        }
+        EditBoard(board) => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let mut game_manager = existing_game.lock();
-                if game_manager.rename_player(player_addr, name).is_ok() {
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    game_manager.edit_board(board.clone());
                     let player_list: Vec<_> = game_manager
                         .core_game
                         .players
@@ -468,28 +863,102 @@ async fn handle_player_msg(
                         .collect();
 
                     let Some(player_index) = game_manager.get_player_index(player_addr) else {
-                        unreachable!("Player just renamed themselves");
+                        return player_err(ServerError::PlayerNotFound);
                     };
 
                     for player in &game_manager.players {
                         let Some(socket) = player.socket else {
                             continue;
                         };
-                        server_state
-                            .send_to_player(
+                        _ = server_state.send_to_player(
+                            &socket,
+                            GameMessage::LobbyUpdate(
+                                player_index as u64,
+                                game_manager.game_id.clone(),
+                                player_list.clone(),
+                                board.clone(),
+                                game_manager.rule_settings,
+                            ),
+                        );
+                    }
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
+            } else {
+                return player_err(ServerError::NotInGame);
+            }
+        }
+        EditName(name) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    if game_manager.rename_player(player_addr, name).is_ok() {
+                        let player_list: Vec<_> = game_manager
+                            .core_game
+                            .players
+                            .iter()
+                            .map(|p| LobbyPlayerMessage {
+                                name: p.name.clone(),
+                                index: p.index,
+                                color: p.color,
+                            })
+                            .collect();
+
+                        let Some(player_index) = game_manager.get_player_index(player_addr) else {
+                            unreachable!("Player just renamed themselves");
+                        };
+
+                        for player in &game_manager.players {
+                            let Some(socket) = player.socket else {
+                                continue;
+                            };
+                            _ = server_state.send_to_player(
                                 &socket,
                                 GameMessage::LobbyUpdate(
                                     player_index as u64,
                                     game_manager.game_id.clone(),
                                     player_list.clone(),
                                     game_manager.core_game.board.clone(),
+                                    game_manager.rule_settings,
                                 ),
-                            )
-                            .unwrap();
+                            );
+                        }
                     }
-                }
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame);
+            }
+        }
+        SetRules(settings) => {
+            if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    if let Err(e) = game_manager.set_rules(player_addr, settings) {
+                        return player_err(e);
+                    }
+
+                    for player in &game_manager.players {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
+                        _ = server_state.send_to_player(
+                            &socket,
+                            GameMessage::LobbyUpdate(
+                                0,
+                                game_manager.game_id.clone(),
+                                game_manager.player_list(),
+                                game_manager.core_game.board.clone(),
+                                game_manager.rule_settings,
+                            ),
+                        );
+                    }
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
+            } else {
+                return player_err(ServerError::NotInLobby);
             }
         }
         StartGame => {
@@ -497,77 +966,108 @@ async fn handle_player_msg(
                 let connection_player = connection_info_mutex.lock().player.clone();
                 _ = create_event(&server_state, &"start_game".into(), connection_player).await;
 
-                let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.start() {
-                    let Some(socket) = player.socket else {
-                        continue;
-                    };
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    // The board may have been edited since the rules were picked
+                    let board = &game_manager.core_game.board;
+                    if let Err(e) = game_manager.rule_settings.validate(board) {
+                        return player_err(e);
+                    }
+                    for (player, message) in game_manager.start() {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
 
-                    let room_code = game_manager.game_id.clone();
-
-                    match &game_manager.core_game.rules.timing {
-                        truncate_core::rules::Timing::Periodic {
-                            total_time_allowance,
-                            ..
-                        } => {
-                            tokio::spawn(check_game_over(
-                                room_code,
-                                (*total_time_allowance + 1) as i128 * 1000,
-                                server_state.clone(),
-                            ));
-                        }
-                        _ => {}
-                    };
+                        let room_code = game_manager.game_id.clone();
 
-                    server_state.send_to_player(&socket, message).unwrap();
-                }
+                        match &game_manager.core_game.rules.timing {
+                            truncate_core::rules::Timing::Periodic {
+                                total_time_allowance,
+                                ..
+                            } => {
+                                tokio::spawn(check_game_over(
+                                    room_code,
+                                    (*total_time_allowance + 1) as i128 * 1000,
+                                    server_state.clone(),
+                                ));
+                            }
+                            _ => {}
+                        };
+
+                        _ = server_state.send_to_player(&socket, message);
+                    }
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame);
             }
         }
         Resign => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.resign(player_addr) {
-                    let Some(socket) = player.socket else {
-                        continue;
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    let messages = match game_manager.resign(player_addr) {
+                        Ok(messages) => messages,
+                        Err(e) => return player_err(e),
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
-                }
+                    for (player, message) in messages {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
+                        _ = server_state.send_to_player(&socket, message);
+                    }
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame);
             }
         }
         Place(position, tile) => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let mut game_manager = existing_game.lock();
-                for (player, message) in
-                    game_manager.play(player_addr, position, tile, server_state.words())
-                {
-                    let Some(socket) = player.socket else {
-                        continue;
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    let words = server_state.words_for(game_manager.locale);
+                    let messages = match game_manager.play(player_addr, position, tile, words) {
+                        Ok(messages) => messages,
+                        Err(e) => return player_err(e),
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
-                }
-                // TODO: Error handling flow
+                    for (player, message) in messages {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
+                        _ = server_state.send_to_player(&socket, message);
+                    }
+                    // TODO: Error handling flow
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame);
             }
         }
         Swap(from, to) => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let mut game_manager = existing_game.lock();
-                for (player, message) in
-                    game_manager.swap(player_addr, from, to, server_state.words())
-                {
-                    let Some(socket) = player.socket else {
-                        continue;
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    let words = server_state.words_for(game_manager.locale);
+                    let messages = match game_manager.swap(player_addr, from, to, words) {
+                        Ok(messages) => messages,
+                        Err(e) => return player_err(e),
                     };
-                    server_state.send_to_player(&socket, message).unwrap();
-                }
-                // TODO: Error handling flow
+                    for (player, message) in messages {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
+                        _ = server_state.send_to_player(&socket, message);
+                    }
+                    // TODO: Error handling flow
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame);
             }
         }
         Rematch => {
@@ -575,102 +1075,138 @@ async fn handle_player_msg(
                 let connection_player = connection_info_mutex.lock().player.clone();
                 _ = create_event(&server_state, &"rematch".into(), connection_player).await;
 
-                let mut existing_game_manager = existing_game.lock();
-                if existing_game_manager.core_game.winner.is_none() {
-                    return player_err("Cannot rematch unfinished game".into());
-                } else {
-                    let new_game_id = server_state.game_code();
-                    let mut new_game =
-                        GameManager::new(new_game_id.clone(), existing_game_manager.effective_day);
-
-                    let mut next_board = existing_game_manager.core_game.board.clone();
-                    next_board.reset();
-                    new_game.core_game.board = next_board;
-
-                    let mut next_sockets = existing_game_manager.players.clone();
-                    next_sockets.rotate_left(1);
-                    existing_game_manager.players = vec![];
-
-                    let mut next_players = existing_game_manager.core_game.players.clone();
-                    next_players.rotate_left(1);
-                    for (i, player) in next_players.into_iter().enumerate() {
-                        new_game
-                            .add_player(
-                                next_sockets
-                                    .get(i)
-                                    .expect("All players rejoining have a socket")
-                                    .clone(),
-                                player.name,
-                            )
-                            .expect("Failed to add player to game");
-                    }
-
-                    drop(existing_game_manager); // Done with the old game, don't accidentally use it.
-
-                    let new_game = server_state.add_new_game(&new_game_id, new_game);
-                    let new_game_manager = new_game.lock();
-
-                    for (i, player) in new_game_manager.players.iter().enumerate() {
-                        let Some(socket) = player.socket else {
-                            continue;
-                        };
-
-                        server_state.attach_player_to_game(&socket, &new_game_id);
-
-                        let claims = Claims::with_custom_claims(
-                            PlayerClaims {
-                                player_index: i,
-                                room_code: new_game_id.clone(),
-                            },
-                            Duration::from_days(7), // TODO: Determine game expiration time
+                let snapshot = {
+                    let mut existing_game_manager = existing_game.lock();
+                    if existing_game_manager.core_game.winner.is_none() {
+                        return player_err(ServerError::GameUnfinished);
+                    } else {
+                        let new_game_id = server_state.game_code();
+                        let mut new_game = GameManager::new(
+                            new_game_id.clone(),
+                            existing_game_manager.effective_day,
                         );
-                        let token = server_state
-                            .jwt_key
-                            .authenticate(claims)
-                            .expect("Claims should be serializable");
+                        new_game.locale = existing_game_manager.locale;
+
+                        let mut next_board = existing_game_manager.core_game.board.clone();
+                        next_board.reset();
+                        new_game.core_game.board = next_board;
+                        new_game.rule_settings = existing_game_manager.rule_settings;
+
+                        let mut next_sockets = existing_game_manager.players.clone();
+                        next_sockets.rotate_left(1);
+                        existing_game_manager.players = vec![];
+
+                        let mut next_players = existing_game_manager.core_game.players.clone();
+                        next_players.rotate_left(1);
+                        for (i, player) in next_players.into_iter().enumerate() {
+                            new_game
+                                .add_player(
+                                    next_sockets
+                                        .get(i)
+                                        .expect("All players rejoining have a socket")
+                                        .clone(),
+                                    player.name,
+                                )
+                                .expect("Failed to add player to game");
+                        }
 
-                        server_state
-                            .send_to_player(
+                        // Spectators follow the players into the rematch
+                        new_game.spectators = std::mem::take(&mut existing_game_manager.spectators);
+                        let mut spectating = server_state.spectating.lock();
+                        for spectator in new_game.spectators.iter().filter_map(|s| s.socket) {
+                            spectating.insert(spectator, new_game_id.clone());
+                        }
+                        drop(spectating);
+
+                        drop(existing_game_manager); // Done with the old game, don't accidentally use it.
+
+                        let new_game = server_state.add_new_game(&new_game_id, new_game);
+                        let mut new_game_manager = new_game.lock();
+
+                        for (i, player) in new_game_manager.players.iter().enumerate() {
+                            let Some(socket) = player.socket else {
+                                continue;
+                            };
+
+                            server_state.attach_player_to_game(&socket, &new_game_id);
+
+                            let claims = Claims::with_custom_claims(
+                                PlayerClaims {
+                                    player_index: i,
+                                    room_code: new_game_id.clone(),
+                                    instance_id: new_game_manager.instance_id,
+                                },
+                                Duration::from_days(7), // TODO: Determine game expiration time
+                            );
+                            let token = server_state
+                                .jwt_key
+                                .authenticate(claims)
+                                .expect("Claims should be serializable");
+
+                            _ = server_state.send_to_player(
                                 &socket,
                                 GameMessage::JoinedLobby(
                                     i as u64,
                                     new_game_id.clone(),
                                     new_game_manager.player_list(),
                                     new_game_manager.core_game.board.clone(),
+                                    new_game_manager.rule_settings,
                                     token,
                                 ),
-                            )
-                            .unwrap();
+                            );
+                        }
+
+                        if !new_game_manager.spectators.is_empty() {
+                            for (player, message) in new_game_manager.spectators_update() {
+                                let Some(socket) = player.socket else {
+                                    continue;
+                                };
+                                _ = server_state.send_to_player(&socket, message);
+                            }
+                        }
+
+                        new_game_manager.snapshot()
                     }
-                }
+                };
+                server_state.persist_game(snapshot).await;
             }
         }
         Pause => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.pause(server_state.words()) {
-                    let Some(socket) = player.socket else {
-                        continue;
-                    };
-                    server_state.send_to_player(&socket, message).unwrap();
-                }
-                // TODO: Error handling flow
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    let words = server_state.words_for(game_manager.locale);
+                    for (player, message) in game_manager.pause(words) {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
+                        _ = server_state.send_to_player(&socket, message);
+                    }
+                    // TODO: Error handling flow
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame);
             }
         }
         Unpause => {
             if let Some(existing_game) = server_state.get_game_by_player(&player_addr) {
-                let mut game_manager = existing_game.lock();
-                for (player, message) in game_manager.unpause(server_state.words()) {
-                    let Some(socket) = player.socket else {
-                        continue;
-                    };
-                    server_state.send_to_player(&socket, message).unwrap();
-                }
-                // TODO: Error handling flow
+                let snapshot = {
+                    let mut game_manager = existing_game.lock();
+                    let words = server_state.words_for(game_manager.locale);
+                    for (player, message) in game_manager.unpause(words) {
+                        let Some(socket) = player.socket else {
+                            continue;
+                        };
+                        _ = server_state.send_to_player(&socket, message);
+                    }
+                    // TODO: Error handling flow
+                    game_manager.snapshot()
+                };
+                server_state.persist_game(snapshot).await;
             } else {
-                todo!("Handle player not being enrolled in a game");
+                return player_err(ServerError::NotInGame);
             }
         }
         RequestDefinitions(words) => {
@@ -682,9 +1218,8 @@ async fn handle_player_msg(
             // Don't hold the lock while sending messages
             drop(word_db);
 
-            server_state
-                .send_to_player(&player_addr, GameMessage::SupplyDefinitions(definitions))
-                .unwrap();
+            _ = server_state
+                .send_to_player(&player_addr, GameMessage::SupplyDefinitions(definitions));
         }
         CreateAnonymousPlayer {
             screen_width,
@@ -692,6 +1227,7 @@ async fn handle_player_msg(
             user_agent,
             referrer,
             unread_changelogs,
+            locale,
         } => match accounts::create_player(
             &server_state,
             screen_width,
@@ -713,19 +1249,20 @@ async fn handle_player_msg(
 
                 let mut connection_info = connection_info_mutex.lock();
                 connection_info.player = Some(authed_token.clone());
+                connection_info.locale = locale;
 
-                server_state
-                    .send_to_player(
-                        &player_addr,
-                        GameMessage::LoggedInAs {
-                            token: authed_token.token(),
-                            unread_changelogs: vec![],
-                        },
-                    )
-                    .unwrap();
+                _ = server_state.send_to_player(
+                    &player_addr,
+                    GameMessage::LoggedInAs {
+                        token: authed_token.token(),
+                        player_name: None,
+                        unread_changelogs: vec![],
+                    },
+                );
             }
-            Err(_) => {
-                todo!("Error handling for database actions");
+            Err(e) => {
+                eprintln!("Errored creating an anonymous player: {e}");
+                return player_err(e.into());
             }
         },
         Login {
@@ -734,6 +1271,7 @@ async fn handle_player_msg(
             screen_height,
             user_agent,
             referrer: _,
+            locale,
         } => match accounts::login(
             &server_state,
             player_token.clone(),
@@ -744,116 +1282,205 @@ async fn handle_player_msg(
         .await
         {
             Ok(LoginResponse {
-                player_id: _,
                 authed,
+                player_name,
                 unread_changelogs,
             }) => {
                 let mut connection_info = connection_info_mutex.lock();
                 connection_info.player = Some(authed);
+                connection_info.locale = locale;
+
+                _ = server_state.send_to_player(
+                    &player_addr,
+                    GameMessage::LoggedInAs {
+                        token: player_token,
+                        player_name,
+                        unread_changelogs: unread_changelogs
+                            .into_iter()
+                            .map(|c| c.changelog_id)
+                            .collect(),
+                    },
+                );
+            }
+            Err(e) => {
+                eprintln!("Player failed to log in: {e}");
+                return player_err(e.into());
+            }
+        },
+        ClaimName {
+            player_token,
+            player_name,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token) else {
+                return player_err(ServerError::InvalidToken);
+            };
+
+            match accounts::claim_name(&server_state, authed, player_name).await {
+                Ok(name) => {
+                    _ = server_state.send_to_player(&player_addr, GameMessage::NameClaimed(name));
+                }
+                Err(e) => return player_err(e.into()),
+            }
+        }
+        RequestLoginLink {
+            player_token,
+            email,
+        } => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, player_token) else {
+                return player_err(ServerError::InvalidToken);
+            };
+
+            match accounts::request_login_link(&server_state, authed, email.clone()).await {
+                Ok(()) => {
+                    _ = server_state
+                        .send_to_player(&player_addr, GameMessage::LoginLinkSent(email));
+                }
+                Err(e) => return player_err(e.into()),
+            }
+        }
+        RedeemLoginLink(link_token) => {
+            let redeemer = connection_info_mutex.lock().player.clone();
+
+            match accounts::redeem_login_link(&server_state, redeemer, link_token).await {
+                Ok(LoginResponse {
+                    authed,
+                    player_name,
+                    unread_changelogs,
+                }) => {
+                    connection_info_mutex.lock().player = Some(authed.clone());
 
-                server_state
-                    .send_to_player(
+                    _ = server_state.send_to_player(
                         &player_addr,
                         GameMessage::LoggedInAs {
-                            token: player_token,
+                            token: authed.token(),
+                            player_name,
                             unread_changelogs: unread_changelogs
                                 .into_iter()
                                 .map(|c| c.changelog_id)
                                 .collect(),
                         },
-                    )
-                    .unwrap();
-            }
-            Err(_e) => {
-                eprintln!(
-                    "Player tried to login with a bad token and failed ! ! ! ! ! ! ! ! ! ! !"
-                );
-                return player_err("Invalid Token".into());
+                    );
+                }
+                Err(e) => return player_err(e.into()),
             }
-        },
+        }
         LoadDailyPuzzle(token, day) => {
             let Ok(authed) = accounts::auth_player_token(&server_state, token) else {
-                return player_err("Invalid Token".into());
+                return player_err(ServerError::InvalidToken);
             };
 
             if let Ok(Some((puzzle, best))) =
                 daily::load_attempt(&server_state, authed, day as i32).await
             {
-                server_state
-                    .send_to_player(&player_addr, GameMessage::ResumeDailyPuzzle(puzzle, best))
-                    .unwrap();
+                _ = server_state
+                    .send_to_player(&player_addr, GameMessage::ResumeDailyPuzzle(puzzle, best));
             } else {
-                server_state
-                    .send_to_player(
-                        &player_addr,
-                        GameMessage::ResumeDailyPuzzle(
-                            DailyStateMessage {
-                                puzzle_day: day,
-                                attempt: 0,
-                                current_moves: vec![],
-                            },
-                            None,
-                        ),
-                    )
-                    .unwrap();
+                _ = server_state.send_to_player(
+                    &player_addr,
+                    GameMessage::ResumeDailyPuzzle(
+                        DailyStateMessage {
+                            puzzle_day: day,
+                            attempt: 0,
+                            current_moves: vec![],
+                            hints: 0,
+                            takebacks: 0,
+                        },
+                        None,
+                    ),
+                );
             }
         }
         LoadReplay(id) => {
             let connection_player = connection_info_mutex.lock().player.clone();
-            _ = create_event(&server_state, &"load_replay".into(), connection_player).await;
+            _ = create_event(
+                &server_state,
+                &"load_replay".into(),
+                connection_player.clone(),
+            )
+            .await;
 
             let Ok(uuid) = Uuid::parse_str(&id) else {
-                return player_err("Invalid Replay ID".into());
+                return player_err(ServerError::InvalidReplayId);
             };
 
             if let Ok(Some(puzzle)) = daily::load_exact_attempt(&server_state, uuid).await {
-                server_state
-                    .send_to_player(&player_addr, GameMessage::LoadDailyReplay(puzzle))
-                    .unwrap();
+                _ = server_state.send_to_player(&player_addr, GameMessage::LoadDailyReplay(puzzle));
+            } else if let Ok(Some(replay)) =
+                history::load_replay(&server_state, uuid, connection_player).await
+            {
+                _ = server_state.send_to_player(
+                    &player_addr,
+                    GameMessage::LoadMultiplayerReplay(Box::new(replay)),
+                );
             } else {
-                return player_err("Replay does not exist".into());
+                return player_err(ServerError::ReplayNotFound);
             }
         }
         PersistPuzzleMoves {
             player_token,
             day,
+            attempt,
             human_player,
             moves,
             won,
+            hints,
+            takebacks,
         } => {
             let Ok(authed) = accounts::auth_player_token(&server_state, player_token) else {
-                return player_err("Invalid Token".into());
+                return player_err(ServerError::InvalidToken);
             };
 
-            if let Err(e) = daily::persist_moves(
+            match daily::persist_moves(
                 &server_state,
                 authed,
                 day as i32,
+                attempt.map(|a| a as i32),
                 human_player as i32,
                 moves,
                 won,
+                hints,
+                takebacks,
             )
             .await
             {
-                eprintln!("Errored persisting daily game moves: {e}\n{e:?}");
+                Ok(()) => {
+                    _ = server_state
+                        .send_to_player(&player_addr, GameMessage::DailyMovesSaved(day, attempt));
+                }
+                Err(e) => {
+                    eprintln!("Errored persisting daily game moves: {e}\n{e:?}");
+                }
             }
         }
         RequestStats(token) => {
             let Ok(authed) = accounts::auth_player_token(&server_state, token) else {
-                return player_err("Invalid Token".into());
+                return player_err(ServerError::InvalidToken);
             };
 
             match daily::load_stats(&server_state, authed).await {
                 Ok(stats) => {
-                    server_state
-                        .send_to_player(&player_addr, GameMessage::DailyStats(stats))
-                        .unwrap();
+                    _ = server_state.send_to_player(&player_addr, GameMessage::DailyStats(stats));
                 }
                 Err(e) => {
                     eprintln!("Errored loading stats for player: {e}\n{e:?}");
                 }
             }
         }
+        RequestMatchHistory(token) => {
+            let Ok(authed) = accounts::auth_player_token(&server_state, token) else {
+                return player_err(ServerError::InvalidToken);
+            };
+
+            match history::load_match_history(&server_state, authed).await {
+                Ok(history) => {
+                    _ = server_state
+                        .send_to_player(&player_addr, GameMessage::MatchHistory(history));
+                }
+                Err(e) => {
+                    eprintln!("Errored loading match history for player: {e}\n{e:?}");
+                }
+            }
+        }
         MarkChangelogRead(id) => {
             let Some(connection_player) = connection_info_mutex.lock().player.clone() else {
                 eprintln!(
@@ -876,6 +1503,8 @@ async fn handle_player_msg(
 #[derive(Default)]
 struct ConnectionInfo {
     player: Option<AuthedTruncateToken>,
+    /// The language the player asked for when logging in
+    locale: Locale,
 }
 
 async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, addr: SocketAddr) {
@@ -892,7 +1521,8 @@ async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, add
 
     // TODO: try_for_each from TryStreamExt is quite nice,
     // look to bring that trait to the other stream places
-    let handle_player_msg = incoming.try_for_each(|msg| {
+    // Socket errors are boxed, as they're large and rarely happen
+    let handle_player_msg = incoming.map_err(Box::new).try_for_each(|msg| {
         handle_player_msg(msg, addr, server_state.clone(), connection_info.clone())
     });
 
@@ -941,8 +1571,7 @@ async fn handle_connection(server_state: ServerState, raw_stream: TcpStream, add
     pin_mut!(handle_player_msg, messages_to_player);
     future::select(handle_player_msg, messages_to_player).await;
 
-    let mut peer_map = server_state.peers.lock();
-    peer_map.remove(&addr);
+    server_state.drop_player(&addr).await;
 }
 
 async fn check_game_over(game_id: String, check_in_ms: i128, server_state: ServerState) {
@@ -951,16 +1580,20 @@ async fn check_game_over(game_id: String, check_in_ms: i128, server_state: Serve
     }
     tokio::time::sleep(Duration::from_millis(check_in_ms as u64 + 10).into()).await;
 
-    let mut game_map = server_state.games.lock();
-    let Some(existing_game) = game_map.get_mut(&game_id) else {
-        return;
-    };
-    let mut game_manager = existing_game.lock();
-    game_manager.core_game.calculate_game_over(None);
+    let snapshot = {
+        let mut game_map = server_state.games.lock();
+        let Some(existing_game) = game_map.get_mut(&game_id) else {
+            return;
+        };
+        let mut game_manager = existing_game.lock();
+        game_manager.core_game.calculate_game_over(None);
 
-    let words_db = server_state.words();
+        let words_db = server_state.words_for(game_manager.locale);
+
+        let Some(winner) = game_manager.core_game.winner else {
+            return;
+        };
 
-    if let Some(winner) = game_manager.core_game.winner {
         for (player_index, player) in game_manager.players.iter().enumerate() {
             let Some(socket) = player.socket else {
                 continue;
@@ -968,10 +1601,160 @@ async fn check_game_over(game_id: String, check_in_ms: i128, server_state: Serve
             let mut end_game_msg = game_manager.game_msg(player_index, Some(&words_db.lock()));
             // Don't send any of the latest battles or hand changes
             end_game_msg.changes = vec![];
-            server_state
-                .send_to_player(&socket, GameMessage::GameEnd(end_game_msg, winner as u64))
-                .unwrap();
+            _ = server_state
+                .send_to_player(&socket, GameMessage::GameEnd(end_game_msg, winner as u64));
+        }
+
+        let spectator_messages =
+            game_manager.spectator_messages(Some(&words_db.lock()), |mut end_game_msg| {
+                end_game_msg.changes = vec![];
+                GameMessage::GameEnd(end_game_msg, winner as u64)
+            });
+        for (spectator, message) in spectator_messages {
+            let Some(socket) = spectator.socket else {
+                continue;
+            };
+            _ = server_state.send_to_player(&socket, message);
+        }
+
+        game_manager.snapshot()
+    };
+
+    server_state.persist_game(snapshot).await;
+}
+
+async fn check_abandonment(game_id: String, player_index: usize, server_state: ServerState) {
+    tokio::time::sleep(server_state.abandonment_timeout).await;
+
+    let snapshot = {
+        let Some(existing_game) = server_state.get_game_by_code(&game_id) else {
+            return;
+        };
+        let mut game_manager = existing_game.lock();
+
+        let messages = game_manager.abandon(player_index);
+        if messages.is_empty() {
+            return;
+        }
+        println!("Player {player_index} abandoned room {game_id}");
+
+        for (player, message) in messages {
+            let Some(socket) = player.socket else {
+                continue;
+            };
+            _ = server_state.send_to_player(&socket, message);
         }
+
+        game_manager.snapshot()
+    };
+
+    server_state.persist_game(snapshot).await;
+}
+
+/// Puts two matched players straight into a started game
+async fn start_match(server_state: &ServerState, first: MatchTicket, second: MatchTicket) {
+    let game_id = server_state.game_code();
+    let generation = first
+        .agreed_rules(&second)
+        .unwrap_or_else(|| GameRules::latest(Some(first.effective_day)).0);
+    let mut game = GameManager::with_rules(
+        game_id.clone(),
+        first.effective_day,
+        GameRules::generation(generation),
+    );
+    // Tickets only match when their locales agree
+    game.locale = first.locale;
+
+    for ticket in [&first, &second] {
+        let player = Player::new(ticket.socket, Some(ticket.player_id));
+        game.add_player(player, ticket.player_name.clone())
+            .expect("Failed to add matched player to game");
+    }
+    println!(
+        "Matched {} and {} in room {game_id}",
+        first.player_id, second.player_id
+    );
+
+    let game = server_state.add_new_game(&game_id, game);
+    let snapshot = {
+        let mut game_manager = game.lock();
+
+        for (player_index, ticket) in [&first, &second].into_iter().enumerate() {
+            server_state.attach_player_to_game(&ticket.socket, &game_id);
+
+            let claims = Claims::with_custom_claims(
+                PlayerClaims {
+                    player_index,
+                    room_code: game_id.clone(),
+                    instance_id: game_manager.instance_id,
+                },
+                Duration::from_days(7), // TODO: Determine game expiration time
+            );
+            let token = server_state
+                .jwt_key
+                .authenticate(claims)
+                .expect("Claims should be serializable");
+
+            // Players receive the lobby first so they hold a token to rejoin with
+            _ = server_state.send_to_player(
+                &ticket.socket,
+                GameMessage::JoinedLobby(
+                    player_index as u64,
+                    game_id.clone(),
+                    game_manager.player_list(),
+                    game_manager.core_game.board.clone(),
+                    game_manager.rule_settings,
+                    token,
+                ),
+            );
+        }
+
+        for (player, message) in game_manager.start() {
+            let Some(socket) = player.socket else {
+                continue;
+            };
+            _ = server_state.send_to_player(&socket, message);
+        }
+
+        game_manager.snapshot()
+    };
+
+    server_state.persist_game(snapshot).await;
+}
+
+async fn run_matchmaking(server_state: ServerState) {
+    loop {
+        // Widen everyone's search every couple of seconds
+        tokio::time::sleep(Duration::from_secs(2).into()).await;
+
+        let (matches, timed_out) = server_state
+            .match_queue
+            .lock()
+            .sweep(truncate_core::game::now());
+
+        for ticket in timed_out {
+            _ = server_state.send_to_player(
+                &ticket.socket,
+                GameMessage::Matchmaking(MatchmakingStatus::TimedOut),
+            );
+        }
+
+        for (first, second) in matches {
+            start_match(&server_state, first, second).await;
+        }
+    }
+}
+
+async fn reap_games(server_state: ServerState) {
+    loop {
+        // Expire old games every five minutes
+        tokio::time::sleep(Duration::from_mins(5).into()).await;
+
+        let expired = server_state.reap_games().await;
+        if !expired.is_empty() {
+            println!("Expired {} games: {}", expired.len(), expired.join(", "));
+        }
+        println!("Server holds {}", server_state.stats());
     }
 }
 
@@ -980,19 +1763,38 @@ async fn clean_nonces(server_state: ServerState) {
         // Clean all old nonces every five minutes
         tokio::time::sleep(Duration::from_mins(5).into()).await;
 
-        let mut nonce_manager = server_state.nonces.lock();
-        nonce_manager.cleanup(90);
+        server_state.nonces.lock().cleanup(90);
+
+        if server_state.truncate_db.is_some() {
+            if let Err(e) = nonces::cleanup_nonces(&server_state, 90).await {
+                eprintln!("Errored cleaning up nonces: {e}");
+            }
+        }
     }
 }
 
+/// How long a peer can go without answering our pings before we drop them
+const SILENT_PEER_SECS: u64 = 20;
+
 async fn ping_peers(server_state: ServerState) {
     loop {
         // Ping all clients every five seconds
         tokio::time::sleep(Duration::from_secs(5).into()).await;
         let mut bad_peers = vec![];
         let mut peer_map = server_state.peers.lock();
+        let last_seen = server_state.last_seen.lock();
+        let silent_since = truncate_core::game::now().saturating_sub(SILENT_PEER_SECS);
         let all_peers = peer_map.iter();
         for (peer_key, peer_tx) in all_peers {
+            // Clients answer our pings, so a silent peer has lost its connection
+            // even if the socket hasn't been closed yet.
+            if last_seen
+                .get(peer_key)
+                .is_some_and(|seen| *seen < silent_since)
+            {
+                bad_peers.push(*peer_key);
+                continue;
+            }
             match peer_tx.send(GameMessage::Ping) {
                 Ok(()) => {}
                 Err(_) => {
@@ -1000,6 +1802,7 @@ async fn ping_peers(server_state: ServerState) {
                 }
             }
         }
+        // Dropping the sender closes the connection, which then reports the disconnect
         for bad_peer in bad_peers {
             peer_map.remove(&bad_peer);
         }
@@ -1027,14 +1830,35 @@ async fn main() -> Result<(), IoError> {
         k
     };
 
+    let abandonment_timeout = std::time::Duration::from_secs(
+        env::var("ABANDONMENT_TIMEOUT_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(120),
+    );
+
+    let mail_dir = env::var("MAIL_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| env::temp_dir().join("truncate_mail"));
+    println!("Writing outgoing mail to {}", mail_dir.display());
+    let mailer: Arc<dyn Mailer> = Arc::new(FileMailer::new(mail_dir));
+
+    let public_url = env::var("PUBLIC_URL").unwrap_or_else(|_| "https://truncate.town".to_string());
+
     let mut server_state = ServerState {
         games: Arc::new(Mutex::new(HashMap::new())),
         assignments: Arc::new(Mutex::new(HashMap::new())),
         peers: Arc::new(Mutex::new(HashMap::new())),
-        word_db: Arc::new(Mutex::new(read_defs())),
+        match_queue: Arc::new(Mutex::new(MatchQueue::default())),
+        spectating: Arc::new(Mutex::new(HashMap::new())),
+        last_seen: Arc::new(Mutex::new(HashMap::new())),
+        abandonment_timeout,
+        word_db: Arc::new(Mutex::new(read_defs(Locale::default()))),
         nonces: Arc::new(Mutex::new(NonceTracker::default())),
         truncate_db: None,
         jwt_key,
+        mailer,
+        public_url,
     };
 
     if let Ok(db_url) = env::var("DATABASE_URL") {
@@ -1055,6 +1879,16 @@ async fn main() -> Result<(), IoError> {
 
         server_state.truncate_db = Some(pool);
 
+        match games::load_live_games(&server_state).await {
+            Ok(snapshots) => {
+                println!("Restoring {} live games", snapshots.len());
+                for snapshot in snapshots {
+                    server_state.restore_game(snapshot);
+                }
+            }
+            Err(e) => eprintln!("Errored restoring live games: {e}\n{e:?}"),
+        }
+
         println!("Database is ready.");
     } else {
         println!("Running the Truncate server without a database connection.");
@@ -1066,6 +1900,8 @@ async fn main() -> Result<(), IoError> {
 
     tokio::spawn(ping_peers(server_state.clone()));
     tokio::spawn(clean_nonces(server_state.clone()));
+    tokio::spawn(reap_games(server_state.clone()));
+    tokio::spawn(run_matchmaking(server_state.clone()));
 
     std::thread::spawn(move || loop {
         std::thread::sleep(std::time::Duration::from_secs(10));
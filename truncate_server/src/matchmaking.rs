@@ -0,0 +1,241 @@
+use std::net::SocketAddr;
+
+use truncate_core::locale::Locale;
+use uuid::Uuid;
+
+/// How long a player waits for an opponent before we offer them a computer opponent
+pub const MATCHMAKING_TIMEOUT_SECS: u64 = 60;
+
+/// Rating difference that is always acceptable, which widens the longer players wait
+const BASE_RATING_WINDOW: i32 = 100;
+const RATING_WINDOW_GROWTH_PER_SEC: i32 = 10;
+
+#[derive(Debug, Clone)]
+pub struct MatchTicket {
+    pub player_id: Uuid,
+    pub socket: SocketAddr,
+    pub player_name: String,
+    pub effective_day: u32,
+    pub rating: i32,
+    pub rules_generation: Option<u32>,
+    /// Players are only matched with others playing in the same language
+    pub locale: Locale,
+    pub queued_at: u64,
+}
+
+impl MatchTicket {
+    pub fn gives_up_at(&self) -> u64 {
+        self.queued_at + MATCHMAKING_TIMEOUT_SECS
+    }
+
+    fn rating_window(&self, now: u64) -> i32 {
+        let waited = now
+            .saturating_sub(self.queued_at)
+            .min(MATCHMAKING_TIMEOUT_SECS) as i32;
+        BASE_RATING_WINDOW + waited * RATING_WINDOW_GROWTH_PER_SEC
+    }
+
+    fn accepts(&self, other: &MatchTicket, now: u64) -> bool {
+        let rules_agree = match (self.rules_generation, other.rules_generation) {
+            (Some(ours), Some(theirs)) => ours == theirs,
+            _ => true,
+        };
+
+        // Whoever has waited longest decides how far apart the ratings can be
+        let window = self.rating_window(now).max(other.rating_window(now));
+
+        self.player_id != other.player_id
+            && self.locale == other.locale
+            && rules_agree
+            && (self.rating - other.rating).abs() <= window
+    }
+
+    /// The rules both players asked for, if either had a preference
+    pub fn agreed_rules(&self, other: &MatchTicket) -> Option<u32> {
+        self.rules_generation.or(other.rules_generation)
+    }
+}
+
+#[derive(Default)]
+pub struct MatchQueue {
+    tickets: Vec<MatchTicket>,
+}
+
+impl MatchQueue {
+    pub fn len(&self) -> usize {
+        self.tickets.len()
+    }
+
+    /// Queues a player, immediately pairing them if a suitable opponent is waiting.
+    /// Queueing again replaces any earlier ticket for the same player or connection.
+    pub fn enqueue(&mut self, ticket: MatchTicket, now: u64) -> Option<(MatchTicket, MatchTicket)> {
+        self.tickets
+            .retain(|t| t.player_id != ticket.player_id && t.socket != ticket.socket);
+
+        let closest = self
+            .tickets
+            .iter()
+            .enumerate()
+            .filter(|(_, waiting)| waiting.accepts(&ticket, now))
+            .min_by_key(|(_, waiting)| (waiting.rating - ticket.rating).abs())
+            .map(|(index, _)| index);
+
+        match closest {
+            Some(index) => Some((self.tickets.remove(index), ticket)),
+            None => {
+                self.tickets.push(ticket);
+                None
+            }
+        }
+    }
+
+    /// Removes the ticket held by a connection, returning whether there was one
+    pub fn cancel(&mut self, socket: SocketAddr) -> bool {
+        let queued = self.tickets.len();
+        self.tickets.retain(|t| t.socket != socket);
+        self.tickets.len() != queued
+    }
+
+    /// Pairs up players whose rating windows have grown to overlap,
+    /// and removes everyone who has waited too long.
+    pub fn sweep(&mut self, now: u64) -> (Vec<(MatchTicket, MatchTicket)>, Vec<MatchTicket>) {
+        let (timed_out, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tickets)
+            .into_iter()
+            .partition(|t| t.gives_up_at() <= now);
+
+        // Re-queue in order, so that players who have waited longest get first pick
+        let mut matches = vec![];
+        for ticket in waiting {
+            if let Some(pair) = self.enqueue(ticket, now) {
+                matches.push(pair);
+            }
+        }
+
+        (matches, timed_out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(port: u16, rating: i32, queued_at: u64) -> MatchTicket {
+        MatchTicket {
+            player_id: Uuid::new_v4(),
+            socket: SocketAddr::from(([127, 0, 0, 1], port)),
+            player_name: format!("Player {port}"),
+            effective_day: 300,
+            rating,
+            rules_generation: None,
+            locale: Locale::English,
+            queued_at,
+        }
+    }
+
+    #[test]
+    fn rating_windows_widen_while_waiting() {
+        let waiting = ticket(1000, 1500, 0);
+        assert_eq!(waiting.rating_window(0), BASE_RATING_WINDOW);
+        assert_eq!(
+            waiting.rating_window(10),
+            BASE_RATING_WINDOW + 10 * RATING_WINDOW_GROWTH_PER_SEC
+        );
+        // The window stops growing once the player has given up
+        assert_eq!(
+            waiting.rating_window(MATCHMAKING_TIMEOUT_SECS * 10),
+            waiting.rating_window(MATCHMAKING_TIMEOUT_SECS)
+        );
+
+        let mut queue = MatchQueue::default();
+        assert!(queue.enqueue(waiting, 0).is_none());
+        assert!(queue.enqueue(ticket(1001, 1700, 0), 0).is_none());
+        assert_eq!(queue.len(), 2);
+
+        let (matches, timed_out) = queue.sweep(5);
+        assert!(matches.is_empty());
+        assert!(timed_out.is_empty());
+
+        let (matches, timed_out) = queue.sweep(10);
+        assert_eq!(matches.len(), 1);
+        assert!(timed_out.is_empty());
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn closest_rating_is_matched_first() {
+        let mut queue = MatchQueue::default();
+        queue.enqueue(ticket(1000, 1400, 0), 0);
+        queue.enqueue(ticket(1001, 1580, 0), 0);
+
+        let (waiting, arriving) = queue.enqueue(ticket(1002, 1500, 0), 0).unwrap();
+        assert_eq!(waiting.rating, 1580);
+        assert_eq!(arriving.rating, 1500);
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn rules_must_agree() {
+        let mut queue = MatchQueue::default();
+        let mut first = ticket(1000, 1500, 0);
+        first.rules_generation = Some(1);
+        queue.enqueue(first, 0);
+
+        let mut other_rules = ticket(1001, 1500, 0);
+        other_rules.rules_generation = Some(2);
+        assert!(queue.enqueue(other_rules, 0).is_none());
+        assert_eq!(queue.len(), 2);
+
+        // Players without a preference take whatever rules the other player wanted
+        let (waiting, arriving) = queue.enqueue(ticket(1002, 1500, 0), 0).unwrap();
+        assert_eq!(waiting.agreed_rules(&arriving), Some(1));
+    }
+
+    #[test]
+    fn locales_must_agree() {
+        // English is the only locale so far, so check every pairing we can make
+        for ours in Locale::ALL {
+            for theirs in Locale::ALL {
+                let mut first = ticket(1000, 1500, 0);
+                first.locale = ours;
+                let mut second = ticket(1001, 1500, 0);
+                second.locale = theirs;
+                assert_eq!(first.accepts(&second, 0), ours == theirs);
+            }
+        }
+    }
+
+    #[test]
+    fn players_are_never_matched_with_themselves() {
+        let mut queue = MatchQueue::default();
+        let first = ticket(1000, 1500, 0);
+        let player_id = first.player_id;
+        assert!(queue.enqueue(first, 0).is_none());
+
+        // Queueing again from another connection replaces the earlier ticket
+        let mut again = ticket(1001, 1500, 0);
+        again.player_id = player_id;
+        assert!(!again.accepts(&again.clone(), 0));
+        assert!(queue.enqueue(again, 0).is_none());
+        assert_eq!(queue.len(), 1);
+
+        // As does queueing again from the same connection under another account
+        assert!(queue.enqueue(ticket(1001, 1500, 0), 0).is_none());
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn tickets_time_out() {
+        let mut queue = MatchQueue::default();
+        queue.enqueue(ticket(1000, 1000, 0), 0);
+        queue.enqueue(ticket(1001, 2500, 30), 30);
+
+        let (matches, timed_out) = queue.sweep(MATCHMAKING_TIMEOUT_SECS);
+        assert!(matches.is_empty());
+        assert_eq!(timed_out.len(), 1);
+        assert_eq!(timed_out[0].rating, 1000);
+        assert_eq!(queue.len(), 1);
+
+        assert!(queue.cancel(SocketAddr::from(([127, 0, 0, 1], 1001))));
+        assert_eq!(queue.len(), 0);
+    }
+}
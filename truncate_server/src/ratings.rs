@@ -0,0 +1,56 @@
+/// Everyone starts here, matching the default of the `players.rating` column
+pub const DEFAULT_RATING: i32 = 1500;
+
+/// Ratings move quickly for new players, then settle down once they've played a few games
+const PROVISIONAL_GAMES: i32 = 10;
+const PROVISIONAL_K_FACTOR: f64 = 40.0;
+const ESTABLISHED_K_FACTOR: f64 = 20.0;
+
+/// The chance of a player beating their opponent, according to their Elo ratings
+pub fn expected_score(rating: i32, opponent_rating: i32) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) as f64 / 400.0))
+}
+
+/// A player's Elo rating after a game against a single opponent
+pub fn updated_rating(rating: i32, rated_games: i32, opponent_rating: i32, won: bool) -> i32 {
+    let k_factor = if rated_games < PROVISIONAL_GAMES {
+        PROVISIONAL_K_FACTOR
+    } else {
+        ESTABLISHED_K_FACTOR
+    };
+    let score = if won { 1.0 } else { 0.0 };
+
+    rating + (k_factor * (score - expected_score(rating, opponent_rating))).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evenly_matched_players_expect_to_draw() {
+        assert_eq!(expected_score(1500, 1500), 0.5);
+        assert!(expected_score(1700, 1500) > 0.75);
+        assert!((expected_score(1700, 1500) + expected_score(1500, 1700) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ratings_settle_after_provisional_games() {
+        // New players move by half the provisional K-factor between even opponents
+        assert_eq!(updated_rating(1500, 0, 1500, true), 1520);
+        assert_eq!(
+            updated_rating(1500, PROVISIONAL_GAMES - 1, 1500, false),
+            1480
+        );
+
+        // Then by half the established K-factor
+        assert_eq!(updated_rating(1500, PROVISIONAL_GAMES, 1500, true), 1510);
+        assert_eq!(updated_rating(1500, 100, 1500, false), 1490);
+
+        // Beating a much stronger player is worth more than beating a weaker one
+        let upset = updated_rating(1500, 100, 1900, true) - 1500;
+        let expected_win = updated_rating(1900, 100, 1500, true) - 1900;
+        assert!(upset > expected_win);
+        assert!(upset <= ESTABLISHED_K_FACTOR as i32);
+    }
+}
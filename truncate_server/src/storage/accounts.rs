@@ -1,11 +1,18 @@
 use jwt_simple::prelude::*;
 use serde::{Deserialize, Serialize};
-use sqlx::types::time;
 use truncate_core::messages::TruncateToken;
 use uuid::Uuid;
 use woothee::parser::Parser as UAParser;
 
-use crate::{errors::TruncateServerError, ServerState};
+use crate::{errors::TruncateServerError, mailer::Email, ServerState};
+
+const MIN_NAME_LENGTH: usize = 3;
+const MAX_NAME_LENGTH: usize = 20;
+
+/// How long an emailed login link can be used for
+const LOGIN_LINK_LIFETIME_MINS: u64 = 30;
+/// Stops a player from flooding an inbox with login links
+const MAX_LOGIN_LINKS_PER_HOUR: i64 = 5;
 
 #[derive(Clone, Hash, PartialEq, Eq)]
 pub struct AuthedTruncateToken {
@@ -111,8 +118,8 @@ pub struct UnreadChangelog {
 }
 
 pub struct LoginResponse {
-    pub player_id: Uuid,
     pub authed: AuthedTruncateToken,
+    pub player_name: Option<String>,
     pub unread_changelogs: Vec<UnreadChangelog>,
 }
 
@@ -134,12 +141,12 @@ pub async fn login(
 
     struct LoggedInPlayer {
         player_id: Uuid,
-        last_known_changelog: Option<time::OffsetDateTime>,
+        player_name: Option<String>,
     }
 
     let Some(login) = sqlx::query_as!(
         LoggedInPlayer,
-        "SELECT player_id, last_known_changelog FROM players WHERE player_id = $1",
+        "SELECT player_id, player_name FROM players WHERE player_id = $1",
         player_id
     )
     .fetch_optional(pool)
@@ -177,8 +184,8 @@ pub async fn login(
     .await?;
 
     Ok(LoginResponse {
-        player_id,
         authed,
+        player_name: login.player_name,
         unread_changelogs,
     })
 }
@@ -248,3 +255,204 @@ pub async fn mark_most_changelogs_read(
 
     Ok(())
 }
+
+/// Lets a player claim a display name, which no other player can then use
+pub async fn claim_name(
+    server_state: &ServerState,
+    authed: AuthedTruncateToken,
+    player_name: String,
+) -> Result<String, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let player_name = player_name.trim().to_string();
+    let valid_length = (MIN_NAME_LENGTH..=MAX_NAME_LENGTH).contains(&player_name.chars().count());
+    let valid_chars = player_name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid_length || !valid_chars {
+        return Err(TruncateServerError::InvalidName);
+    }
+
+    let claimed = sqlx::query!(
+        "UPDATE players SET player_name = $2 WHERE player_id = $1",
+        authed.player(),
+        player_name
+    )
+    .execute(pool)
+    .await;
+
+    match claimed {
+        Ok(_) => Ok(player_name),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            Err(TruncateServerError::NameTaken)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LoginLinkClaims {
+    link_id: Uuid,
+}
+
+/// Emails a single-use link that logs whoever opens it into the account owning that email.
+/// If no account owns the email yet, opening the link will attach the email to the requesting player.
+pub async fn request_login_link(
+    server_state: &ServerState,
+    authed: AuthedTruncateToken,
+    email: String,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let email = email.trim().to_lowercase();
+    let valid_email = match email.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && domain.contains('.')
+                && !domain.contains('@')
+                && !email.contains(char::is_whitespace)
+                && email.len() <= 255
+        }
+        None => false,
+    };
+    if !valid_email {
+        return Err(TruncateServerError::InvalidEmail);
+    }
+
+    let recent_links = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM login_links
+        WHERE requested_by = $1 AND created_at > CURRENT_TIMESTAMP - INTERVAL '1 hour'"#,
+        authed.player()
+    )
+    .fetch_one(pool)
+    .await?;
+    if recent_links >= MAX_LOGIN_LINKS_PER_HOUR {
+        return Err(TruncateServerError::TooManyLoginLinks);
+    }
+
+    let link_id = sqlx::query_scalar!(
+        "INSERT INTO login_links (requested_by, player_email) VALUES ($1, $2) RETURNING link_id",
+        authed.player(),
+        email
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let claims = Claims::with_custom_claims(
+        LoginLinkClaims { link_id },
+        Duration::from_mins(LOGIN_LINK_LIFETIME_MINS),
+    );
+    let token = server_state
+        .jwt_key
+        .authenticate(claims)
+        .expect("Claims should be serializable");
+    let link = format!("{}/?j=LOGIN:{token}", server_state.public_url);
+
+    let email = Email {
+        to: email,
+        subject: "Log in to Truncate".to_string(),
+        body: format!(
+            "Open this link to log in to Truncate and bring your daily puzzles with you:\n\n{link}\n\n\
+            The link works once, and expires in {LOGIN_LINK_LIFETIME_MINS} minutes."
+        ),
+    };
+
+    // Mailers may block on IO, so keep them off the async workers
+    let mailer = server_state.mailer.clone();
+    tokio::task::spawn_blocking(move || mailer.send(email))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+/// Uses up a login link, returning the account it logs into.
+/// Daily puzzles played by an anonymous redeemer are carried over to that account,
+/// except for days the account has already played.
+pub async fn redeem_login_link(
+    server_state: &ServerState,
+    redeemer: Option<AuthedTruncateToken>,
+    link_token: String,
+) -> Result<LoginResponse, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let Ok(claims) = server_state
+        .jwt_key
+        .verify_token::<LoginLinkClaims>(&link_token, None)
+    else {
+        return Err(TruncateServerError::InvalidLoginLink);
+    };
+
+    let mut transaction = pool.begin().await?;
+
+    let Some(link) = sqlx::query!(
+        "UPDATE login_links SET redeemed_at = CURRENT_TIMESTAMP
+        WHERE link_id = $1 AND redeemed_at IS NULL
+        RETURNING requested_by, player_email",
+        claims.custom.link_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    else {
+        return Err(TruncateServerError::InvalidLoginLink);
+    };
+
+    let existing_account = sqlx::query_scalar!(
+        "SELECT player_id FROM players WHERE LOWER(player_email) = $1",
+        link.player_email
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let player_id = match existing_account {
+        Some(player_id) => player_id,
+        None => {
+            sqlx::query!(
+                "UPDATE players SET player_email = $2 WHERE player_id = $1",
+                link.requested_by,
+                link.player_email
+            )
+            .execute(&mut *transaction)
+            .await?;
+            link.requested_by
+        }
+    };
+
+    if let Some(redeemer) = redeemer.filter(|r| r.player() != player_id) {
+        sqlx::query!(
+            "UPDATE daily_puzzle_results SET player_id = $2
+            WHERE player_id = $1
+                AND daily_puzzle NOT IN (
+                    SELECT daily_puzzle FROM daily_puzzle_results WHERE player_id = $2
+                )
+                AND EXISTS (
+                    SELECT 1 FROM players WHERE player_id = $1 AND player_email IS NULL
+                )",
+            redeemer.player(),
+            player_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    let player_name = sqlx::query_scalar!(
+        "SELECT player_name FROM players WHERE player_id = $1",
+        player_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    let unread_changelogs = get_unreads(pool, player_id).await?;
+
+    Ok(LoginResponse {
+        authed: get_player_token(server_state, player_id),
+        player_name,
+        unread_changelogs,
+    })
+}
@@ -14,7 +14,8 @@ pub struct AttemptRecord {
     attempt_id: Uuid,
     attempt_number: i32,
     sequence_of_moves: String,
-    won: bool,
+    hints_used: i32,
+    takebacks_used: i32,
 }
 pub struct DailyPuzzleRecord {
     result_id: Uuid,
@@ -48,6 +49,8 @@ pub async fn load_attempt(
                 puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
                 attempt: a.attempt_number.try_into().unwrap_or_default(),
                 current_moves: best,
+                hints: a.hints_used.try_into().unwrap_or_default(),
+                takebacks: a.takebacks_used.try_into().unwrap_or_default(),
             })
         })
         .flatten();
@@ -63,6 +66,8 @@ pub async fn load_attempt(
             puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
             attempt: attempt_record.attempt_number.try_into().unwrap_or_default(),
             current_moves,
+            hints: attempt_record.hints_used.try_into().unwrap_or_default(),
+            takebacks: attempt_record.takebacks_used.try_into().unwrap_or_default(),
         },
         best_record,
     )))
@@ -116,6 +121,8 @@ pub async fn get_or_create_latest_attempt(
             puzzle_day: daily_puzzle.try_into().unwrap_or_default(),
             attempt: latest_attempt.attempt_number.try_into().unwrap_or_default(),
             current_moves,
+            hints: latest_attempt.hints_used.try_into().unwrap_or_default(),
+            takebacks: latest_attempt.takebacks_used.try_into().unwrap_or_default(),
         },
         latest_attempt,
     ))
@@ -153,7 +160,7 @@ async fn get_latest_attempt_for_day(
 
     sqlx::query_as!(
         AttemptRecord,
-        "SELECT attempt_id, sequence_of_moves, attempt_number, won FROM daily_puzzle_attempts WHERE result_id = $1 ORDER BY attempt_number DESC LIMIT 1",
+        "SELECT attempt_id, sequence_of_moves, attempt_number, hints_used, takebacks_used FROM daily_puzzle_attempts WHERE result_id = $1 ORDER BY attempt_number DESC LIMIT 1",
         result_id
     )
     .fetch_optional(pool)
@@ -171,7 +178,7 @@ async fn get_best_attempt_for_day(
 
     sqlx::query_as!(
         AttemptRecord,
-        "SELECT attempt_id, sequence_of_moves, attempt_number, won FROM daily_puzzle_attempts WHERE result_id = $1 AND won = true ORDER BY move_count ASC LIMIT 1",
+        "SELECT attempt_id, sequence_of_moves, attempt_number, hints_used, takebacks_used FROM daily_puzzle_attempts WHERE result_id = $1 AND won = true ORDER BY move_count ASC LIMIT 1",
         result_id
     )
     .fetch_optional(pool)
@@ -179,6 +186,26 @@ async fn get_best_attempt_for_day(
     .map_err(Into::into)
 }
 
+async fn get_attempt_for_day(
+    server_state: &ServerState,
+    result_id: Uuid,
+    attempt_number: i32,
+) -> Result<Option<AttemptRecord>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query_as!(
+        AttemptRecord,
+        "SELECT attempt_id, sequence_of_moves, attempt_number, hints_used, takebacks_used FROM daily_puzzle_attempts WHERE result_id = $1 AND attempt_number = $2",
+        result_id,
+        attempt_number
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
 async fn create_new_attempt(
     server_state: &ServerState,
     result_id: Uuid,
@@ -205,17 +232,22 @@ async fn create_new_attempt(
         attempt_id: new_attempt.attempt_id,
         attempt_number: new_attempt_number,
         sequence_of_moves: String::new(),
-        won: false,
+        hints_used: 0,
+        takebacks_used: 0,
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn persist_moves(
     server_state: &ServerState,
     player: AuthedTruncateToken,
     daily_puzzle: i32,
+    attempt_number: Option<i32>,
     human_player: i32,
     moves: Vec<Move>,
     won: bool,
+    hints: u32,
+    takebacks: u32,
 ) -> Result<(), TruncateServerError> {
     let Some(pool) = &server_state.truncate_db else {
         return Err(TruncateServerError::DatabaseOffline);
@@ -225,9 +257,32 @@ pub async fn persist_moves(
         get_or_create_latest_attempt(server_state, player.clone(), daily_puzzle, human_player)
             .await?;
 
+    // Attempts played offline can arrive after the player has moved on to a later attempt
+    if let Some(attempt_number) = attempt_number.filter(|n| *n != attempt.attempt_number) {
+        let day_record = get_day_record(server_state, player.clone(), daily_puzzle)
+            .await?
+            .expect("Getting the latest attempt should have created the relevant day");
+        attempt =
+            match get_attempt_for_day(server_state, day_record.result_id, attempt_number).await? {
+                Some(attempt) => attempt,
+                None => create_new_attempt(server_state, day_record.result_id).await?,
+            };
+    }
+
     let packed_moves = pack_moves(&moves, 2);
 
-    if !packed_moves.starts_with(&attempt.sequence_of_moves) {
+    // Taking back a move is the only legitimate way to rewrite an attempt's history,
+    // and each new takeback can only undo the player's last move and the computer's reply.
+    let new_takebacks = (takebacks as i32 - attempt.takebacks_used).max(0) as usize;
+    let continues_history = match moves::packing::unpack_moves(&attempt.sequence_of_moves, 2) {
+        Ok(previous_moves) => {
+            let kept = previous_moves.len().saturating_sub(new_takebacks * 2);
+            moves.starts_with(&previous_moves[..kept])
+        }
+        Err(_) => false,
+    };
+
+    if !continues_history {
         // sacré bleu! somebody is trying to change history!
         // no sir, we will create a new attempt for these moves.
         let day_record = get_day_record(server_state, player, daily_puzzle)
@@ -252,11 +307,14 @@ pub async fn persist_moves(
 
     sqlx::query!(
         "UPDATE daily_puzzle_attempts 
-         SET sequence_of_moves = $1, move_count = $2, won = $3
-         WHERE attempt_id = $4",
+         SET sequence_of_moves = $1, move_count = $2, won = $3,
+             hints_used = GREATEST(hints_used, $4), takebacks_used = GREATEST(takebacks_used, $5)
+         WHERE attempt_id = $6",
         packed_moves,
         human_moves as i32,
         won,
+        hints as i32,
+        takebacks as i32,
         attempt.attempt_id
     )
     .execute(pool)
@@ -279,6 +337,8 @@ pub async fn load_stats(
         attempt_ids: Option<Vec<Uuid>>,
         move_counts: Option<Vec<i32>>,
         wins: Option<Vec<bool>>,
+        hints: Option<Vec<i32>>,
+        takebacks: Option<Vec<i32>>,
     }
 
     let results = sqlx::query_as!(
@@ -287,7 +347,9 @@ pub async fn load_stats(
             dpr.daily_puzzle, 
             ARRAY_AGG(dpa.attempt_id ORDER BY dpa.attempt_number) AS attempt_ids,
             ARRAY_AGG(dpa.move_count ORDER BY dpa.attempt_number) AS move_counts,
-            ARRAY_AGG(dpa.won ORDER BY dpa.attempt_number) AS wins
+            ARRAY_AGG(dpa.won ORDER BY dpa.attempt_number) AS wins,
+            ARRAY_AGG(dpa.hints_used ORDER BY dpa.attempt_number) AS hints,
+            ARRAY_AGG(dpa.takebacks_used ORDER BY dpa.attempt_number) AS takebacks
         FROM 
             daily_puzzle_results dpr
         JOIN 
@@ -309,10 +371,14 @@ pub async fn load_stats(
             .into_iter()
             .zip(day.wins.unwrap_or_default().into_iter())
             .zip(day.attempt_ids.unwrap_or_default().into_iter())
-            .map(|((moves, won), id)| DailyAttempt {
+            .zip(day.hints.unwrap_or_default())
+            .zip(day.takebacks.unwrap_or_default())
+            .map(|((((moves, won), id), hints), takebacks)| DailyAttempt {
                 id: id.to_string(),
                 moves: moves.try_into().unwrap_or_default(),
                 won,
+                hints: hints.try_into().unwrap_or_default(),
+                takebacks: takebacks.try_into().unwrap_or_default(),
             })
             .collect::<Vec<_>>();
 
@@ -340,6 +406,8 @@ pub async fn load_exact_attempt(
         attempt_number: i32,
         sequence_of_moves: String,
         daily_puzzle: i32,
+        hints_used: i32,
+        takebacks_used: i32,
     }
 
     let record = sqlx::query_as!(
@@ -347,6 +415,8 @@ pub async fn load_exact_attempt(
         "SELECT 
             dpa.sequence_of_moves,
             dpa.attempt_number,
+            dpa.hints_used,
+            dpa.takebacks_used,
             dpr.daily_puzzle
         FROM
             daily_puzzle_attempts dpa
@@ -373,5 +443,7 @@ pub async fn load_exact_attempt(
         puzzle_day: attempt_record.daily_puzzle.try_into().unwrap_or_default(),
         attempt: attempt_record.attempt_number.try_into().unwrap_or_default(),
         current_moves,
+        hints: attempt_record.hints_used.try_into().unwrap_or_default(),
+        takebacks: attempt_record.takebacks_used.try_into().unwrap_or_default(),
     }))
 }
@@ -0,0 +1,205 @@
+use truncate_core::{
+    game::Game,
+    locale::Locale,
+    moves::packing::{pack_moves, unpack_moves},
+};
+use uuid::Uuid;
+
+use crate::{errors::TruncateServerError, game_state::GameSnapshot, ServerState};
+
+struct LiveGameRecord {
+    room_code: String,
+    instance_id: Uuid,
+    snapshot_version: i64,
+    effective_day: i32,
+    player_count: i32,
+    game_state: serde_json::Value,
+    sequence_of_moves: String,
+    rule_settings: Option<serde_json::Value>,
+    player_ids: serde_json::Value,
+    starting_game: Option<serde_json::Value>,
+    locale: String,
+}
+
+impl LiveGameRecord {
+    fn into_snapshot(self) -> Option<GameSnapshot> {
+        let player_count = self.player_count.try_into().unwrap_or_default();
+
+        let core_game = match serde_json::from_value::<Game>(self.game_state) {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("Couldn't restore room {}: {e}", self.room_code);
+                return None;
+            }
+        };
+
+        // The game state is authoritative, so a broken move list only costs us the history.
+        let moves = unpack_moves(&self.sequence_of_moves, player_count).unwrap_or_default();
+        // Likewise, games we can't read accounts or a starting point for just won't be recorded.
+        let player_ids = serde_json::from_value(self.player_ids).unwrap_or_default();
+        let rule_settings = self
+            .rule_settings
+            .and_then(|settings| serde_json::from_value(settings).ok())
+            .unwrap_or_default();
+        let starting_game = self
+            .starting_game
+            .and_then(|game| serde_json::from_value(game).ok());
+
+        Some(GameSnapshot {
+            room_code: self.room_code,
+            instance_id: self.instance_id,
+            snapshot_version: self.snapshot_version,
+            effective_day: self.effective_day.try_into().unwrap_or_default(),
+            player_count,
+            core_game,
+            rule_settings,
+            moves,
+            player_ids,
+            starting_game,
+            locale: Locale::from_tag(&self.locale).unwrap_or_default(),
+        })
+    }
+}
+
+/// Stores the latest state of a live game, replacing any older snapshot of it.
+pub async fn persist_game(
+    server_state: &ServerState,
+    snapshot: GameSnapshot,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let game_state = serde_json::to_value(&snapshot.core_game)?;
+    let sequence_of_moves = pack_moves(&snapshot.moves, snapshot.player_count);
+    let rule_settings = serde_json::to_value(snapshot.rule_settings)?;
+    let player_ids = serde_json::to_value(&snapshot.player_ids)?;
+    let starting_game = snapshot
+        .starting_game
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()?;
+
+    // Snapshots can arrive out of order when players act at the same time,
+    // so only newer versions of the same game may overwrite a row.
+    // A new game can only take over a recycled room code once the old game is over.
+    let result = sqlx::query!(
+        "INSERT INTO live_games (
+            room_code,
+            instance_id,
+            snapshot_version,
+            effective_day,
+            player_count,
+            game_state,
+            sequence_of_moves,
+            finished,
+            player_ids,
+            starting_game,
+            rule_settings,
+            locale
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        ON CONFLICT (room_code) DO UPDATE SET
+            instance_id = EXCLUDED.instance_id,
+            snapshot_version = EXCLUDED.snapshot_version,
+            effective_day = EXCLUDED.effective_day,
+            player_count = EXCLUDED.player_count,
+            game_state = EXCLUDED.game_state,
+            sequence_of_moves = EXCLUDED.sequence_of_moves,
+            finished = EXCLUDED.finished,
+            player_ids = EXCLUDED.player_ids,
+            starting_game = EXCLUDED.starting_game,
+            rule_settings = EXCLUDED.rule_settings,
+            locale = EXCLUDED.locale,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE CASE WHEN live_games.instance_id = EXCLUDED.instance_id
+            THEN live_games.snapshot_version < EXCLUDED.snapshot_version
+            ELSE live_games.finished OR live_games.updated_at < CURRENT_TIMESTAMP - INTERVAL '7 days'
+        END;",
+        snapshot.room_code,
+        snapshot.instance_id,
+        snapshot.snapshot_version,
+        snapshot.effective_day as i32,
+        snapshot.player_count as i32,
+        game_state,
+        sequence_of_moves,
+        snapshot.core_game.winner.is_some(),
+        player_ids,
+        starting_game,
+        rule_settings,
+        snapshot.locale.tag()
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        eprintln!(
+            "Dropped snapshot {} of room {}, as the database holds a newer or different game",
+            snapshot.snapshot_version, snapshot.room_code
+        );
+    }
+
+    Ok(())
+}
+
+/// Loads the unfinished game being played under a room code, if there is one.
+pub async fn load_game(
+    server_state: &ServerState,
+    room_code: &str,
+) -> Result<Option<GameSnapshot>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let record = sqlx::query_as!(
+        LiveGameRecord,
+        "SELECT room_code, instance_id, snapshot_version, effective_day, player_count, game_state, sequence_of_moves, rule_settings, player_ids, starting_game, locale
+        FROM live_games WHERE room_code = $1 AND NOT finished",
+        room_code
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record.and_then(LiveGameRecord::into_snapshot))
+}
+
+/// Loads all unfinished games that players could still hold valid tokens for.
+pub async fn load_live_games(
+    server_state: &ServerState,
+) -> Result<Vec<GameSnapshot>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let records = sqlx::query_as!(
+        LiveGameRecord,
+        "SELECT room_code, instance_id, snapshot_version, effective_day, player_count, game_state, sequence_of_moves, rule_settings, player_ids, starting_game, locale
+        FROM live_games WHERE NOT finished AND updated_at > CURRENT_TIMESTAMP - INTERVAL '7 days'"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .filter_map(LiveGameRecord::into_snapshot)
+        .collect())
+}
+
+/// Forgets games that have been dropped from memory, so that they can't be restored
+/// and their room codes can be taken over by new games straight away.
+pub async fn delete_games(
+    server_state: &ServerState,
+    room_codes: &[String],
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    sqlx::query!(
+        "DELETE FROM live_games WHERE room_code = ANY($1)",
+        room_codes
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use truncate_core::{
+    game::Game,
+    messages::{MatchHistory, MatchHistoryEntry, MultiplayerReplayMessage},
+    moves::packing::{pack_moves, unpack_moves},
+};
+use uuid::Uuid;
+
+use crate::{
+    errors::TruncateServerError,
+    game_state::GameSnapshot,
+    ratings::{self, DEFAULT_RATING},
+    ServerState,
+};
+
+use super::accounts::AuthedTruncateToken;
+
+/// How many of their most recent games a player can browse
+const MATCH_HISTORY_LENGTH: i64 = 50;
+
+/// Records a finished multiplayer game, updating the ratings of its players if they were both logged in.
+/// Each game is only ever recorded once, so this is safe to call with every snapshot of a finished game.
+pub async fn record_finished_game(
+    server_state: &ServerState,
+    snapshot: &GameSnapshot,
+) -> Result<(), TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let (Some(winner), Some(started_at), Some(starting_game)) = (
+        snapshot.core_game.winner,
+        snapshot.core_game.started_at,
+        &snapshot.starting_game,
+    ) else {
+        return Ok(());
+    };
+
+    let mut transaction = pool.begin().await?;
+
+    let recorded = sqlx::query!(
+        "INSERT INTO multiplayer_games (
+            game_id,
+            room_code,
+            rules_generation,
+            player_count,
+            starting_game,
+            sequence_of_moves,
+            winner,
+            started_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, to_timestamp($8))
+        ON CONFLICT (game_id) DO NOTHING
+        RETURNING game_id;",
+        snapshot.instance_id,
+        snapshot.room_code,
+        snapshot.core_game.rules.generation.map(|gen| gen as i32),
+        snapshot.player_count as i32,
+        serde_json::to_value(starting_game)?,
+        pack_moves(&snapshot.moves, snapshot.player_count),
+        winner as i32,
+        started_at as f64
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    if recorded.is_none() {
+        return Ok(());
+    }
+
+    let mut ratings: Vec<(Option<i32>, Option<i32>)> = vec![(None, None); snapshot.player_count];
+
+    // Only one-on-one games between two different accounts are rated
+    if let [Some(first), Some(second)] = snapshot.player_ids[..] {
+        if first != second {
+            let records = sqlx::query!(
+                "SELECT player_id, rating, rated_games FROM players
+                WHERE player_id = $1 OR player_id = $2
+                FOR UPDATE;",
+                first,
+                second
+            )
+            .fetch_all(&mut *transaction)
+            .await?;
+
+            let find = |id: Uuid| records.iter().find(|r| r.player_id == id);
+            if let (Some(first), Some(second)) = (find(first), find(second)) {
+                for (player_index, (player, opponent)) in
+                    [(first, second), (second, first)].into_iter().enumerate()
+                {
+                    let rating_after = ratings::updated_rating(
+                        player.rating,
+                        player.rated_games,
+                        opponent.rating,
+                        winner == player_index,
+                    );
+                    ratings[player_index] = (Some(player.rating), Some(rating_after));
+
+                    sqlx::query!(
+                        "UPDATE players SET rating = $1, rated_games = rated_games + 1
+                        WHERE player_id = $2;",
+                        rating_after,
+                        player.player_id
+                    )
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+
+                sqlx::query!(
+                    "UPDATE multiplayer_games SET rated = true WHERE game_id = $1;",
+                    snapshot.instance_id
+                )
+                .execute(&mut *transaction)
+                .await?;
+            }
+        }
+    }
+
+    for (player_number, (player, (rating_before, rating_after))) in
+        snapshot.core_game.players.iter().zip(ratings).enumerate()
+    {
+        sqlx::query!(
+            "INSERT INTO multiplayer_game_players (
+                game_id,
+                player_number,
+                player_id,
+                player_name,
+                rating_before,
+                rating_after
+            ) VALUES ($1, $2, $3, $4, $5, $6);",
+            snapshot.instance_id,
+            player_number as i32,
+            snapshot.player_ids.get(player_number).copied().flatten(),
+            player.name,
+            rating_before,
+            rating_after
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn get_rating(
+    server_state: &ServerState,
+    player: &AuthedTruncateToken,
+) -> Result<i32, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let rating = sqlx::query_scalar!(
+        "SELECT rating FROM players WHERE player_id = $1;",
+        player.player()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(rating.unwrap_or(DEFAULT_RATING))
+}
+
+/// Returns a player's current rating along with their most recent multiplayer games
+pub async fn load_match_history(
+    server_state: &ServerState,
+    player: AuthedTruncateToken,
+) -> Result<MatchHistory, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let rating = get_rating(server_state, &player).await?;
+
+    let records = sqlx::query!(
+        r#"SELECT
+            g.game_id,
+            EXTRACT(EPOCH FROM g.finished_at)::BIGINT AS "finished_at!",
+            EXTRACT(EPOCH FROM g.finished_at - g.started_at)::BIGINT AS "duration_secs!",
+            g.winner,
+            me.player_number,
+            me.rating_before,
+            me.rating_after,
+            ARRAY(
+                SELECT them.player_name FROM multiplayer_game_players them
+                WHERE them.game_id = g.game_id AND them.player_number <> me.player_number
+                ORDER BY them.player_number
+            ) AS "opponents!"
+        FROM
+            multiplayer_game_players me
+        JOIN
+            multiplayer_games g ON g.game_id = me.game_id
+        WHERE
+            me.player_id = $1
+        ORDER BY
+            g.finished_at DESC
+        LIMIT $2;"#,
+        player.player(),
+        MATCH_HISTORY_LENGTH
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let games = records
+        .into_iter()
+        .map(|game| MatchHistoryEntry {
+            game_id: game.game_id.to_string(),
+            finished_at: game.finished_at.try_into().unwrap_or_default(),
+            duration_secs: game.duration_secs.try_into().unwrap_or_default(),
+            player_number: game.player_number.try_into().unwrap_or_default(),
+            opponents: game.opponents,
+            won: game.winner == game.player_number,
+            rating_change: game
+                .rating_before
+                .zip(game.rating_after)
+                .map(|(before, after)| after - before),
+        })
+        .collect();
+
+    Ok(MatchHistory { rating, games })
+}
+
+/// Loads a recorded multiplayer game, to be watched from the perspective of the given player if they played in it
+pub async fn load_replay(
+    server_state: &ServerState,
+    game_id: Uuid,
+    viewer: Option<AuthedTruncateToken>,
+) -> Result<Option<MultiplayerReplayMessage>, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let Some(record) = sqlx::query!(
+        "SELECT starting_game, sequence_of_moves, player_count FROM multiplayer_games
+        WHERE game_id = $1;",
+        game_id
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let player_number = match viewer {
+        Some(viewer) => sqlx::query_scalar!(
+            "SELECT player_number FROM multiplayer_game_players
+            WHERE game_id = $1 AND player_id = $2
+            ORDER BY player_number LIMIT 1;",
+            game_id,
+            viewer.player()
+        )
+        .fetch_optional(pool)
+        .await?
+        .unwrap_or_default(),
+        None => 0,
+    };
+
+    let starting_game: Game = serde_json::from_value(record.starting_game)?;
+    let Ok(moves) = unpack_moves(
+        &record.sequence_of_moves,
+        record.player_count.try_into().unwrap_or_default(),
+    ) else {
+        return Err(TruncateServerError::BadRequest);
+    };
+
+    Ok(Some(MultiplayerReplayMessage {
+        game_id: game_id.to_string(),
+        starting_game,
+        moves,
+        player_number: player_number.try_into().unwrap_or_default(),
+    }))
+}
@@ -1,3 +1,6 @@
 pub mod accounts;
 pub mod daily;
 pub mod events;
+pub mod games;
+pub mod history;
+pub mod nonces;
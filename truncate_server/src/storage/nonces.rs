@@ -0,0 +1,50 @@
+use truncate_core::messages::Nonce;
+
+use crate::{errors::TruncateServerError, ServerState};
+
+use super::accounts::AuthedTruncateToken;
+
+/// Records that a player's message has been handled, returning false if it already had been
+pub async fn burn_nonce(
+    server_state: &ServerState,
+    player: &AuthedTruncateToken,
+    nonce: &Nonce,
+) -> Result<bool, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let burned = sqlx::query!(
+        "INSERT INTO burned_nonces (player_id, generated_at, nonce_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING;",
+        player.player(),
+        nonce.generated_at as i64,
+        // Postgres has no unsigned integers, so these are stored by their bits
+        nonce.id as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(burned.rows_affected() == 1)
+}
+
+/// Forgets nonces generated more than the given number of minutes ago
+pub async fn cleanup_nonces(
+    server_state: &ServerState,
+    minutes: u64,
+) -> Result<u64, TruncateServerError> {
+    let Some(pool) = &server_state.truncate_db else {
+        return Err(TruncateServerError::DatabaseOffline);
+    };
+
+    let cutoff = truncate_core::game::now().saturating_sub(60 * minutes);
+    let cleaned = sqlx::query!(
+        "DELETE FROM burned_nonces WHERE generated_at < $1;",
+        cutoff as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(cleaned.rows_affected())
+}